@@ -0,0 +1,1185 @@
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File, Metadata};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufWriter, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use chrono::{DateTime, Datelike, Local, TimeZone, Timelike};
+use glob::Pattern;
+use crate::error::{FileError, Operation};
+use crate::models::{ConflictPolicy, FileEntry};
+use crate::operations;
+
+/// Archive formats that can be created.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+    TarXz,
+    TarZst,
+}
+
+impl ArchiveFormat {
+    pub const ALL: [ArchiveFormat; 5] = [
+        ArchiveFormat::Zip,
+        ArchiveFormat::Tar,
+        ArchiveFormat::TarGz,
+        ArchiveFormat::TarXz,
+        ArchiveFormat::TarZst,
+    ];
+
+    /// File name extension, without the leading dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::Tar => "tar",
+            ArchiveFormat::TarGz => "tar.gz",
+            ArchiveFormat::TarXz => "tar.xz",
+            ArchiveFormat::TarZst => "tar.zst",
+        }
+    }
+
+    /// Lowest and highest compression level; plain tar has none.
+    pub fn levels(self) -> Option<(u32, u32)> {
+        match self {
+            ArchiveFormat::Tar => None,
+            ArchiveFormat::Zip | ArchiveFormat::TarGz | ArchiveFormat::TarXz => Some((0, 9)),
+            ArchiveFormat::TarZst => Some((1, 19)),
+        }
+    }
+
+    pub fn default_level(self) -> u32 {
+        match self {
+            ArchiveFormat::Tar => 0,
+            ArchiveFormat::Zip | ArchiveFormat::TarGz | ArchiveFormat::TarXz => 6,
+            ArchiveFormat::TarZst => 3,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompressOptions {
+    pub format: ArchiveFormat,
+    pub level: u32,
+    /// Keep permission bits and owners; otherwise files get 644 and folders 755.
+    pub preserve_permissions: bool,
+    /// Store symbolic links as links instead of what they point to.
+    pub preserve_symlinks: bool,
+    /// Globs such as `*.o` or `target`; an item whose name or path inside
+    /// the archive matches is left out together with its contents.
+    pub exclude: Vec<String>,
+}
+
+impl CompressOptions {
+    pub fn new(format: ArchiveFormat) -> Self {
+        Self { format, level: format.default_level(), preserve_permissions: true, preserve_symlinks: true, exclude: Vec::new() }
+    }
+}
+
+/// How far a long archive operation has got.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Progress {
+    pub done_bytes: u64,
+    pub total_bytes: u64,
+    /// The item being worked on.
+    pub current: PathBuf,
+}
+
+/// An item to put into an archive.
+struct Member {
+    path: PathBuf,
+    /// Path inside the archive, with `/` separators.
+    name: String,
+    metadata: Metadata,
+}
+
+fn cancelled(operation: Operation, path: &Path) -> FileError {
+    FileError::invalid(operation, path, "cancelled")
+}
+
+/// A failed read or write, which `ProgressReader` also uses to stop on cancel.
+fn io_error(operation: Operation, path: &Path, error: &io::Error, cancel: &AtomicBool) -> FileError {
+    if cancel.load(Ordering::Relaxed) { cancelled(operation, path) } else { FileError::io(operation, path, error) }
+}
+
+/// Everything under `sources`, named relative to each source's parent.
+fn collect_members(sources: &[PathBuf], options: &CompressOptions, skip: &[&Path]) -> Result<Vec<Member>, FileError> {
+    let exclude: Vec<Pattern> = options.exclude.iter()
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| Pattern::new(p.trim()).map_err(|e| FileError::invalid(Operation::Compress, Path::new(p), e.to_string())))
+        .collect::<Result<_, _>>()?;
+
+    let mut members = Vec::new();
+    let mut visited = HashSet::new();
+    for source in sources {
+        let name = source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        walk(source, name, options, &exclude, skip, &mut visited, &mut members)?;
+    }
+    Ok(members)
+}
+
+fn walk(path: &Path, name: String, options: &CompressOptions, exclude: &[Pattern], skip: &[&Path], visited: &mut HashSet<PathBuf>, members: &mut Vec<Member>) -> Result<(), FileError> {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    if skip.contains(&path) || exclude.iter().any(|p| p.matches(&file_name) || p.matches(&name)) {
+        return Ok(());
+    }
+    let metadata = if options.preserve_symlinks { fs::symlink_metadata(path) } else { fs::metadata(path) }
+        .map_err(|e| FileError::io(Operation::Read, path, &e))?;
+
+    let is_dir = metadata.is_dir();
+    members.push(Member { path: path.to_path_buf(), name: name.clone(), metadata });
+    if !is_dir {
+        return Ok(());
+    }
+    // Followed links can lead back up the tree
+    if let Ok(real) = path.canonicalize()
+        && !visited.insert(real)
+    {
+        return Ok(());
+    }
+    let mut entries: Vec<_> = fs::read_dir(path)
+        .map_err(|e| FileError::io(Operation::Read, path, &e))?
+        .filter_map(Result::ok)
+        .collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let child = format!("{}/{}", name, entry.file_name().to_string_lossy());
+        walk(&entry.path(), child, options, exclude, skip, visited, members)?;
+    }
+    Ok(())
+}
+
+fn default_mode(metadata: &Metadata) -> u32 {
+    if metadata.is_dir() || metadata.permissions().mode() & 0o111 != 0 { 0o755 } else { 0o644 }
+}
+
+/// Reads a file while counting bytes into `progress` and watching `cancel`.
+struct ProgressReader<'a, R> {
+    inner: R,
+    progress: &'a mut Progress,
+    report: &'a mut dyn FnMut(&Progress),
+    cancel: &'a AtomicBool,
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.cancel.load(Ordering::Relaxed) {
+            return Err(io::Error::other("cancelled"));
+        }
+        let n = self.inner.read(buf)?;
+        self.progress.done_bytes += n as u64;
+        (self.report)(self.progress);
+        Ok(n)
+    }
+}
+
+/// Packs `sources` into a new archive at `destination`. The archive is
+/// written under a temporary name and only appears once it is complete;
+/// `report` is called as data is read, and setting `cancel` stops early.
+pub fn create_archive(sources: &[PathBuf], destination: &Path, options: &CompressOptions, report: &mut dyn FnMut(&Progress), cancel: &AtomicBool) -> Result<(), FileError> {
+    if fs::symlink_metadata(destination).is_ok() {
+        return Err(FileError::kind_of(Operation::Compress, destination, io::ErrorKind::AlreadyExists));
+    }
+    let file_name = destination.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let partial = destination.with_file_name(format!(".{}.part", file_name));
+
+    let members = collect_members(sources, options, &[destination, &partial])?;
+    let mut progress = Progress {
+        total_bytes: members.iter().filter(|m| m.metadata.is_file()).map(|m| m.metadata.len()).sum(),
+        ..Progress::default()
+    };
+
+    let file = File::create(&partial).map_err(|e| FileError::io(Operation::Create, &partial, &e))?;
+    let result = write_archive(BufWriter::new(file), &members, options, &mut progress, report, cancel);
+    let result = result.and_then(|()| {
+        fs::rename(&partial, destination).map_err(|e| FileError::io(Operation::Rename, &partial, &e))
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    result
+}
+
+fn write_archive(file: BufWriter<File>, members: &[Member], options: &CompressOptions, progress: &mut Progress, report: &mut dyn FnMut(&Progress), cancel: &AtomicBool) -> Result<(), FileError> {
+    let level = options.level;
+    let finished = |result: io::Result<BufWriter<File>>, progress: &Progress| {
+        result.and_then(|mut file| file.flush()).map_err(|e| FileError::io(Operation::Write, &progress.current, &e))
+    };
+    match options.format {
+        ArchiveFormat::Zip => write_zip(file, members, options, progress, report, cancel),
+        ArchiveFormat::Tar => {
+            let file = write_tar(file, members, options, progress, report, cancel)?;
+            finished(Ok(file), progress)
+        }
+        ArchiveFormat::TarGz => {
+            let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::new(level));
+            let encoder = write_tar(encoder, members, options, progress, report, cancel)?;
+            finished(encoder.finish(), progress)
+        }
+        ArchiveFormat::TarXz => {
+            let encoder = xz2::write::XzEncoder::new(file, level);
+            let encoder = write_tar(encoder, members, options, progress, report, cancel)?;
+            finished(encoder.finish(), progress)
+        }
+        ArchiveFormat::TarZst => {
+            let encoder = zstd::Encoder::new(file, level as i32).map_err(|e| FileError::io(Operation::Compress, &progress.current, &e))?;
+            let encoder = write_tar(encoder, members, options, progress, report, cancel)?;
+            finished(encoder.finish(), progress)
+        }
+    }
+}
+
+/// Writes the tar stream and hands back the writer so a compressor can be
+/// finished.
+fn write_tar<W: Write>(writer: W, members: &[Member], options: &CompressOptions, progress: &mut Progress, report: &mut dyn FnMut(&Progress), cancel: &AtomicBool) -> Result<W, FileError> {
+    let mut builder = tar::Builder::new(writer);
+    for member in members {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(cancelled(Operation::Compress, &member.path));
+        }
+        progress.current = member.path.clone();
+        report(progress);
+
+        let error = |e: io::Error| io_error(Operation::Compress, &member.path, &e, cancel);
+        let mut header = tar::Header::new_gnu();
+        header.set_metadata_in_mode(&member.metadata, tar::HeaderMode::Complete);
+        if !options.preserve_permissions {
+            header.set_mode(default_mode(&member.metadata));
+            header.set_uid(0);
+            header.set_gid(0);
+        }
+
+        let file_type = member.metadata.file_type();
+        if file_type.is_symlink() {
+            let target = fs::read_link(&member.path).map_err(|e| FileError::io(Operation::Read, &member.path, &e))?;
+            builder.append_link(&mut header, &member.name, &target).map_err(error)?;
+        } else if file_type.is_dir() {
+            builder.append_data(&mut header, format!("{}/", member.name), io::empty()).map_err(error)?;
+        } else if file_type.is_file() {
+            let file = File::open(&member.path).map_err(|e| FileError::io(Operation::Read, &member.path, &e))?;
+            let reader = ProgressReader { inner: file, progress: &mut *progress, report: &mut *report, cancel };
+            builder.append_data(&mut header, &member.name, reader).map_err(error)?;
+        }
+        // Sockets, fifos and devices are left out
+    }
+    builder.into_inner().map_err(|e| FileError::io(Operation::Compress, &progress.current, &e))
+}
+
+fn zip_time(metadata: &Metadata) -> zip::DateTime {
+    let modified = DateTime::<Local>::from(metadata.modified().unwrap_or(std::time::SystemTime::UNIX_EPOCH));
+    zip::DateTime::from_date_and_time(
+        modified.year().clamp(1980, 2107) as u16,
+        modified.month() as u8,
+        modified.day() as u8,
+        modified.hour() as u8,
+        modified.minute() as u8,
+        modified.second().min(59) as u8,
+    ).unwrap_or_default()
+}
+
+fn write_zip(file: BufWriter<File>, members: &[Member], options: &CompressOptions, progress: &mut Progress, report: &mut dyn FnMut(&Progress), cancel: &AtomicBool) -> Result<(), FileError> {
+    use zip::write::SimpleFileOptions;
+    use zip::CompressionMethod;
+
+    let method = if options.level == 0 { CompressionMethod::Stored } else { CompressionMethod::Deflated };
+    let mut zip = zip::ZipWriter::new(file);
+    for member in members {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(cancelled(Operation::Compress, &member.path));
+        }
+        progress.current = member.path.clone();
+        report(progress);
+
+        let zip_error = |e: zip::result::ZipError| FileError::invalid(Operation::Compress, &member.path, e.to_string());
+        let mode = if options.preserve_permissions { member.metadata.mode() & 0o7777 } else { default_mode(&member.metadata) };
+        let mut file_options = SimpleFileOptions::default()
+            .compression_method(method)
+            .unix_permissions(mode)
+            .last_modified_time(zip_time(&member.metadata));
+        if method == CompressionMethod::Deflated {
+            file_options = file_options.compression_level(Some(options.level as i64));
+        }
+
+        let file_type = member.metadata.file_type();
+        if file_type.is_symlink() {
+            let target = fs::read_link(&member.path).map_err(|e| FileError::io(Operation::Read, &member.path, &e))?;
+            zip.add_symlink(&member.name, target.to_string_lossy(), file_options).map_err(zip_error)?;
+        } else if file_type.is_dir() {
+            zip.add_directory(&member.name, file_options).map_err(zip_error)?;
+        } else if file_type.is_file() {
+            let file_options = file_options.large_file(member.metadata.len() >= u32::MAX as u64);
+            zip.start_file(&member.name, file_options).map_err(zip_error)?;
+            let file = File::open(&member.path).map_err(|e| FileError::io(Operation::Read, &member.path, &e))?;
+            let mut reader = ProgressReader { inner: file, progress: &mut *progress, report: &mut *report, cancel };
+            io::copy(&mut reader, &mut zip).map_err(|e| io_error(Operation::Compress, &member.path, &e, cancel))?;
+        }
+    }
+    let mut file = zip.finish().map_err(|e| FileError::invalid(Operation::Compress, &progress.current, e.to_string()))?;
+    file.flush().map_err(|e| FileError::io(Operation::Write, &progress.current, &e))
+}
+
+/// Compression around a tar stream or a single file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+/// Archive types that can be read, recognised by their file name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar(Compression),
+    /// One compressed file, such as `notes.txt.gz`.
+    Single(Compression),
+}
+
+// Longer suffixes first so `.tar.gz` wins over `.gz`
+const SUFFIXES: [(&str, ArchiveKind); 16] = [
+    (".tar.gz", ArchiveKind::Tar(Compression::Gzip)),
+    (".tar.bz2", ArchiveKind::Tar(Compression::Bzip2)),
+    (".tar.xz", ArchiveKind::Tar(Compression::Xz)),
+    (".tar.zst", ArchiveKind::Tar(Compression::Zstd)),
+    (".tgz", ArchiveKind::Tar(Compression::Gzip)),
+    (".tbz2", ArchiveKind::Tar(Compression::Bzip2)),
+    (".tbz", ArchiveKind::Tar(Compression::Bzip2)),
+    (".txz", ArchiveKind::Tar(Compression::Xz)),
+    (".tzst", ArchiveKind::Tar(Compression::Zstd)),
+    (".tar", ArchiveKind::Tar(Compression::None)),
+    (".zip", ArchiveKind::Zip),
+    (".jar", ArchiveKind::Zip),
+    (".gz", ArchiveKind::Single(Compression::Gzip)),
+    (".bz2", ArchiveKind::Single(Compression::Bzip2)),
+    (".xz", ArchiveKind::Single(Compression::Xz)),
+    (".zst", ArchiveKind::Single(Compression::Zstd)),
+];
+
+impl ArchiveKind {
+    pub fn detect(path: &Path) -> Option<Self> {
+        split_name(path).map(|(kind, _)| kind)
+    }
+
+    /// Whether the archive holds entries that can be browsed like a folder.
+    pub fn is_browsable(self) -> bool {
+        !matches!(self, ArchiveKind::Single(_))
+    }
+}
+
+fn split_name(path: &Path) -> Option<(ArchiveKind, String)> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    let lower = name.to_lowercase();
+    SUFFIXES.iter()
+        .find(|(suffix, _)| lower.len() > suffix.len() && lower.ends_with(suffix))
+        .map(|(suffix, kind)| (*kind, name[..name.len() - suffix.len()].to_string()))
+}
+
+/// The archive's name without its archive extensions: `photos` for
+/// `photos.tar.gz`.
+pub fn archive_stem(path: &Path) -> String {
+    match split_name(path) {
+        Some((_, stem)) => stem,
+        None => path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+    }
+}
+
+/// What an archive entry is.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MemberKind {
+    File,
+    Dir,
+    Symlink(PathBuf),
+    /// A second name for an earlier entry, given by its name in the archive.
+    HardLink(String),
+}
+
+/// An entry inside an archive.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArchiveMember {
+    /// Name as stored, with `/` separators.
+    pub name: String,
+    /// `name` as a relative path, or `None` when it is absolute or climbs
+    /// out with `..` and so must not be extracted.
+    pub path: Option<PathBuf>,
+    pub kind: MemberKind,
+    /// Uncompressed size.
+    pub size: u64,
+    pub modified: Option<DateTime<Local>>,
+    pub mode: Option<u32>,
+}
+
+impl ArchiveMember {
+    pub fn is_dir(&self) -> bool {
+        self.kind == MemberKind::Dir
+    }
+}
+
+/// `name` as a path that stays below the folder it is joined to.
+fn safe_path(name: &str) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+fn decoder<'a>(reader: impl Read + 'a, compression: Compression) -> io::Result<Box<dyn Read + 'a>> {
+    Ok(match compression {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+        Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(reader)),
+        Compression::Xz => Box::new(xz2::read::XzDecoder::new_multi_decoder(reader)),
+        Compression::Zstd => Box::new(zstd::Decoder::new(reader)?),
+    })
+}
+
+fn open(path: &Path) -> Result<File, FileError> {
+    File::open(path).map_err(|e| FileError::io(Operation::Read, path, &e))
+}
+
+fn open_zip(path: &Path) -> Result<zip::ZipArchive<File>, FileError> {
+    zip::ZipArchive::new(open(path)?).map_err(|e| FileError::invalid(Operation::Read, path, e.to_string()))
+}
+
+fn open_tar(path: &Path, compression: Compression) -> Result<tar::Archive<Box<dyn Read>>, FileError> {
+    let reader = decoder(io::BufReader::new(open(path)?), compression).map_err(|e| FileError::io(Operation::Read, path, &e))?;
+    Ok(tar::Archive::new(reader))
+}
+
+fn unsupported(path: &Path) -> FileError {
+    FileError::invalid(Operation::Read, path, "not a supported archive format")
+}
+
+fn tar_member<R: Read>(entry: &tar::Entry<R>) -> Option<ArchiveMember> {
+    let header = entry.header();
+    let name = String::from_utf8_lossy(&entry.path_bytes()).to_string();
+    let link = || entry.link_name_bytes().map(|l| String::from_utf8_lossy(&l).to_string()).unwrap_or_default();
+    let entry_type = header.entry_type();
+    let kind = if entry_type.is_dir() {
+        MemberKind::Dir
+    } else if entry_type.is_symlink() {
+        MemberKind::Symlink(PathBuf::from(link()))
+    } else if entry_type.is_hard_link() {
+        MemberKind::HardLink(link())
+    } else if entry_type.is_file() || entry_type.is_contiguous() {
+        MemberKind::File
+    } else {
+        // Devices, fifos and the like
+        return None;
+    };
+    Some(ArchiveMember {
+        path: safe_path(&name),
+        name,
+        kind,
+        size: entry.size(),
+        modified: header.mtime().ok().and_then(|t| Local.timestamp_opt(t as i64, 0).single()),
+        mode: header.mode().ok(),
+    })
+}
+
+fn zip_member(file: &mut zip::read::ZipFile) -> Result<ArchiveMember, io::Error> {
+    let name = file.name().to_string();
+    let kind = if file.is_dir() {
+        MemberKind::Dir
+    } else if file.is_symlink() {
+        let mut target = String::new();
+        file.read_to_string(&mut target)?;
+        MemberKind::Symlink(PathBuf::from(target))
+    } else {
+        MemberKind::File
+    };
+    let modified = file.last_modified().and_then(|t| {
+        Local.with_ymd_and_hms(t.year() as i32, t.month() as u32, t.day() as u32, t.hour() as u32, t.minute() as u32, t.second() as u32).single()
+    });
+    Ok(ArchiveMember { path: safe_path(&name), name, kind, size: file.size(), modified, mode: file.unix_mode() })
+}
+
+/// The entries of the archive at `path`. A single compressed file lists as
+/// one entry with an unknown size.
+pub fn list_archive(path: &Path) -> Result<Vec<ArchiveMember>, FileError> {
+    let read_error = |e: io::Error| FileError::io(Operation::Read, path, &e);
+    match ArchiveKind::detect(path).ok_or_else(|| unsupported(path))? {
+        ArchiveKind::Zip => {
+            let mut zip = open_zip(path)?;
+            (0..zip.len())
+                .map(|i| {
+                    let mut file = zip.by_index(i).map_err(|e| FileError::invalid(Operation::Read, path, e.to_string()))?;
+                    zip_member(&mut file).map_err(read_error)
+                })
+                .collect()
+        }
+        ArchiveKind::Tar(compression) => {
+            let mut archive = open_tar(path, compression)?;
+            let mut members = Vec::new();
+            for entry in archive.entries().map_err(read_error)? {
+                if let Some(member) = tar_member(&entry.map_err(read_error)?) {
+                    members.push(member);
+                }
+            }
+            Ok(members)
+        }
+        ArchiveKind::Single(_) => {
+            let name = archive_stem(path);
+            let modified = fs::metadata(path).and_then(|m| m.modified()).ok().map(DateTime::<Local>::from);
+            Ok(vec![ArchiveMember { path: safe_path(&name), name, kind: MemberKind::File, size: 0, modified, mode: None }])
+        }
+    }
+}
+
+/// The top-level folder holding every entry, when there is exactly one.
+pub fn single_root(members: &[ArchiveMember]) -> Option<PathBuf> {
+    let mut root = None;
+    let mut nested = false;
+    for member in members {
+        let Some(path) = &member.path else { continue };
+        let mut components = path.components();
+        let first = components.next()?.as_os_str();
+        match root {
+            None => root = Some(first),
+            Some(root) if root != first => return None,
+            Some(_) => {}
+        }
+        nested |= member.is_dir() || components.next().is_some();
+    }
+    root.filter(|_| nested).map(PathBuf::from)
+}
+
+/// What an extraction did.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExtractSummary {
+    /// The folder (or single file) the contents ended up in.
+    pub destination: PathBuf,
+    pub extracted: usize,
+    /// Entries left alone because something was already there.
+    pub skipped: usize,
+    /// Names of entries refused because they would land outside the
+    /// destination.
+    pub unsafe_entries: Vec<String>,
+}
+
+/// Unpacks `archive` into `folder`. An archive holding a single top-level
+/// folder is extracted as it is; anything else goes into a new folder named
+/// after the archive. `policy` decides about files that already exist.
+/// Entries with absolute or `..` paths, and entries that would be written
+/// through a link leading outside, are refused. What was extracted before a
+/// failure or a cancel is left in place.
+pub fn extract_archive(archive: &Path, folder: &Path, policy: ConflictPolicy, report: &mut dyn FnMut(&Progress), cancel: &AtomicBool) -> Result<ExtractSummary, FileError> {
+    let kind = ArchiveKind::detect(archive).ok_or_else(|| unsupported(archive))?;
+    let mut progress = Progress { current: archive.to_path_buf(), ..Progress::default() };
+    report(&progress);
+
+    if let ArchiveKind::Single(compression) = kind {
+        return extract_single(archive, folder, compression, policy, &mut progress, report, cancel);
+    }
+
+    let members = list_archive(archive)?;
+    if members.is_empty() {
+        return Err(FileError::invalid(Operation::Extract, archive, "the archive is empty"));
+    }
+    let root = single_root(&members);
+    let base = match root {
+        Some(_) => folder.to_path_buf(),
+        None => operations::unique_path(&folder.join(archive_stem(archive))),
+    };
+    fs::create_dir_all(&base).map_err(|e| FileError::io(Operation::Create, &base, &e))?;
+    let real_base = base.canonicalize().map_err(|e| FileError::io(Operation::Read, &base, &e))?;
+
+    progress.total_bytes = members.iter().filter(|m| m.kind == MemberKind::File && m.path.is_some()).map(|m| m.size).sum();
+    let mut extractor = Extractor {
+        base: real_base,
+        policy,
+        summary: ExtractSummary { destination: root.map(|r| base.join(r)).unwrap_or(base), ..ExtractSummary::default() },
+        progress: &mut progress,
+        report,
+        cancel,
+    };
+
+    each_member(archive, &mut |member, data| extractor.extract(member, data).map(|()| true))?;
+    Ok(extractor.summary)
+}
+
+/// Calls `visit` with every entry of the zip or tar `archive` and its data,
+/// until it returns `false`.
+fn each_member(archive: &Path, visit: &mut dyn FnMut(&ArchiveMember, &mut dyn Read) -> Result<bool, FileError>) -> Result<(), FileError> {
+    let read_error = |e: io::Error| FileError::io(Operation::Read, archive, &e);
+    match ArchiveKind::detect(archive) {
+        Some(ArchiveKind::Zip) => {
+            let mut zip = open_zip(archive)?;
+            for i in 0..zip.len() {
+                let mut file = zip.by_index(i).map_err(|e| FileError::invalid(Operation::Read, archive, e.to_string()))?;
+                let member = zip_member(&mut file).map_err(read_error)?;
+                if !visit(&member, &mut file)? {
+                    break;
+                }
+            }
+            Ok(())
+        }
+        Some(ArchiveKind::Tar(compression)) => {
+            let mut tar = open_tar(archive, compression)?;
+            for entry in tar.entries().map_err(read_error)? {
+                let mut entry = entry.map_err(read_error)?;
+                if let Some(member) = tar_member(&entry)
+                    && !visit(&member, &mut entry)?
+                {
+                    break;
+                }
+            }
+            Ok(())
+        }
+        Some(ArchiveKind::Single(_)) | None => Err(unsupported(archive)),
+    }
+}
+
+fn extract_single(archive: &Path, folder: &Path, compression: Compression, policy: ConflictPolicy, progress: &mut Progress, report: &mut dyn FnMut(&Progress), cancel: &AtomicBool) -> Result<ExtractSummary, FileError> {
+    let destination = folder.join(archive_stem(archive));
+    let Some(target) = operations::resolve_conflict(&destination, policy, Operation::Extract)? else {
+        return Ok(ExtractSummary { destination, skipped: 1, ..ExtractSummary::default() });
+    };
+    let file = open(archive)?;
+    progress.total_bytes = file.metadata().map(|m| m.len()).unwrap_or(0);
+    // Counts compressed bytes, as the uncompressed size is not known up front
+    let reader = ProgressReader { inner: io::BufReader::new(file), progress, report, cancel };
+    let mut reader = decoder(reader, compression).map_err(|e| FileError::io(Operation::Read, archive, &e))?;
+    write_file(&target, &mut reader, cancel)?;
+    Ok(ExtractSummary { destination: target, extracted: 1, ..ExtractSummary::default() })
+}
+
+/// Creates `path` with the contents of `data`, removing it again on failure.
+fn write_file(path: &Path, data: &mut dyn Read, cancel: &AtomicBool) -> Result<(), FileError> {
+    let result = File::create(path)
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            io::copy(data, &mut writer)?;
+            writer.flush()
+        })
+        .map_err(|e| io_error(Operation::Extract, path, &e, cancel));
+    if result.is_err() {
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+/// Writes archive entries below `base`, which is canonical.
+struct Extractor<'a> {
+    base: PathBuf,
+    policy: ConflictPolicy,
+    summary: ExtractSummary,
+    progress: &'a mut Progress,
+    report: &'a mut dyn FnMut(&Progress),
+    cancel: &'a AtomicBool,
+}
+
+impl Extractor<'_> {
+    /// Whether `path` resolves to somewhere below `base`, looking at the
+    /// part of it that exists already.
+    fn inside(&self, path: &Path) -> bool {
+        let mut existing = path;
+        while fs::symlink_metadata(existing).is_err() {
+            match existing.parent() {
+                Some(parent) => existing = parent,
+                None => return false,
+            }
+        }
+        existing.canonicalize().is_ok_and(|real| real.starts_with(&self.base))
+    }
+
+    fn refuse(&mut self, member: &ArchiveMember) {
+        self.summary.unsafe_entries.push(member.name.clone());
+    }
+
+    fn extract(&mut self, member: &ArchiveMember, data: &mut dyn Read) -> Result<(), FileError> {
+        let Some(relative) = &member.path else {
+            self.refuse(member);
+            return Ok(());
+        };
+        let path = self.base.join(relative);
+        if self.cancel.load(Ordering::Relaxed) {
+            return Err(cancelled(Operation::Extract, &path));
+        }
+        self.progress.current = path.clone();
+        (self.report)(self.progress);
+
+        let parent = path.parent().unwrap_or(&self.base).to_path_buf();
+        if !self.inside(&parent) {
+            self.refuse(member);
+            return Ok(());
+        }
+        fs::create_dir_all(&parent).map_err(|e| FileError::io(Operation::Create, &parent, &e))?;
+
+        // Folders that already exist are merged into
+        if member.is_dir() && fs::symlink_metadata(&path).is_ok_and(|m| m.is_dir()) {
+            return Ok(());
+        }
+        let Some(target) = operations::resolve_conflict(&path, self.policy, Operation::Extract)? else {
+            self.summary.skipped += 1;
+            return Ok(());
+        };
+        let create_error = |e: io::Error| FileError::io(Operation::Extract, &target, &e);
+
+        match &member.kind {
+            MemberKind::Dir => {
+                fs::create_dir(&target).map_err(create_error)?;
+                // Keep folders writable so their contents can follow
+                if let Some(mode) = member.mode {
+                    let _ = fs::set_permissions(&target, fs::Permissions::from_mode(mode & 0o777 | 0o700));
+                }
+            }
+            MemberKind::Symlink(to) => std::os::unix::fs::symlink(to, &target).map_err(create_error)?,
+            MemberKind::HardLink(name) => {
+                let source = safe_path(name).map(|p| self.base.join(p));
+                let Some(source) = source.filter(|s| s.is_file() && self.inside(s)) else {
+                    self.refuse(member);
+                    return Ok(());
+                };
+                fs::hard_link(&source, &target)
+                    .or_else(|_| fs::copy(&source, &target).map(|_| ()))
+                    .map_err(create_error)?;
+            }
+            MemberKind::File => {
+                let mut reader = ProgressReader { inner: data, progress: &mut *self.progress, report: &mut *self.report, cancel: self.cancel };
+                write_file(&target, &mut reader, self.cancel)?;
+                if let Some(mode) = member.mode {
+                    let _ = fs::set_permissions(&target, fs::Permissions::from_mode(mode & 0o777));
+                }
+                if let Some(modified) = member.modified {
+                    let _ = filetime::set_file_mtime(&target, filetime::FileTime::from_unix_time(modified.timestamp(), 0));
+                }
+            }
+        }
+        self.summary.extracted += 1;
+        Ok(())
+    }
+}
+
+/// The zip or tar file that `path` leads into and the path inside it, for
+/// paths such as `/data/photos.zip/2023/beach`. The archive itself gives an
+/// empty inner path; anything else on disk gives `None`.
+pub fn locate(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    // Only a path with an archive's name along it can lead into one, which
+    // spares ordinary paths the metadata lookups below
+    if !path.ancestors().any(|a| ArchiveKind::detect(a).is_some_and(ArchiveKind::is_browsable)) {
+        return None;
+    }
+    let mut archive = path;
+    let metadata = loop {
+        match fs::metadata(archive) {
+            Ok(metadata) => break metadata,
+            Err(_) => archive = archive.parent()?,
+        }
+    };
+    if !metadata.is_file() || !ArchiveKind::detect(archive).is_some_and(ArchiveKind::is_browsable) {
+        return None;
+    }
+    let inner = path.strip_prefix(archive).ok()?.to_path_buf();
+    Some((archive.to_path_buf(), inner))
+}
+
+/// Whether `path` names an entry inside an archive rather than a file on disk.
+pub fn is_member(path: &Path) -> bool {
+    locate(path).is_some_and(|(_, inner)| !inner.as_os_str().is_empty())
+}
+
+/// Whether `path` is an archive that can be browsed, or a folder inside one.
+pub fn is_folder(path: &Path) -> bool {
+    locate(path).is_some_and(|(archive, inner)| inner.as_os_str().is_empty() || read_folder(&archive, &inner, true).is_ok())
+}
+
+/// The error for trying to change something inside an archive.
+pub fn read_only(operation: Operation, path: &Path) -> FileError {
+    FileError::invalid(operation, path, "files inside an archive can't be changed")
+}
+
+struct CachedListing {
+    archive: PathBuf,
+    len: u64,
+    modified: Option<SystemTime>,
+    members: Arc<Vec<ArchiveMember>>,
+}
+
+/// Listings of recently browsed archives, so moving between folders of a
+/// compressed tarball doesn't decompress it every time.
+static LISTINGS: Mutex<Vec<CachedListing>> = Mutex::new(Vec::new());
+const CACHED_LISTINGS: usize = 8;
+
+fn cached_listing(archive: &Path) -> Result<Arc<Vec<ArchiveMember>>, FileError> {
+    let metadata = fs::metadata(archive).map_err(|e| FileError::io(Operation::Read, archive, &e))?;
+    let (len, modified) = (metadata.len(), metadata.modified().ok());
+    let mut listings = LISTINGS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cached) = listings.iter().find(|c| c.archive == archive && c.len == len && c.modified == modified) {
+        return Ok(cached.members.clone());
+    }
+
+    let members = Arc::new(list_archive(archive)?);
+    listings.retain(|c| c.archive != archive);
+    if listings.len() >= CACHED_LISTINGS {
+        listings.remove(0);
+    }
+    listings.push(CachedListing { archive: archive.to_path_buf(), len, modified, members: members.clone() });
+    Ok(members)
+}
+
+/// The entries of the folder `inner` of `archive`, as they would be read
+/// from disk. Folders that only appear in the paths of their contents are
+/// listed too.
+pub fn read_folder(archive: &Path, inner: &Path, show_hidden: bool) -> Result<Vec<FileEntry>, FileError> {
+    let members = cached_listing(archive)?;
+    let folder = archive.join(inner);
+    let archive_modified = fs::metadata(archive).and_then(|m| m.modified()).map(DateTime::<Local>::from).unwrap_or_else(|_| Local::now());
+
+    let mut found = inner.as_os_str().is_empty();
+    let mut children: BTreeMap<String, FileEntry> = BTreeMap::new();
+    for member in members.iter() {
+        let Some(rest) = member.path.as_deref().and_then(|p| p.strip_prefix(inner).ok()) else { continue };
+        let mut components = rest.components();
+        let Some(first) = components.next() else {
+            // The folder's own entry
+            found |= member.is_dir();
+            continue;
+        };
+        found = true;
+        let name = first.as_os_str().to_string_lossy().to_string();
+        if !show_hidden && name.starts_with('.') {
+            continue;
+        }
+        let direct = components.next().is_none();
+        let entry = children.entry(name.clone()).or_insert_with(|| FileEntry {
+            path: folder.join(&name),
+            is_dir: true,
+            size: 0,
+            modified: archive_modified,
+            extension: Path::new(&name).extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default(),
+            name,
+        });
+        if direct {
+            entry.is_dir = member.is_dir();
+            entry.size = if member.kind == MemberKind::File { member.size } else { 0 };
+            entry.modified = member.modified.unwrap_or(archive_modified);
+        }
+    }
+    if !found {
+        return Err(FileError::kind_of(Operation::Read, &folder, io::ErrorKind::NotFound));
+    }
+    Ok(children.into_values().collect())
+}
+
+/// The listing entry of the archive member at `path`.
+pub fn member_entry(path: &Path) -> Option<FileEntry> {
+    let (archive, inner) = locate(path.parent()?)?;
+    read_folder(&archive, &inner, true).ok()?.into_iter().find(|entry| entry.path == path)
+}
+
+/// Copies the archive member at `path`, with everything below it for a
+/// folder, out to `destination` on disk. Like a copy on disk, files that
+/// exist are replaced and folders are merged into.
+pub fn copy_out(path: &Path, destination: &Path) -> Result<(), FileError> {
+    let not_found = || FileError::kind_of(Operation::Copy, path, io::ErrorKind::NotFound);
+    let (archive, inner) = locate(path).filter(|(_, inner)| !inner.as_os_str().is_empty()).ok_or_else(not_found)?;
+    let (Some(parent), Some(name)) = (destination.parent(), destination.file_name()) else {
+        return Err(FileError::invalid(Operation::Copy, destination, "not a file or folder name"));
+    };
+    let base = parent.canonicalize().map_err(|e| FileError::io(Operation::Copy, parent, &e))?;
+
+    let mut progress = Progress::default();
+    let cancel = AtomicBool::new(false);
+    let mut extractor = Extractor {
+        base,
+        policy: ConflictPolicy::Overwrite,
+        summary: ExtractSummary::default(),
+        progress: &mut progress,
+        report: &mut |_| {},
+        cancel: &cancel,
+    };
+    // Joining an empty path would add a trailing slash
+    let renamed = |rest: &Path| if rest.as_os_str().is_empty() { PathBuf::from(name) } else { Path::new(name).join(rest) };
+    let mut found = false;
+    each_member(&archive, &mut |member, data| {
+        let Some(rest) = member.path.as_deref().and_then(|p| p.strip_prefix(&inner).ok()) else { return Ok(true) };
+        found = true;
+        let kind = match &member.kind {
+            // Links within the copied part point to their copies
+            MemberKind::HardLink(link) => match safe_path(link).as_deref().and_then(|l| l.strip_prefix(&inner).ok()) {
+                Some(rest) => MemberKind::HardLink(renamed(rest).to_string_lossy().to_string()),
+                None => member.kind.clone(),
+            },
+            kind => kind.clone(),
+        };
+        let copy = ArchiveMember { path: Some(renamed(rest)), kind, ..member.clone() };
+        extractor.extract(&copy, data)?;
+        // A file is done once found; a folder's contents can be anywhere
+        Ok(member.is_dir() || !rest.as_os_str().is_empty())
+    })?;
+    if !found {
+        return Err(not_found());
+    }
+    Ok(())
+}
+
+/// Copies made by `temporary_copy`, removed by `remove_temporary_copies`.
+static TEMPORARY_COPIES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// This run's folder for temporary copies, once it has been made.
+static TEMPORARY_FOLDER: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// This run's folder for temporary copies, made on first use with a random
+/// name that only the user can enter.
+fn temporary_folder() -> Result<PathBuf, FileError> {
+    let mut folder = TEMPORARY_FOLDER.lock().unwrap_or_else(|e| e.into_inner());
+    match folder.as_ref() {
+        Some(folder) => check_private(folder)?,
+        None => *folder = Some(create_private_folder(&std::env::temp_dir())?),
+    }
+    Ok(folder.clone().unwrap_or_default())
+}
+
+fn create_private_folder(parent: &Path) -> Result<PathBuf, FileError> {
+    loop {
+        let random = RandomState::new().build_hasher().finish();
+        let path = parent.join(format!("fileexp-archive-{:016x}", random));
+        match fs::DirBuilder::new().mode(0o700).create(&path) {
+            Ok(()) => {
+                check_private(&path)?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(FileError::io(Operation::Create, &path, &e)),
+        }
+    }
+}
+
+/// Fails unless `folder` is a real folder of the current user that nobody
+/// else can enter, so copies can't end up somewhere another user controls.
+fn check_private(folder: &Path) -> Result<(), FileError> {
+    let metadata = fs::symlink_metadata(folder).map_err(|e| FileError::io(Operation::Open, folder, &e))?;
+    if !metadata.is_dir() {
+        return Err(FileError::invalid(Operation::Open, folder, "not a folder"));
+    }
+    if metadata.uid() != nix::unistd::Uid::current().as_raw() {
+        return Err(FileError::invalid(Operation::Open, folder, "owned by another user"));
+    }
+    if metadata.mode() & 0o077 != 0 {
+        return Err(FileError::invalid(Operation::Open, folder, "other users can open it"));
+    }
+    Ok(())
+}
+
+/// Copies the archive member at `path` into a temporary folder, so another
+/// program can open it.
+pub fn temporary_copy(path: &Path) -> Result<PathBuf, FileError> {
+    let name = path.file_name().ok_or_else(|| FileError::invalid(Operation::Open, path, "not a file name"))?;
+    let destination = temporary_folder()?.join(name);
+    copy_out(path, &destination)?;
+    TEMPORARY_COPIES.lock().unwrap_or_else(|e| e.into_inner()).push(destination.clone());
+    Ok(destination)
+}
+
+/// Deletes the copies `temporary_copy` made, e.g. when the program exits.
+pub fn remove_temporary_copies() {
+    let mut copies = TEMPORARY_COPIES.lock().unwrap_or_else(|e| e.into_inner());
+    for copy in copies.drain(..) {
+        let _ = if copy.is_dir() { fs::remove_dir_all(&copy) } else { fs::remove_file(&copy) };
+    }
+    if let Some(folder) = TEMPORARY_FOLDER.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        let _ = fs::remove_dir(folder);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    enum Entry<'a> {
+        File(&'a str, &'a str),
+        Dir(&'a str),
+        Symlink(&'a str, &'a Path),
+    }
+
+    /// Writes a tar with the names exactly as given, which `tar::Builder`
+    /// would refuse for `..` and absolute paths.
+    fn write_tar(path: &Path, entries: &[Entry]) {
+        let mut builder = tar::Builder::new(File::create(path).unwrap());
+        for entry in entries {
+            let mut header = tar::Header::new_gnu();
+            let (name, data): (&str, &[u8]) = match entry {
+                Entry::File(name, data) => {
+                    header.set_entry_type(tar::EntryType::Regular);
+                    (name, data.as_bytes())
+                }
+                Entry::Dir(name) => {
+                    header.set_entry_type(tar::EntryType::Directory);
+                    (name, &[])
+                }
+                Entry::Symlink(name, target) => {
+                    header.set_entry_type(tar::EntryType::Symlink);
+                    header.set_link_name(target).unwrap();
+                    (name, &[])
+                }
+            };
+            let raw = &mut header.as_gnu_mut().unwrap().name;
+            raw[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, data).unwrap();
+        }
+        builder.finish().unwrap();
+    }
+
+    fn extract(archive: &Path, folder: &Path, policy: ConflictPolicy) -> ExtractSummary {
+        extract_archive(archive, folder, policy, &mut |_| {}, &AtomicBool::new(false)).unwrap()
+    }
+
+    /// A folder to extract into and a sibling that must stay untouched.
+    fn setup() -> (TempDir, PathBuf, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let (folder, outside) = (dir.path().join("folder"), dir.path().join("outside"));
+        fs::create_dir(&folder).unwrap();
+        fs::create_dir(&outside).unwrap();
+        (dir, folder, outside)
+    }
+
+    #[test]
+    fn safe_path_rejects_escapes() {
+        assert_eq!(safe_path("a/./b"), Some(PathBuf::from("a/b")));
+        assert_eq!(safe_path("../a"), None);
+        assert_eq!(safe_path("a/../../b"), None);
+        assert_eq!(safe_path("/etc/passwd"), None);
+        assert_eq!(safe_path("./"), None);
+    }
+
+    #[test]
+    fn parent_and_absolute_members_are_refused() {
+        let (dir, folder, _) = setup();
+        let archive = folder.join("evil.tar");
+        let absolute = dir.path().join("absolute.txt");
+        write_tar(&archive, &[
+            Entry::File("../escape.txt", "evil"),
+            Entry::File(absolute.to_str().unwrap(), "evil"),
+            Entry::File("ok.txt", "fine"),
+        ]);
+
+        let summary = extract(&archive, &folder, ConflictPolicy::Fail);
+        assert_eq!(summary.extracted, 1);
+        assert_eq!(summary.unsafe_entries, vec!["../escape.txt".to_string(), absolute.to_string_lossy().to_string()]);
+        assert!(!dir.path().join("escape.txt").exists());
+        assert!(!absolute.exists());
+        assert_eq!(fs::read_to_string(folder.join("evil/ok.txt")).unwrap(), "fine");
+    }
+
+    #[test]
+    fn members_below_a_symlink_pointing_outside_are_refused() {
+        let (_dir, folder, outside) = setup();
+        let archive = folder.join("link.tar");
+        write_tar(&archive, &[
+            Entry::Dir("root/"),
+            Entry::Symlink("root/out", &outside),
+            Entry::File("root/out/through.txt", "evil"),
+        ]);
+
+        let summary = extract(&archive, &folder, ConflictPolicy::Fail);
+        assert_eq!(summary.unsafe_entries, vec!["root/out/through.txt".to_string()]);
+        assert!(fs::symlink_metadata(folder.join("root/out")).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_dir(&outside).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn overwrite_replaces_a_symlink_instead_of_writing_through_it() {
+        let (_dir, folder, outside) = setup();
+        let victim = outside.join("victim.txt");
+        fs::write(&victim, "original").unwrap();
+        fs::create_dir(folder.join("root")).unwrap();
+        std::os::unix::fs::symlink(&victim, folder.join("root/file.txt")).unwrap();
+
+        let archive = folder.join("root.tar");
+        write_tar(&archive, &[Entry::Dir("root/"), Entry::File("root/file.txt", "new")]);
+
+        let summary = extract(&archive, &folder, ConflictPolicy::Overwrite);
+        assert_eq!(summary.destination, folder.join("root"));
+        assert_eq!(fs::read_to_string(&victim).unwrap(), "original");
+        let extracted = folder.join("root/file.txt");
+        assert!(!fs::symlink_metadata(&extracted).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&extracted).unwrap(), "new");
+    }
+
+    #[test]
+    fn zip_members_with_parent_components_are_refused() {
+        let (dir, folder, _) = setup();
+        let archive = folder.join("evil.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("../zip-escape.txt", options).unwrap();
+        zip.write_all(b"evil").unwrap();
+        zip.start_file("good.txt", options).unwrap();
+        zip.write_all(b"ok").unwrap();
+        zip.finish().unwrap();
+
+        let summary = extract(&archive, &folder, ConflictPolicy::Fail);
+        assert_eq!(summary.unsafe_entries, vec!["../zip-escape.txt".to_string()]);
+        assert!(!dir.path().join("zip-escape.txt").exists());
+        assert!(folder.join("evil/good.txt").is_file());
+    }
+
+    #[test]
+    fn single_root_keeps_the_folder_and_other_layouts_get_one() {
+        let (_dir, folder, _) = setup();
+        let flat = folder.join("flat.tar");
+        write_tar(&flat, &[Entry::File("x", "1"), Entry::File("y", "2")]);
+        assert_eq!(extract(&flat, &folder, ConflictPolicy::Fail).destination, folder.join("flat"));
+
+        let nested = folder.join("nested.tar");
+        write_tar(&nested, &[Entry::File("proj/a", "1"), Entry::File("proj/b/c", "2")]);
+        assert_eq!(extract(&nested, &folder, ConflictPolicy::Fail).destination, folder.join("proj"));
+    }
+
+    #[test]
+    fn only_paths_through_an_archive_are_members() {
+        let (dir, folder, _) = setup();
+        let archive = folder.join("docs.tar");
+        write_tar(&archive, &[Entry::Dir("sub/"), Entry::File("sub/a.txt", "text")]);
+
+        assert!(is_member(&archive.join("sub/a.txt")));
+        assert!(!is_member(&archive));
+        assert!(!is_member(&folder.join("plain/a.txt")));
+        // An archive's name alone isn't enough when no such file exists
+        assert!(!is_member(&dir.path().join("missing.tar/a.txt")));
+    }
+
+    #[test]
+    fn temporary_copies_are_removed() {
+        let (_dir, folder, _) = setup();
+        let archive = folder.join("docs.tar");
+        write_tar(&archive, &[Entry::File("a.txt", "text")]);
+
+        let copy = temporary_copy(&archive.join("a.txt")).unwrap();
+        assert_eq!(fs::read_to_string(&copy).unwrap(), "text");
+        let parent = copy.parent().unwrap();
+        assert_eq!(fs::metadata(parent).unwrap().mode() & 0o777, 0o700);
+        assert!(!parent.to_string_lossy().contains(&std::process::id().to_string()));
+        remove_temporary_copies();
+        assert!(!copy.exists());
+    }
+
+    #[test]
+    fn shared_or_linked_folders_are_not_private() {
+        let dir = tempfile::tempdir().unwrap();
+        let private = create_private_folder(dir.path()).unwrap();
+        assert!(check_private(&private).is_ok());
+
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&private, &link).unwrap();
+        assert_eq!(check_private(&link).unwrap_err().reason(), "not a folder");
+        fs::set_permissions(&private, fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(check_private(&private).unwrap_err().reason(), "other users can open it");
+    }
+}
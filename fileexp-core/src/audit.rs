@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Local};
+use crate::error::{FileError, Operation};
+use crate::explorer::Change;
+
+const LOG_NAME: &str = "audit.jsonl";
+// The log is rotated once it reaches this size, keeping this many old files
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+const KEEP_ROTATED: usize = 4;
+
+/// One line of the audit log.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub timestamp: DateTime<Local>,
+    pub user: String,
+    pub operation: Operation,
+    /// The item acted on; for terminal commands the working directory.
+    pub source: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination: Option<PathBuf>,
+    /// The command line, for terminal commands.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditRecord {
+    pub fn new(operation: Operation, source: &Path, destination: Option<&Path>, result: Result<(), String>) -> Self {
+        Self {
+            timestamp: Local::now(),
+            user: current_user(),
+            operation,
+            source: source.to_path_buf(),
+            destination: destination.map(Path::to_path_buf),
+            command: None,
+            ok: result.is_ok(),
+            error: result.err(),
+        }
+    }
+
+    pub fn from_change(change: &Change) -> Self {
+        let result = match &change.error {
+            Some(error) => Err(error.to_string()),
+            None => Ok(()),
+        };
+        Self::new(change.operation, &change.source, change.destination.as_deref(), result)
+    }
+
+    /// A shell command run in `dir`; `result` is its exit status or why it
+    /// could not be started.
+    pub fn command(command: &str, dir: &Path, result: Result<(), String>) -> Self {
+        Self { command: Some(command.to_string()), ..Self::new(Operation::Execute, dir, None, result) }
+    }
+
+    /// Whether the record mentions `text` in one of its paths or its command.
+    pub fn mentions(&self, text: &str) -> bool {
+        self.source.to_string_lossy().contains(text)
+            || self.destination.as_ref().is_some_and(|d| d.to_string_lossy().contains(text))
+            || self.command.as_ref().is_some_and(|c| c.contains(text))
+    }
+}
+
+/// The login name, or the numeric user id when the environment doesn't say.
+fn current_user() -> String {
+    use std::os::unix::fs::MetadataExt;
+    std::env::var("USER")
+        .or_else(|_| std::env::var("LOGNAME"))
+        .unwrap_or_else(|_| match fs::metadata("/proc/self") {
+            Ok(metadata) => format!("uid {}", metadata.uid()),
+            Err(_) => "unknown".to_string(),
+        })
+}
+
+/// Append-only JSON-lines log of everything that changed files, rotated as
+/// `audit.jsonl`, `audit.jsonl.1`, ... in a directory.
+#[derive(Clone, Debug)]
+pub struct AuditLog {
+    dir: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn file(&self, generation: usize) -> PathBuf {
+        match generation {
+            0 => self.dir.join(LOG_NAME),
+            n => self.dir.join(format!("{}.{}", LOG_NAME, n)),
+        }
+    }
+
+    fn rotate(&self) -> Result<(), FileError> {
+        for generation in (0..KEEP_ROTATED).rev() {
+            let from = self.file(generation);
+            if from.exists() {
+                let to = self.file(generation + 1);
+                fs::rename(&from, &to).map_err(|e| FileError::io(Operation::Rename, &from, &e))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn record(&self, record: &AuditRecord) -> Result<(), FileError> {
+        let path = self.file(0);
+        let mut line = serde_json::to_string(record).map_err(|e| FileError::invalid(Operation::Write, &path, e.to_string()))?;
+        line.push('\n');
+
+        fs::create_dir_all(&self.dir).map_err(|e| FileError::io(Operation::Create, &self.dir, &e))?;
+        if fs::metadata(&path).is_ok_and(|m| m.len() + line.len() as u64 > MAX_LOG_BYTES) {
+            self.rotate()?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| FileError::io(Operation::Write, &path, &e))
+    }
+
+    /// Every record still kept, oldest first. Lines that don't parse are skipped.
+    pub fn read_all(&self) -> Vec<AuditRecord> {
+        (0..=KEEP_ROTATED)
+            .rev()
+            .filter_map(|generation| fs::read_to_string(self.file(generation)).ok())
+            .flat_map(|contents| {
+                contents.lines().filter_map(|line| serde_json::from_str(line).ok()).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(source: &str) -> AuditRecord {
+        AuditRecord::new(Operation::Delete, Path::new(source), None, Ok(()))
+    }
+
+    #[test]
+    fn records_are_read_back_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("logs"));
+        log.record(&record("/a")).unwrap();
+        log.record(&record("/b")).unwrap();
+        let sources: Vec<PathBuf> = log.read_all().into_iter().map(|r| r.source).collect();
+        assert_eq!(sources, vec![PathBuf::from("/a"), PathBuf::from("/b")]);
+    }
+
+    #[test]
+    fn a_full_log_is_rotated_and_the_oldest_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().to_path_buf());
+        let full = format!("{}\n", serde_json::to_string(&record("/old")).unwrap()).repeat(MAX_LOG_BYTES as usize / 100);
+        assert!(full.len() as u64 > MAX_LOG_BYTES - 100);
+        fs::write(log.file(0), &full).unwrap();
+        for generation in 1..=KEEP_ROTATED {
+            fs::write(log.file(generation), format!("generation {}\n", generation)).unwrap();
+        }
+
+        log.record(&record("/new")).unwrap();
+
+        assert_eq!(fs::read_to_string(log.file(1)).unwrap(), full);
+        assert_eq!(fs::read_to_string(log.file(2)).unwrap(), "generation 1\n");
+        assert_eq!(fs::read_to_string(log.file(KEEP_ROTATED)).unwrap(), format!("generation {}\n", KEEP_ROTATED - 1));
+        assert!(!log.file(KEEP_ROTATED + 1).exists());
+        let current = fs::read_to_string(log.file(0)).unwrap();
+        assert_eq!(current.lines().count(), 1);
+        assert!(current.contains("/new"));
+        // Unparseable lines of older generations are skipped
+        assert_eq!(log.read_all().last().map(|r| r.source.clone()), Some(PathBuf::from("/new")));
+    }
+}
@@ -0,0 +1,42 @@
+use std::fs;
+use std::path::PathBuf;
+use crate::error::{FileError, Operation};
+use crate::models::Bookmark;
+
+/// Bookmarked folders, kept in a JSON file.
+#[derive(Clone, Default)]
+pub struct BookmarkStore {
+    pub items: Vec<Bookmark>,
+    path: PathBuf,
+}
+
+impl BookmarkStore {
+    /// Reads the bookmarks saved in `path`; a missing file means none.
+    pub fn load_from(path: PathBuf) -> Self {
+        let items = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_default(),
+            Err(_) => Vec::new(),
+        };
+        Self { items, path }
+    }
+
+    pub fn save(&self) -> Result<(), FileError> {
+        let json = serde_json::to_string(&self.items).map_err(|e| FileError::invalid(Operation::Write, &self.path, e.to_string()))?;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| FileError::io(Operation::Write, parent, &e))?;
+        }
+        fs::write(&self.path, json).map_err(|e| FileError::io(Operation::Write, &self.path, &e))
+    }
+
+    pub fn add(&mut self, name: String, path: PathBuf) -> Result<(), FileError> {
+        self.items.push(Bookmark { name, path });
+        self.save()
+    }
+
+    pub fn remove(&mut self, index: usize) -> Result<(), FileError> {
+        if index < self.items.len() {
+            self.items.remove(index);
+        }
+        self.save()
+    }
+}
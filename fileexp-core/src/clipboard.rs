@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+use crate::models::FileOperation;
+
+/// Files copied or cut for a later paste.
+#[derive(Clone, Debug, Default)]
+pub struct Clipboard {
+    pub operation: Option<FileOperation>,
+}
+
+impl Clipboard {
+    pub fn copy(&mut self, paths: Vec<PathBuf>) {
+        self.operation = Some(FileOperation::Copy(paths));
+    }
+
+    pub fn cut(&mut self, paths: Vec<PathBuf>) {
+        self.operation = Some(FileOperation::Cut(paths));
+    }
+
+    pub fn has_content(&self) -> bool {
+        self.operation.is_some()
+    }
+
+    /// Drops items that were moved elsewhere from a cut. Returns true if
+    /// that emptied the clipboard.
+    pub fn forget_moved(&mut self, moved: &[PathBuf]) -> bool {
+        let Some(FileOperation::Cut(paths)) = &mut self.operation else {
+            return false;
+        };
+        paths.retain(|p| !moved.contains(p));
+        if paths.is_empty() {
+            self.operation = None;
+            return true;
+        }
+        false
+    }
+}
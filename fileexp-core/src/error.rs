@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A kind of file operation, as named in errors and the audit log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    Read,
+    Create,
+    Write,
+    Copy,
+    Move,
+    Rename,
+    Delete,
+    Open,
+    Trash,
+    Restore,
+    Compress,
+    Extract,
+    /// A shell command run from the terminal panel.
+    Execute,
+}
+
+impl Operation {
+    pub fn verb(self) -> &'static str {
+        match self {
+            Operation::Read => "read",
+            Operation::Create => "create",
+            Operation::Write => "write",
+            Operation::Copy => "copy",
+            Operation::Move => "move",
+            Operation::Rename => "rename",
+            Operation::Delete => "delete",
+            Operation::Open => "open",
+            Operation::Trash => "trash",
+            Operation::Restore => "restore",
+            Operation::Compress => "compress",
+            Operation::Extract => "extract",
+            Operation::Execute => "run",
+        }
+    }
+}
+
+/// Why a file operation failed and on which path.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FileError {
+    /// The file system refused; `kind` tells permission problems, missing
+    /// files, name clashes, cross-device moves and full disks apart.
+    Io { operation: Operation, path: PathBuf, kind: io::ErrorKind },
+    /// The request made no sense for this path, like copying a folder into itself.
+    Invalid { operation: Operation, path: PathBuf, reason: String },
+}
+
+impl FileError {
+    pub fn io(operation: Operation, path: &Path, error: &io::Error) -> Self {
+        Self::Io { operation, path: path.to_path_buf(), kind: error.kind() }
+    }
+
+    pub fn kind_of(operation: Operation, path: &Path, kind: io::ErrorKind) -> Self {
+        Self::Io { operation, path: path.to_path_buf(), kind }
+    }
+
+    pub fn invalid(operation: Operation, path: &Path, reason: impl Into<String>) -> Self {
+        Self::Invalid { operation, path: path.to_path_buf(), reason: reason.into() }
+    }
+
+    pub fn operation(&self) -> Operation {
+        match self {
+            Self::Io { operation, .. } | Self::Invalid { operation, .. } => *operation,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Io { path, .. } | Self::Invalid { path, .. } => path,
+        }
+    }
+
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Self::Io { kind, .. } => *kind,
+            Self::Invalid { .. } => io::ErrorKind::InvalidInput,
+        }
+    }
+
+    /// Whether trying again could work once the user fixed something, e.g.
+    /// freed space or changed permissions.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Io { .. })
+    }
+
+    /// The cause in words, without the path.
+    pub fn reason(&self) -> String {
+        match self {
+            Self::Io { kind, .. } => match kind {
+                io::ErrorKind::PermissionDenied => "permission denied".to_string(),
+                io::ErrorKind::NotFound => "no such file or folder".to_string(),
+                io::ErrorKind::AlreadyExists => "already exists".to_string(),
+                io::ErrorKind::CrossesDevices => "cannot move across file systems".to_string(),
+                io::ErrorKind::StorageFull => "no space left on the device".to_string(),
+                io::ErrorKind::DirectoryNotEmpty => "folder is not empty".to_string(),
+                io::ErrorKind::ReadOnlyFilesystem => "read-only file system".to_string(),
+                kind => kind.to_string(),
+            },
+            Self::Invalid { reason, .. } => reason.clone(),
+        }
+    }
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to {} '{}': {}", self.operation().verb(), self.path().display(), self.reason())
+    }
+}
+
+impl std::error::Error for FileError {}
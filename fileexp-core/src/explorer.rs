@@ -0,0 +1,580 @@
+use std::path::{Path, PathBuf};
+use crate::clipboard::Clipboard;
+use crate::error::{FileError, Operation};
+use crate::history::History;
+use crate::models::{ConflictPolicy, FileEntry, FileOperation, SortBy};
+use crate::operations;
+use crate::rename;
+use crate::selection::Selection;
+use crate::trash;
+use crate::utils;
+
+/// Something a front end asks the explorer to do.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Navigate(PathBuf),
+    Back,
+    Forward,
+    /// Jump to an entry of the back/forward history.
+    GoToHistory(usize),
+    Up,
+    /// Re-read the current folder.
+    Refresh,
+    Select(Vec<PathBuf>),
+    /// Select just the entry at this index of the listing (a plain click).
+    SelectOnly(usize),
+    /// Add or remove the entry at this index (a Ctrl+click).
+    ToggleSelected(usize),
+    ClearSelection,
+    SetFilter(String),
+    SetSort { by: SortBy, ascending: bool },
+    SetShowHidden(bool),
+    CopySelection,
+    CutSelection,
+    Paste,
+    /// Copy or move the paths into `target`, settling items that already
+    /// exist there with `conflict`.
+    Transfer { operation: FileOperation, target: PathBuf, conflict: ConflictPolicy },
+    DeleteSelection,
+    Delete(Vec<PathBuf>),
+    /// Move the paths to the freedesktop.org trash.
+    Trash(Vec<PathBuf>),
+    /// Create an empty file at the path, resolved when the command was made
+    /// so a retry after navigating still targets the same folder.
+    CreateFile(PathBuf),
+    CreateFolder(PathBuf),
+    /// Give the item at `path` a new name in the same folder.
+    Rename { path: PathBuf, new_name: String },
+    /// Rename each (old path, new path) pair, all of them or none.
+    RenameBatch(Vec<(PathBuf, PathBuf)>),
+}
+
+impl Command {
+    /// Whether running the command can change anything on disk.
+    pub fn changes_files(&self) -> bool {
+        matches!(
+            self,
+            Command::Paste
+                | Command::Transfer { .. }
+                | Command::DeleteSelection
+                | Command::Delete(_)
+                | Command::Trash(_)
+                | Command::CreateFile(_)
+                | Command::CreateFolder(_)
+                | Command::Rename { .. }
+                | Command::RenameBatch(_)
+        )
+    }
+}
+
+/// A change on disk that a command made or tried to make.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    pub operation: Operation,
+    pub source: PathBuf,
+    pub destination: Option<PathBuf>,
+    /// Why it failed, if it did.
+    pub error: Option<FileError>,
+}
+
+/// What happened while running a command, in order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// The current folder changed.
+    Navigated(PathBuf),
+    /// The listing was read again; the selection is cleared.
+    Refreshed,
+    SelectionChanged,
+    ClipboardChanged,
+    /// A file or folder was created, renamed, copied, moved or deleted, or
+    /// that was attempted and failed.
+    Changed(Change),
+    /// A status line for the user.
+    Message(String),
+    /// A command failed. `retry` runs the failed step again; for copies,
+    /// moves and deletes it also carries on with the items after it, and
+    /// `skip` carries on with just those.
+    Error { error: FileError, retry: Option<Command>, skip: Option<Command> },
+}
+
+/// One browsing location: the listing of a folder with its filter and sort
+/// order, the selection in it, the back/forward history and the clipboard.
+#[derive(Clone, Debug)]
+pub struct Explorer {
+    pub current_path: PathBuf,
+    pub entries: Vec<FileEntry>,
+    pub filter: String,
+    pub sort_by: SortBy,
+    pub sort_ascending: bool,
+    pub show_hidden: bool,
+    pub selection: Selection,
+    pub history: History,
+    pub clipboard: Clipboard,
+}
+
+impl Explorer {
+    /// An explorer at `path`. Call [`Command::Refresh`] to read it.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            history: History::new(path.clone()),
+            current_path: path,
+            entries: Vec::new(),
+            filter: String::new(),
+            sort_by: SortBy::Name,
+            sort_ascending: true,
+            show_hidden: false,
+            selection: Selection::default(),
+            clipboard: Clipboard::default(),
+        }
+    }
+
+    pub fn selected_paths(&self) -> Vec<PathBuf> {
+        self.selection.paths(&self.entries)
+    }
+
+    /// The selected entries, in the order they were selected.
+    pub fn selected_entries(&self) -> Vec<FileEntry> {
+        self.selection.indices.iter().filter_map(|&i| self.entries.get(i).cloned()).collect()
+    }
+
+    /// The entry selected first, which single-item actions work on.
+    pub fn first_selected(&self) -> Option<(usize, &FileEntry)> {
+        let index = *self.selection.indices.first()?;
+        Some((index, self.entries.get(index)?))
+    }
+
+    /// Paths `command` would delete, move, rename or create, including
+    /// where copied and moved items end up.
+    pub fn paths_at_risk(&self, command: &Command) -> Vec<PathBuf> {
+        let transferred = |operation: &FileOperation, target: &Path| {
+            let destinations = operation.paths().iter().map(|path| target.join(path.file_name().unwrap_or_default()));
+            match operation {
+                FileOperation::Cut(paths) => paths.iter().cloned().chain(destinations).collect(),
+                FileOperation::Copy(_) => destinations.collect(),
+            }
+        };
+        match command {
+            Command::Paste => match &self.clipboard.operation {
+                Some(operation) => transferred(operation, &self.current_path),
+                None => Vec::new(),
+            },
+            Command::Transfer { operation, target, .. } => transferred(operation, target),
+            Command::DeleteSelection => self.selected_paths(),
+            Command::Delete(paths) | Command::Trash(paths) => paths.clone(),
+            Command::CreateFile(path) | Command::CreateFolder(path) => vec![path.clone()],
+            Command::Rename { path, new_name } => vec![path.clone(), path.with_file_name(new_name)],
+            Command::RenameBatch(renames) => renames.iter().flat_map(|(from, to)| [from.clone(), to.clone()]).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn execute(&mut self, command: Command) -> Vec<Event> {
+        let mut events = Vec::new();
+        match command {
+            Command::Navigate(path) => {
+                if operations::is_folder(&path) {
+                    self.history.push(path.clone());
+                    self.enter(path, &mut events);
+                }
+            }
+            Command::Back => {
+                if self.history.can_go_back() {
+                    self.go_to_history(self.history.index - 1, &mut events);
+                }
+            }
+            Command::Forward => {
+                if self.history.can_go_forward() {
+                    self.go_to_history(self.history.index + 1, &mut events);
+                }
+            }
+            Command::GoToHistory(index) => self.go_to_history(index, &mut events),
+            Command::Up => {
+                if let Some(parent) = self.current_path.parent() {
+                    return self.execute(Command::Navigate(parent.to_path_buf()));
+                }
+            }
+            Command::Refresh => self.refresh(&mut events),
+            Command::Select(paths) => {
+                self.selection.select_paths(&self.entries, &paths);
+                events.push(Event::SelectionChanged);
+            }
+            Command::SelectOnly(index) => {
+                if index < self.entries.len() {
+                    self.selection.select_only(index);
+                    events.push(Event::SelectionChanged);
+                }
+            }
+            Command::ToggleSelected(index) => {
+                if index < self.entries.len() {
+                    self.selection.toggle(index);
+                    events.push(Event::SelectionChanged);
+                }
+            }
+            Command::ClearSelection => {
+                self.selection.clear();
+                events.push(Event::SelectionChanged);
+            }
+            Command::SetFilter(filter) => {
+                self.filter = filter;
+                self.refresh(&mut events);
+            }
+            Command::SetSort { by, ascending } => {
+                self.sort_by = by;
+                self.sort_ascending = ascending;
+                self.refresh(&mut events);
+            }
+            Command::SetShowHidden(show_hidden) => {
+                self.show_hidden = show_hidden;
+                self.refresh(&mut events);
+            }
+            Command::CopySelection => {
+                if !self.selection.is_empty() {
+                    self.clipboard.copy(self.selected_paths());
+                    events.push(Event::ClipboardChanged);
+                    events.push(Event::Message(format!("Copied {} items", self.selection.len())));
+                }
+            }
+            Command::CutSelection => {
+                if !self.selection.is_empty() {
+                    self.clipboard.cut(self.selected_paths());
+                    events.push(Event::ClipboardChanged);
+                    events.push(Event::Message(format!("Cut {} items", self.selection.len())));
+                }
+            }
+            Command::Paste => {
+                if let Some(operation) = self.clipboard.operation.clone() {
+                    let message = match operation {
+                        FileOperation::Copy(_) => "Paste completed",
+                        FileOperation::Cut(_) => "Move completed",
+                    };
+                    let target = self.current_path.clone();
+                    self.transfer(operation, target, ConflictPolicy::Fail, message.to_string(), &mut events);
+                }
+            }
+            Command::Transfer { operation, target, conflict } => {
+                let verb = match operation {
+                    FileOperation::Copy(_) => "Copied",
+                    FileOperation::Cut(_) => "Moved",
+                };
+                let message = format!("{} {} items to {}", verb, operation.paths().len(), target.display());
+                self.transfer(operation, target, conflict, message, &mut events);
+            }
+            Command::DeleteSelection => {
+                let paths = self.selected_paths();
+                self.delete(paths, &mut events);
+            }
+            Command::Delete(paths) => self.delete(paths, &mut events),
+            Command::Trash(paths) => self.trash(paths, &mut events),
+            Command::CreateFile(ref path) => {
+                let result = operations::create_new_file(path);
+                changed(Operation::Create, path, None, &result, &mut events);
+                let message = format!("Created file: {}", path.file_name().unwrap_or_default().to_string_lossy());
+                self.finish_change(result, message, command, &mut events);
+            }
+            Command::CreateFolder(ref path) => {
+                let result = operations::create_new_folder(path);
+                changed(Operation::Create, path, None, &result, &mut events);
+                let message = format!("Created folder: {}", path.file_name().unwrap_or_default().to_string_lossy());
+                self.finish_change(result, message, command, &mut events);
+            }
+            Command::Rename { ref path, ref new_name } => {
+                let result = operations::rename_file(path, new_name);
+                changed(Operation::Rename, path, Some(path.with_file_name(new_name)), &result, &mut events);
+                let message = format!("Renamed to: {}", new_name);
+                self.finish_change(result, message, command, &mut events);
+            }
+            Command::RenameBatch(ref renames) => {
+                let result = rename::plan_renames(renames.clone()).and_then(|planned| {
+                    rename::apply_renames_atomically(&planned).map(|()| planned)
+                });
+                match result {
+                    Ok(planned) => {
+                        for (from, to) in &planned {
+                            changed(Operation::Rename, from, Some(to.clone()), &Ok(()), &mut events);
+                        }
+                        let message = format!("Renamed {} items", planned.len());
+                        self.finish_change(Ok(()), message, command, &mut events);
+                    }
+                    Err(error) => {
+                        let destination = renames.iter().find(|(from, _)| from == error.path()).map(|(_, to)| to.clone());
+                        changed(Operation::Rename, error.path(), destination, &Err(error.clone()), &mut events);
+                        self.finish_change(Err(error), String::new(), command, &mut events);
+                    }
+                }
+            }
+        }
+        events
+    }
+
+    fn enter(&mut self, path: PathBuf, events: &mut Vec<Event>) {
+        self.current_path = path.clone();
+        events.push(Event::Navigated(path));
+        self.refresh(events);
+    }
+
+    fn go_to_history(&mut self, index: usize, events: &mut Vec<Event>) {
+        if let Some(path) = self.history.go_to(index) {
+            self.enter(path, events);
+        }
+    }
+
+    fn refresh(&mut self, events: &mut Vec<Event>) {
+        self.selection.clear();
+        events.push(Event::Refreshed);
+        match operations::read_directory(&self.current_path, self.show_hidden) {
+            Ok(mut entries) => {
+                entries.retain(|e| utils::filter_matches(e, &self.filter));
+                utils::sort_entries(&mut entries, self.sort_by, self.sort_ascending);
+                self.entries = entries;
+            }
+            Err(error) => events.push(Event::Error { error, retry: Some(Command::Refresh), skip: None }),
+        }
+    }
+
+    /// Re-reads the folder after a change on disk and reports the outcome.
+    fn finish_change(&mut self, result: Result<(), FileError>, message: String, command: Command, events: &mut Vec<Event>) {
+        match result {
+            Ok(()) => {
+                self.refresh(events);
+                events.push(Event::Message(message));
+            }
+            Err(error) => events.push(Event::Error { error, retry: Some(command), skip: None }),
+        }
+    }
+
+    /// Reports a failure part way through `paths`, where `retry` builds the
+    /// command that works on the given rest of them.
+    fn batch_failed(&mut self, error: FileError, paths: &[PathBuf], failed: usize, retry: impl Fn(Vec<PathBuf>) -> Command, events: &mut Vec<Event>) {
+        if failed > 0 {
+            self.refresh(events);
+        }
+        let skip = (failed + 1 < paths.len()).then(|| retry(paths[failed + 1..].to_vec()));
+        let retry = Some(retry(paths[failed..].to_vec()));
+        events.push(Event::Error { error, retry, skip });
+    }
+
+    /// Copies or moves `operation`'s paths into `target`, stopping at the
+    /// first failure. Moved items are dropped from a cut on the clipboard.
+    fn transfer(&mut self, operation: FileOperation, target: PathBuf, conflict: ConflictPolicy, message: String, events: &mut Vec<Event>) {
+        let paths = operation.paths();
+        let move_items = matches!(operation, FileOperation::Cut(_));
+        for (i, path) in paths.iter().enumerate() {
+            let Some(file_name) = path.file_name() else { continue };
+            let destination = target.join(file_name);
+            let (op, result) = if move_items {
+                (Operation::Move, operations::move_item(path, &destination, conflict))
+            } else {
+                (Operation::Copy, operations::copy_item(path, &destination, conflict))
+            };
+            changed(op, path, Some(destination), &result, events);
+            if let Err(error) = result {
+                if move_items && self.clipboard.forget_moved(&paths[..i]) {
+                    events.push(Event::ClipboardChanged);
+                }
+                let retry = |rest| Command::Transfer { operation: operation.with_paths(rest), target: target.clone(), conflict };
+                self.batch_failed(error, paths, i, retry, events);
+                return;
+            }
+        }
+
+        if move_items && self.clipboard.forget_moved(paths) {
+            events.push(Event::ClipboardChanged);
+        }
+        self.refresh(events);
+        events.push(Event::Message(message));
+    }
+
+    fn delete(&mut self, paths: Vec<PathBuf>, events: &mut Vec<Event>) {
+        for (i, path) in paths.iter().enumerate() {
+            let result = operations::delete_item(path);
+            changed(Operation::Delete, path, None, &result, events);
+            if let Err(error) = result {
+                self.batch_failed(error, &paths, i, Command::Delete, events);
+                return;
+            }
+        }
+        self.refresh(events);
+        events.push(Event::Message(format!("Deleted {} items", paths.len())));
+    }
+
+    fn trash(&mut self, paths: Vec<PathBuf>, events: &mut Vec<Event>) {
+        for (i, path) in paths.iter().enumerate() {
+            let result = trash::trash_item(path).map(|_| ());
+            changed(Operation::Trash, path, None, &result, events);
+            if let Err(error) = result {
+                self.batch_failed(error, &paths, i, Command::Trash, events);
+                return;
+            }
+        }
+        self.refresh(events);
+        events.push(Event::Message(format!("Moved {} items to the trash", paths.len())));
+    }
+}
+
+fn changed(operation: Operation, source: &Path, destination: Option<PathBuf>, result: &Result<(), FileError>, events: &mut Vec<Event>) {
+    events.push(Event::Changed(Change {
+        operation,
+        source: source.to_path_buf(),
+        destination,
+        error: result.as_ref().err().cloned(),
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// A temporary folder holding `a/`, `b/` and `file.txt`, with an
+    /// explorer showing it.
+    fn setup() -> (TempDir, Explorer) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("a")).unwrap();
+        fs::create_dir(dir.path().join("b")).unwrap();
+        fs::write(dir.path().join("file.txt"), "text").unwrap();
+        let mut explorer = Explorer::new(dir.path().to_path_buf());
+        explorer.execute(Command::Refresh);
+        (dir, explorer)
+    }
+
+    fn names(explorer: &Explorer) -> Vec<&str> {
+        explorer.entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    fn changes(events: &[Event]) -> Vec<&Change> {
+        events.iter().filter_map(|e| match e { Event::Changed(change) => Some(change), _ => None }).collect()
+    }
+
+    fn error(events: &[Event]) -> Option<&FileError> {
+        events.iter().find_map(|e| match e { Event::Error { error, .. } => Some(error), _ => None })
+    }
+
+    #[test]
+    fn refresh_lists_the_folder() {
+        let (_dir, explorer) = setup();
+        assert_eq!(names(&explorer), vec!["a", "b", "file.txt"]);
+    }
+
+    #[test]
+    fn navigate_back_and_forward() {
+        let (dir, mut explorer) = setup();
+        let a = dir.path().join("a");
+
+        let events = explorer.execute(Command::Navigate(a.clone()));
+        assert_eq!(events, vec![Event::Navigated(a.clone()), Event::Refreshed]);
+        assert_eq!(explorer.current_path, a);
+
+        let events = explorer.execute(Command::Back);
+        assert_eq!(events[0], Event::Navigated(dir.path().to_path_buf()));
+        assert!(explorer.history.can_go_forward());
+
+        explorer.execute(Command::Forward);
+        assert_eq!(explorer.current_path, a);
+        assert!(!explorer.history.can_go_forward());
+    }
+
+    #[test]
+    fn navigate_to_a_file_does_nothing() {
+        let (dir, mut explorer) = setup();
+        assert!(explorer.execute(Command::Navigate(dir.path().join("file.txt"))).is_empty());
+        assert_eq!(explorer.current_path, dir.path());
+    }
+
+    #[test]
+    fn clicks_select_through_commands() {
+        let (_dir, mut explorer) = setup();
+        assert_eq!(explorer.execute(Command::SelectOnly(1)), vec![Event::SelectionChanged]);
+        explorer.execute(Command::ToggleSelected(2));
+        assert_eq!(explorer.selection.indices, vec![1, 2]);
+        explorer.execute(Command::ToggleSelected(1));
+        assert_eq!(explorer.selection.indices, vec![2]);
+        // Indices past the listing are ignored
+        assert!(explorer.execute(Command::SelectOnly(9)).is_empty());
+        explorer.execute(Command::ClearSelection);
+        assert!(explorer.selection.is_empty());
+    }
+
+    #[test]
+    fn refresh_clears_the_selection() {
+        let (_dir, mut explorer) = setup();
+        explorer.execute(Command::SelectOnly(0));
+        explorer.execute(Command::Refresh);
+        assert!(explorer.selection.is_empty());
+    }
+
+    #[test]
+    fn create_file_and_folder() {
+        let (dir, mut explorer) = setup();
+        let events = explorer.execute(Command::CreateFile(dir.path().join("new.txt")));
+        assert_eq!(changes(&events)[0].operation, Operation::Create);
+        assert!(changes(&events)[0].error.is_none());
+        assert!(events.contains(&Event::Refreshed));
+        assert!(dir.path().join("new.txt").is_file());
+
+        explorer.execute(Command::CreateFolder(dir.path().join("folder")));
+        assert!(dir.path().join("folder").is_dir());
+        assert!(names(&explorer).contains(&"folder"));
+    }
+
+    #[test]
+    fn failed_create_can_be_retried() {
+        let (dir, mut explorer) = setup();
+        let events = explorer.execute(Command::CreateFolder(dir.path().join("a")));
+        assert!(changes(&events)[0].error.is_some());
+        let Some(Event::Error { retry: Some(retry), .. }) = events.last() else { panic!("no retry offered") };
+        assert_eq!(retry, &Command::CreateFolder(dir.path().join("a")));
+
+        // The retry still targets the original folder after navigating away
+        explorer.execute(Command::Navigate(dir.path().join("b")));
+        fs::remove_dir(dir.path().join("a")).unwrap();
+        explorer.execute(retry.clone());
+        assert!(dir.path().join("a").is_dir());
+        assert!(!dir.path().join("b/a").exists());
+    }
+
+    #[test]
+    fn rename_reports_both_paths() {
+        let (dir, mut explorer) = setup();
+        let path = dir.path().join("file.txt");
+        let events = explorer.execute(Command::Rename { path: path.clone(), new_name: "renamed.txt".to_string() });
+        let change = changes(&events)[0];
+        assert_eq!(change.source, path);
+        assert_eq!(change.destination, Some(dir.path().join("renamed.txt")));
+        assert_eq!(names(&explorer), vec!["a", "b", "renamed.txt"]);
+    }
+
+    #[test]
+    fn copies_and_new_items_put_their_destinations_at_risk() {
+        let (dir, explorer) = setup();
+        let (file, out) = (dir.path().join("file.txt"), dir.path().join("a"));
+        let transfer = |operation| Command::Transfer { operation, target: out.clone(), conflict: ConflictPolicy::Fail };
+
+        assert_eq!(explorer.paths_at_risk(&transfer(FileOperation::Copy(vec![file.clone()]))), vec![out.join("file.txt")]);
+        assert_eq!(explorer.paths_at_risk(&transfer(FileOperation::Cut(vec![file.clone()]))), vec![file.clone(), out.join("file.txt")]);
+        assert_eq!(explorer.paths_at_risk(&Command::CreateFolder(out.join("new"))), vec![out.join("new")]);
+        let rename = Command::Rename { path: file.clone(), new_name: "renamed.txt".to_string() };
+        assert_eq!(explorer.paths_at_risk(&rename), vec![file, dir.path().join("renamed.txt")]);
+    }
+
+    #[test]
+    fn delete_selection() {
+        let (dir, mut explorer) = setup();
+        explorer.execute(Command::Select(vec![dir.path().join("a"), dir.path().join("file.txt")]));
+        let events = explorer.execute(Command::DeleteSelection);
+        assert_eq!(changes(&events).len(), 2);
+        assert_eq!(events.last(), Some(&Event::Message("Deleted 2 items".to_string())));
+        assert_eq!(names(&explorer), vec!["b"]);
+    }
+
+    #[test]
+    fn delete_stops_at_the_first_failure() {
+        let (dir, mut explorer) = setup();
+        let paths = vec![dir.path().join("a"), dir.path().join("missing"), dir.path().join("b")];
+        let events = explorer.execute(Command::Delete(paths.clone()));
+        assert_eq!(error(&events).map(|e| e.path()), Some(paths[1].as_path()));
+        assert!(matches!(events.last(), Some(Event::Error { retry: Some(Command::Delete(rest)), skip: Some(Command::Delete(after)), .. })
+            if rest == &paths[1..] && after == &paths[2..]));
+        assert!(dir.path().join("b").exists());
+    }
+}
@@ -0,0 +1,90 @@
+use std::path::{Path, PathBuf};
+
+/// Back/forward navigation history. `index` points at the current folder.
+#[derive(Clone, Debug)]
+pub struct History {
+    pub entries: Vec<PathBuf>,
+    pub index: usize,
+}
+
+impl History {
+    pub fn new(start: PathBuf) -> Self {
+        Self { entries: vec![start], index: 0 }
+    }
+
+    pub fn current(&self) -> &Path {
+        &self.entries[self.index]
+    }
+
+    /// Records a visit, dropping everything after the current entry.
+    pub fn push(&mut self, path: PathBuf) {
+        self.entries.truncate(self.index + 1);
+        self.entries.push(path);
+        self.index = self.entries.len() - 1;
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.index > 0
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        self.index + 1 < self.entries.len()
+    }
+
+    /// Moves to entry `index` and returns its path.
+    pub fn go_to(&mut self, index: usize) -> Option<PathBuf> {
+        let path = self.entries.get(index)?.clone();
+        self.index = index;
+        Some(path)
+    }
+
+    /// Indices of the entries behind the current one, nearest first.
+    pub fn back_entries(&self) -> Vec<usize> {
+        (0..self.index).rev().collect()
+    }
+
+    /// Indices of the entries ahead of the current one, nearest first.
+    pub fn forward_entries(&self) -> Vec<usize> {
+        (self.index + 1..self.entries.len()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(paths: &[&str]) -> History {
+        let mut history = History::new(PathBuf::from(paths[0]));
+        for path in &paths[1..] {
+            history.push(PathBuf::from(path));
+        }
+        history
+    }
+
+    #[test]
+    fn push_moves_to_the_new_entry() {
+        let history = history(&["/a", "/b", "/c"]);
+        assert_eq!(history.current(), Path::new("/c"));
+        assert!(history.can_go_back());
+        assert!(!history.can_go_forward());
+        assert_eq!(history.back_entries(), vec![1, 0]);
+    }
+
+    #[test]
+    fn push_after_going_back_drops_the_forward_entries() {
+        let mut history = history(&["/a", "/b", "/c"]);
+        assert_eq!(history.go_to(0), Some(PathBuf::from("/a")));
+        assert_eq!(history.forward_entries(), vec![1, 2]);
+
+        history.push(PathBuf::from("/d"));
+        assert_eq!(history.entries, vec![PathBuf::from("/a"), PathBuf::from("/d")]);
+        assert!(!history.can_go_forward());
+    }
+
+    #[test]
+    fn go_to_outside_the_history_changes_nothing() {
+        let mut history = history(&["/a", "/b"]);
+        assert_eq!(history.go_to(5), None);
+        assert_eq!(history.index, 1);
+    }
+}
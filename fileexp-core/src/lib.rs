@@ -0,0 +1,23 @@
+//! UI-free core of the file explorer: the directory listing, selection,
+//! navigation history, file clipboard and bookmark store, plus the file
+//! operations behind them. Front ends drive an [`Explorer`] with
+//! [`Command`]s and react to the [`Event`]s it returns.
+
+pub mod archive;
+pub mod audit;
+pub mod bookmarks;
+pub mod clipboard;
+pub mod error;
+pub mod explorer;
+pub mod history;
+pub mod models;
+pub mod operations;
+pub mod plan;
+pub mod rename;
+pub mod replay;
+pub mod selection;
+pub mod trash;
+pub mod utils;
+
+pub use error::{FileError, Operation};
+pub use explorer::{Change, Command, Event, Explorer};
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use chrono::{DateTime, Local};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FileOperation {
+    Copy(Vec<PathBuf>),
+    Cut(Vec<PathBuf>),
+}
+
+impl FileOperation {
+    pub fn paths(&self) -> &[PathBuf] {
+        match self {
+            FileOperation::Copy(paths) | FileOperation::Cut(paths) => paths,
+        }
+    }
+
+    /// The same kind of operation on other paths.
+    pub fn with_paths(&self, paths: Vec<PathBuf>) -> Self {
+        match self {
+            FileOperation::Copy(_) => FileOperation::Copy(paths),
+            FileOperation::Cut(_) => FileOperation::Cut(paths),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FileEntry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: DateTime<Local>,
+    pub name: String,
+    pub extension: String,
+}
+
+/// What to do when a copy, move or restore target already exists.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ConflictPolicy {
+    Skip,
+    Overwrite,
+    /// Pick a free name like "file (2).txt".
+    Rename,
+    Fail,
+}
+
+impl std::str::FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(Self::Skip),
+            "overwrite" => Ok(Self::Overwrite),
+            "rename" => Ok(Self::Rename),
+            "fail" => Ok(Self::Fail),
+            _ => Err(format!("unknown conflict policy '{}' (expected skip, overwrite, rename or fail)", s)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
+pub enum SortBy {
+    Name,
+    Size,
+    Modified,
+    Type,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Bookmark {
+    pub name: String,
+    pub path: PathBuf,
+}
@@ -0,0 +1,367 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+use chrono::Local;
+use crate::archive;
+use crate::error::{FileError, Operation};
+use crate::models::{ConflictPolicy, FileEntry};
+
+pub fn create_new_file(file_path: &Path) -> Result<(), FileError> {
+    if archive::is_member(file_path) {
+        return Err(archive::read_only(Operation::Create, file_path));
+    }
+    File::create(file_path).map(|_| ()).map_err(|e| FileError::io(Operation::Create, file_path, &e))
+}
+
+pub fn create_new_folder(folder_path: &Path) -> Result<(), FileError> {
+    if archive::is_member(folder_path) {
+        return Err(archive::read_only(Operation::Create, folder_path));
+    }
+    fs::create_dir(folder_path).map_err(|e| FileError::io(Operation::Create, folder_path, &e))
+}
+
+pub fn rename_file(old_path: &Path, new_name: &str) -> Result<(), FileError> {
+    if archive::is_member(old_path) {
+        return Err(archive::read_only(Operation::Rename, old_path));
+    }
+    let new_path = old_path.parent().unwrap().join(new_name);
+    fs::rename(old_path, &new_path).map_err(|e| FileError::io(Operation::Rename, old_path, &e))
+}
+
+pub fn delete_item(path: &Path) -> Result<(), FileError> {
+    if archive::is_member(path) {
+        return Err(archive::read_only(Operation::Delete, path));
+    }
+    // A link to a folder is removed, not the folder it points to
+    let is_dir = fs::symlink_metadata(path).is_ok_and(|m| m.is_dir());
+    let result = if is_dir {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    result.map_err(|e| FileError::io(Operation::Delete, path, &e))
+}
+
+/// Copies `source` to `destination`, settling an existing destination with
+/// `policy`. Symlinks are copied as links rather than followed.
+pub fn copy_item(source: &Path, destination: &Path, policy: ConflictPolicy) -> Result<(), FileError> {
+    if source == destination {
+        return Err(FileError::kind_of(Operation::Copy, destination, io::ErrorKind::AlreadyExists));
+    }
+    if archive::is_member(destination) {
+        return Err(archive::read_only(Operation::Copy, destination));
+    }
+    copy_tree(source, destination, policy, &mut HashSet::new())
+}
+
+/// Whether `destination` lies inside the folder `source`, also through links.
+fn inside(destination: &Path, source: &Path) -> bool {
+    if destination.starts_with(source) {
+        return true;
+    }
+    let parent = destination.parent().and_then(|p| p.canonicalize().ok());
+    matches!((parent, source.canonicalize()), (Some(parent), Ok(source)) if parent.starts_with(&source))
+}
+
+fn copy_tree(source: &Path, destination: &Path, policy: ConflictPolicy, visited: &mut HashSet<PathBuf>) -> Result<(), FileError> {
+    let metadata = match fs::symlink_metadata(source) {
+        Ok(metadata) => metadata,
+        // Items inside an archive are extracted
+        Err(_) if archive::is_member(source) => {
+            return match resolve_conflict(destination, policy, Operation::Copy)? {
+                Some(destination) => archive::copy_out(source, &destination),
+                None => Ok(()),
+            };
+        }
+        Err(e) => return Err(FileError::io(Operation::Copy, source, &e)),
+    };
+    let file_type = metadata.file_type();
+    if file_type.is_dir() && inside(destination, source) {
+        return Err(FileError::invalid(Operation::Copy, source, "cannot copy a folder into itself"));
+    }
+
+    // Overwriting merges a folder into an existing one instead of replacing it
+    let merge = file_type.is_dir()
+        && policy == ConflictPolicy::Overwrite
+        && fs::symlink_metadata(destination).is_ok_and(|m| m.is_dir());
+    let destination = if merge {
+        destination.to_path_buf()
+    } else {
+        match resolve_conflict(destination, policy, Operation::Copy)? {
+            Some(destination) => destination,
+            None => return Ok(()),
+        }
+    };
+
+    if file_type.is_symlink() {
+        let target = fs::read_link(source).map_err(|e| FileError::io(Operation::Read, source, &e))?;
+        symlink(&target, &destination).map_err(|e| FileError::io(Operation::Copy, source, &e))
+    } else if file_type.is_dir() {
+        // A folder reached twice, e.g. through a bind mount, is copied once
+        let real = source.canonicalize().map_err(|e| FileError::io(Operation::Read, source, &e))?;
+        if !visited.insert(real) {
+            return Ok(());
+        }
+        if !merge {
+            fs::create_dir(&destination).map_err(|e| FileError::io(Operation::Create, &destination, &e))?;
+        }
+        let entries = fs::read_dir(source).map_err(|e| FileError::io(Operation::Read, source, &e))?;
+        for entry in entries.filter_map(Result::ok) {
+            copy_tree(&entry.path(), &destination.join(entry.file_name()), policy, visited)?;
+        }
+        Ok(())
+    } else if file_type.is_file() {
+        fs::copy(source, &destination)
+            .map(|_| ())
+            .map_err(|e| FileError::io(Operation::Copy, source, &e))
+    } else {
+        Err(FileError::invalid(Operation::Copy, source, "unknown file type"))
+    }
+}
+
+/// Moves `source` to `destination`, settling an existing destination with
+/// `policy`.
+pub fn move_item(source: &Path, destination: &Path, policy: ConflictPolicy) -> Result<(), FileError> {
+    if let Some(path) = [source, destination].into_iter().find(|p| archive::is_member(p)) {
+        return Err(archive::read_only(Operation::Move, path));
+    }
+    let is_dir = |path: &Path| fs::symlink_metadata(path).is_ok_and(|m| m.is_dir());
+    let merge = policy == ConflictPolicy::Overwrite && is_dir(source) && is_dir(destination);
+    let target = if merge {
+        destination.to_path_buf()
+    } else {
+        match resolve_conflict(destination, policy, Operation::Move)? {
+            Some(target) => target,
+            None => return Ok(()),
+        }
+    };
+    match fs::rename(source, &target) {
+        Ok(_) => Ok(()),
+        // Renaming only works within one filesystem, so fall back to copy + delete
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => move_across(source, &target, policy),
+        // A folder can't be renamed over one that has files, so merge into it instead
+        Err(e) if merge && matches!(e.kind(), io::ErrorKind::DirectoryNotEmpty | io::ErrorKind::AlreadyExists) => {
+            move_across(source, &target, policy)
+        }
+        Err(e) => Err(FileError::io(Operation::Move, source, &e)),
+    }
+}
+
+/// Moves by copying and then deleting the source, which only happens once
+/// the whole copy succeeded.
+fn move_across(source: &Path, target: &Path, policy: ConflictPolicy) -> Result<(), FileError> {
+    copy_item(source, target, policy)?;
+    delete_item(source)
+}
+
+/// Where an item bound for `destination` should go under `policy`, or `None`
+/// to skip it. `Overwrite` removes the existing item first.
+pub fn resolve_conflict(destination: &Path, policy: ConflictPolicy, operation: Operation) -> Result<Option<PathBuf>, FileError> {
+    if fs::symlink_metadata(destination).is_err() {
+        return Ok(Some(destination.to_path_buf()));
+    }
+    match policy {
+        ConflictPolicy::Skip => Ok(None),
+        ConflictPolicy::Overwrite => {
+            delete_item(destination)?;
+            Ok(Some(destination.to_path_buf()))
+        }
+        ConflictPolicy::Rename => Ok(Some(unique_path(destination))),
+        ConflictPolicy::Fail => Err(FileError::kind_of(operation, destination, io::ErrorKind::AlreadyExists)),
+    }
+}
+
+/// `path` with " (2)", " (3)", ... added to the name until nothing exists there.
+pub fn unique_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    let parent = path.parent().unwrap_or(Path::new(""));
+    
+    let mut candidate = path.to_path_buf();
+    let mut n = 2;
+    while fs::symlink_metadata(&candidate).is_ok() {
+        candidate = parent.join(format!("{} ({}){}", stem, n, extension));
+        n += 1;
+    }
+    candidate
+}
+
+pub fn open_file(path: &Path) -> Result<(), FileError> {
+    // Other programs can't look inside archives, so they get a copy
+    if archive::is_member(path) {
+        let copy = archive::temporary_copy(path)?;
+        return open::that(&copy).map_err(|e| FileError::io(Operation::Open, path, &e));
+    }
+    open::that(path).map_err(|e| FileError::io(Operation::Open, path, &e))
+}
+
+/// Whether `path` can be listed: a folder, a browsable archive or a folder
+/// inside one.
+pub fn is_folder(path: &Path) -> bool {
+    path.is_dir() || archive::is_folder(path)
+}
+
+pub fn read_directory(path: &PathBuf, show_hidden: bool) -> Result<Vec<FileEntry>, FileError> {
+    if let Some((archive, inner)) = archive::locate(path) {
+        return archive::read_folder(&archive, &inner, show_hidden);
+    }
+    match fs::read_dir(path) {
+        Ok(entries) => {
+            let mut file_entries: Vec<FileEntry> = entries
+                .filter_map(Result::ok)
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    let file_name = path.file_name()?.to_str()?.to_string();
+                    
+                    if !show_hidden && file_name.starts_with('.') {
+                        return None;
+                    }
+                    
+                    let metadata = entry.metadata().ok()?;
+                    let modified = metadata.modified().ok()?;
+                    let modified = chrono::DateTime::<Local>::from(modified);
+                    
+                    let extension = path.extension()
+                        .and_then(|ext| ext.to_str())
+                        .unwrap_or("")
+                        .to_string();
+                    
+                    Some(FileEntry {
+                        path: path.clone(),
+                        is_dir: path.is_dir(),
+                        size: if metadata.is_file() { metadata.len() } else { 0 },
+                        modified,
+                        name: file_name,
+                        extension,
+                    })
+                })
+                .collect();
+
+            file_entries.sort_by(|a, b| {
+                if a.is_dir && !b.is_dir {
+                    std::cmp::Ordering::Less
+                } else if !a.is_dir && b.is_dir {
+                    std::cmp::Ordering::Greater
+                } else {
+                    a.name.cmp(&b.name)
+                }
+            });
+
+            Ok(file_entries)
+        }
+        Err(e) => Err(FileError::io(Operation::Read, path, &e)),
+    }
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// `src/` holding `file.txt`, `sub/inner.txt` and links to both, a link
+    /// back up to `src` and a dangling link, next to an empty `out/`.
+    fn setup() -> (TempDir, PathBuf, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let (src, out) = (dir.path().join("src"), dir.path().join("out"));
+        fs::create_dir_all(src.join("sub")).unwrap();
+        fs::create_dir(&out).unwrap();
+        fs::write(src.join("file.txt"), "file").unwrap();
+        fs::write(src.join("sub/inner.txt"), "inner").unwrap();
+        symlink("file.txt", src.join("file-link")).unwrap();
+        symlink("sub", src.join("sub-link")).unwrap();
+        symlink("..", src.join("sub/loop")).unwrap();
+        symlink("missing", src.join("dangling")).unwrap();
+        (dir, src, out)
+    }
+
+    fn is_link(path: &Path) -> bool {
+        fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink())
+    }
+
+    #[test]
+    fn links_are_copied_as_links() {
+        let (_dir, src, out) = setup();
+        copy_item(&src.join("sub-link"), &out.join("sub-link"), ConflictPolicy::Fail).unwrap();
+        copy_item(&src.join("dangling"), &out.join("dangling"), ConflictPolicy::Fail).unwrap();
+        assert_eq!(fs::read_link(out.join("sub-link")).unwrap(), PathBuf::from("sub"));
+        assert_eq!(fs::read_link(out.join("dangling")).unwrap(), PathBuf::from("missing"));
+    }
+
+    #[test]
+    fn a_link_loop_is_copied_once() {
+        let (_dir, src, out) = setup();
+        let copy = out.join("src");
+        copy_item(&src, &copy, ConflictPolicy::Fail).unwrap();
+        assert_eq!(fs::read_to_string(copy.join("sub/inner.txt")).unwrap(), "inner");
+        assert!(is_link(&copy.join("sub/loop")));
+        assert!(is_link(&copy.join("file-link")));
+        assert!(is_link(&copy.join("sub-link")));
+    }
+
+    #[test]
+    fn copying_into_itself_through_a_link_is_refused() {
+        let (dir, src, _) = setup();
+        let link = dir.path().join("link");
+        symlink(&src, &link).unwrap();
+        let error = copy_item(&src, &link.join("sub/copy"), ConflictPolicy::Fail).unwrap_err();
+        assert_eq!(error.reason(), "cannot copy a folder into itself");
+    }
+
+    #[test]
+    fn existing_files_follow_the_policy() {
+        let (_dir, src, out) = setup();
+        let target = out.join("file.txt");
+        fs::write(&target, "old").unwrap();
+
+        assert!(copy_item(&src.join("file.txt"), &target, ConflictPolicy::Fail).is_err());
+        copy_item(&src.join("file.txt"), &target, ConflictPolicy::Skip).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "old");
+        copy_item(&src.join("file.txt"), &target, ConflictPolicy::Rename).unwrap();
+        assert_eq!(fs::read_to_string(out.join("file (2).txt")).unwrap(), "file");
+        copy_item(&src.join("file.txt"), &target, ConflictPolicy::Overwrite).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "file");
+    }
+
+    #[test]
+    fn moving_across_filesystems_keeps_links() {
+        let (_dir, src, out) = setup();
+        let target = out.join("src");
+        move_across(&src, &target, ConflictPolicy::Fail).unwrap();
+        assert!(!src.exists());
+        assert_eq!(fs::read_to_string(target.join("sub/inner.txt")).unwrap(), "inner");
+        assert!(is_link(&target.join("sub/loop")));
+        assert!(is_link(&target.join("dangling")));
+    }
+
+    #[test]
+    fn a_failed_copy_keeps_the_source_when_moving() {
+        let (_dir, src, out) = setup();
+        fs::create_dir(out.join("src")).unwrap();
+        assert!(move_across(&src, &out.join("src"), ConflictPolicy::Fail).is_err());
+        assert!(src.join("file.txt").exists());
+    }
+
+    #[test]
+    fn folders_are_only_merged_when_overwriting() {
+        let (_dir, src, out) = setup();
+        fs::create_dir_all(out.join("src/sub")).unwrap();
+        fs::write(out.join("src/sub/kept.txt"), "kept").unwrap();
+
+        let error = move_item(&src, &out.join("src"), ConflictPolicy::Fail).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert!(src.exists());
+
+        move_item(&src, &out.join("src"), ConflictPolicy::Overwrite).unwrap();
+        assert!(!src.exists());
+        assert_eq!(fs::read_to_string(out.join("src/sub/inner.txt")).unwrap(), "inner");
+        assert_eq!(fs::read_to_string(out.join("src/sub/kept.txt")).unwrap(), "kept");
+    }
+
+    #[test]
+    fn deleting_a_link_keeps_its_target() {
+        let (_dir, src, _) = setup();
+        delete_item(&src.join("sub-link")).unwrap();
+        assert!(src.join("sub/inner.txt").exists());
+    }
+}
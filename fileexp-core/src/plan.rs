@@ -0,0 +1,368 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::archive;
+use crate::explorer::Command;
+use crate::models::{ConflictPolicy, FileOperation};
+use crate::operations;
+
+/// What a planned step does to its destination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Effect {
+    Create,
+    /// Replaces an existing file.
+    Overwrite,
+    /// Adds to a folder that already exists.
+    Merge,
+    Remove,
+}
+
+impl Effect {
+    /// Whether the step clashes with something already at the destination.
+    pub fn is_conflict(self) -> bool {
+        matches!(self, Effect::Overwrite | Effect::Merge)
+    }
+}
+
+/// One file or folder touched by a planned item.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlannedFile {
+    pub source: PathBuf,
+    /// Where the change happens: the destination, or the path removed.
+    pub path: PathBuf,
+    pub effect: Effect,
+    pub is_dir: bool,
+    pub bytes: u64,
+    /// Unchecked files and folders are left as they are.
+    pub selected: bool,
+}
+
+/// A top-level item of a paste or delete, which the user can leave out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlanItem {
+    pub source: PathBuf,
+    pub destination: Option<PathBuf>,
+    pub effect: Effect,
+    /// Set when the item can't be done at all, e.g. a folder into itself.
+    pub problem: Option<String>,
+    /// The item and everything below it, in walk order.
+    pub files: Vec<PlannedFile>,
+    pub bytes: u64,
+    pub selected: bool,
+}
+
+impl PlanItem {
+    pub fn conflicts(&self) -> usize {
+        self.files.iter().filter(|f| f.effect.is_conflict()).count()
+    }
+
+    /// The file at `index` and everything below it.
+    fn subtree(&self, index: usize) -> &[PlannedFile] {
+        let root = &self.files[index].path;
+        let len = self.files[index..].iter().take_while(|f| f.path.starts_with(root)).count();
+        &self.files[index..index + len]
+    }
+
+    /// Checks or unchecks the file at `index` together with its contents.
+    pub fn set_selected(&mut self, index: usize, selected: bool) {
+        let len = self.subtree(index).len();
+        for file in &mut self.files[index..index + len] {
+            file.selected = selected;
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlanKind {
+    Transfer { operation: FileOperation, target: PathBuf },
+    Delete,
+}
+
+/// The full effect of a bulk paste or delete, worked out before running it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plan {
+    pub kind: PlanKind,
+    pub items: Vec<PlanItem>,
+}
+
+impl Plan {
+    /// Copying or moving `operation`'s paths into `target`. Setting `cancel`
+    /// stops walking the folders early.
+    pub fn transfer(operation: &FileOperation, target: &Path, cancel: &AtomicBool) -> Self {
+        let items = operation.paths().iter().map(|source| {
+            let destination = target.join(source.file_name().unwrap_or_default());
+            let mut files = Vec::new();
+            let source_is_dir = source.is_dir() || archive::member_entry(source).is_some_and(|e| e.is_dir);
+            let problem = if source == &destination {
+                Some("already in this folder".to_string())
+            } else if source_is_dir && destination.starts_with(source) {
+                Some("cannot copy a folder into itself".to_string())
+            } else if fs::symlink_metadata(source).is_err() && !archive::is_member(source) {
+                Some("no longer exists".to_string())
+            } else if archive::is_member(&destination) {
+                Some("files inside an archive can't be changed".to_string())
+            } else if destination.exists() && destination.is_dir() != source_is_dir {
+                Some("something of another type has this name".to_string())
+            } else {
+                plan_copy(source, &destination, &mut files, cancel);
+                None
+            };
+            item(source.clone(), Some(destination), problem, files)
+        }).collect();
+        Self { kind: PlanKind::Transfer { operation: operation.clone(), target: target.to_path_buf() }, items }
+    }
+
+    pub fn delete(paths: &[PathBuf], cancel: &AtomicBool) -> Self {
+        let items = paths.iter().map(|path| {
+            let mut files = Vec::new();
+            let problem = if fs::symlink_metadata(path).is_err() {
+                Some("no longer exists".to_string())
+            } else {
+                plan_remove(path, &mut files, cancel);
+                None
+            };
+            item(path.clone(), None, problem, files)
+        }).collect();
+        Self { kind: PlanKind::Delete, items }
+    }
+
+    pub fn selected(&self) -> impl Iterator<Item = &PlanItem> {
+        self.items.iter().filter(|item| item.selected)
+    }
+
+    fn selected_files(&self) -> impl Iterator<Item = &PlannedFile> {
+        self.selected().flat_map(|item| &item.files).filter(|f| f.selected)
+    }
+
+    /// Bytes written, or freed for a delete, by the selected files.
+    pub fn total_bytes(&self) -> u64 {
+        self.selected_files().map(|f| f.bytes).sum()
+    }
+
+    pub fn count(&self, effect: Effect) -> usize {
+        self.selected_files().filter(|f| f.effect == effect).count()
+    }
+
+    /// The commands that run the selected items, in order. Items checked as
+    /// a whole run together; a folder with unchecked contents is recreated
+    /// at the destination and only its checked contents are handled.
+    pub fn commands(&self) -> Vec<Command> {
+        let mut commands = Vec::new();
+        let (whole, partial): (Vec<&PlanItem>, Vec<&PlanItem>) = self.selected()
+            .partition(|item| item.files.iter().all(|f| f.selected));
+        if !whole.is_empty() {
+            let target = match &self.kind {
+                PlanKind::Transfer { target, .. } => target.as_path(),
+                PlanKind::Delete => Path::new(""),
+            };
+            commands.push(self.command(whole.iter().map(|item| item.source.clone()).collect(), target));
+        }
+        for item in partial {
+            self.partial_commands(item, 0, &mut commands);
+        }
+        commands
+    }
+
+    /// Commands for the checked contents of the folder at `index` of `item`.
+    fn partial_commands(&self, item: &PlanItem, index: usize, commands: &mut Vec<Command>) {
+        let folder = &item.files[index];
+        if matches!(self.kind, PlanKind::Transfer { .. }) && folder.effect == Effect::Create {
+            commands.push(Command::CreateFolder(folder.path.clone()));
+        }
+        let mut whole = Vec::new();
+        let mut child = index + 1;
+        while child < item.files.len() && item.files[child].path.starts_with(&folder.path) {
+            let subtree = item.subtree(child);
+            if subtree.iter().all(|f| f.selected) {
+                whole.push(item.files[child].source.clone());
+            } else if subtree.iter().any(|f| f.selected) {
+                self.partial_commands(item, child, commands);
+            }
+            child += subtree.len();
+        }
+        if !whole.is_empty() {
+            commands.push(self.command(whole, &folder.path));
+        }
+    }
+
+    /// This plan's operation on `paths`; copies and moves go into `target`.
+    /// The preview already showed what gets replaced or merged, so existing
+    /// items are overwritten.
+    fn command(&self, paths: Vec<PathBuf>, target: &Path) -> Command {
+        match &self.kind {
+            PlanKind::Transfer { operation, .. } => Command::Transfer {
+                operation: operation.with_paths(paths),
+                target: target.to_path_buf(),
+                conflict: ConflictPolicy::Overwrite,
+            },
+            PlanKind::Delete => Command::Delete(paths),
+        }
+    }
+}
+
+fn item(source: PathBuf, destination: Option<PathBuf>, problem: Option<String>, files: Vec<PlannedFile>) -> PlanItem {
+    let effect = files.first().map_or(Effect::Create, |f| f.effect);
+    let bytes = files.iter().map(|f| f.bytes).sum();
+    // Items that can't run and ones that clash start unchecked
+    let selected = problem.is_none() && !files.iter().any(|f| f.effect == Effect::Overwrite);
+    PlanItem { source, destination, effect, problem, files, bytes, selected }
+}
+
+fn plan_copy(source: &Path, destination: &Path, files: &mut Vec<PlannedFile>, cancel: &AtomicBool) {
+    if cancel.load(Ordering::Relaxed) {
+        return;
+    }
+    // Items inside an archive are described by its listing
+    let (is_dir, len) = match fs::symlink_metadata(source) {
+        Ok(metadata) => (metadata.is_dir(), metadata.len()),
+        Err(_) => match archive::member_entry(source) {
+            Some(entry) => (entry.is_dir, entry.size),
+            None => return,
+        },
+    };
+    let existing = fs::symlink_metadata(destination).ok();
+    let effect = match &existing {
+        None => Effect::Create,
+        Some(existing) if is_dir && existing.is_dir() => Effect::Merge,
+        Some(_) => Effect::Overwrite,
+    };
+    let bytes = if is_dir { 0 } else { len };
+    files.push(PlannedFile { source: source.to_path_buf(), path: destination.to_path_buf(), effect, is_dir, bytes, selected: true });
+
+    if !is_dir {
+        return;
+    }
+    if let Ok(entries) = fs::read_dir(source) {
+        let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            plan_copy(&entry.path(), &destination.join(entry.file_name()), files, cancel);
+        }
+    } else if let Ok(entries) = operations::read_directory(&source.to_path_buf(), true) {
+        for entry in entries {
+            plan_copy(&entry.path, &destination.join(&entry.name), files, cancel);
+        }
+    }
+}
+
+fn plan_remove(path: &Path, files: &mut Vec<PlannedFile>, cancel: &AtomicBool) {
+    if cancel.load(Ordering::Relaxed) {
+        return;
+    }
+    let Ok(metadata) = fs::symlink_metadata(path) else { return };
+    let is_dir = metadata.is_dir();
+    let bytes = if is_dir { 0 } else { metadata.len() };
+    files.push(PlannedFile { source: path.to_path_buf(), path: path.to_path_buf(), effect: Effect::Remove, is_dir, bytes, selected: true });
+
+    if is_dir && let Ok(entries) = fs::read_dir(path) {
+        let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            plan_remove(&entry.path(), files, cancel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explorer::Explorer;
+    use tempfile::TempDir;
+
+    /// `dir/` holding `sub/z.txt`, `x.txt` and `y.txt`, next to an empty `out/`.
+    fn setup() -> TempDir {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("dir/sub")).unwrap();
+        fs::create_dir(root.path().join("out")).unwrap();
+        for name in ["dir/sub/z.txt", "dir/x.txt", "dir/y.txt"] {
+            fs::write(root.path().join(name), name).unwrap();
+        }
+        root
+    }
+
+    fn index_of(item: &PlanItem, path: &Path) -> usize {
+        item.files.iter().position(|f| f.path == path).unwrap()
+    }
+
+    fn run(root: &TempDir, commands: Vec<Command>) {
+        let mut explorer = Explorer::new(root.path().to_path_buf());
+        for command in commands {
+            explorer.execute(command);
+        }
+    }
+
+    #[test]
+    fn fully_checked_items_run_as_one_command() {
+        let root = setup();
+        let dir = root.path().join("dir");
+        let plan = Plan::transfer(&FileOperation::Copy(vec![dir.clone()]), &root.path().join("out"), &AtomicBool::new(false));
+        assert_eq!(plan.items[0].files.len(), 5);
+        assert_eq!(plan.commands(), vec![Command::Transfer {
+            operation: FileOperation::Copy(vec![dir]),
+            target: root.path().join("out"),
+            conflict: ConflictPolicy::Overwrite,
+        }]);
+    }
+
+    #[test]
+    fn unchecked_files_are_left_out_of_a_copy() {
+        let root = setup();
+        let (dir, out) = (root.path().join("dir"), root.path().join("out/dir"));
+        let mut plan = Plan::transfer(&FileOperation::Copy(vec![dir.clone()]), &root.path().join("out"), &AtomicBool::new(false));
+        let index = index_of(&plan.items[0], &out.join("y.txt"));
+        plan.items[0].set_selected(index, false);
+
+        let commands = plan.commands();
+        assert_eq!(commands, vec![
+            Command::CreateFolder(out.clone()),
+            Command::Transfer {
+                operation: FileOperation::Copy(vec![dir.join("sub"), dir.join("x.txt")]),
+                target: out.clone(),
+                conflict: ConflictPolicy::Overwrite,
+            },
+        ]);
+        run(&root, commands);
+        assert!(out.join("sub/z.txt").is_file());
+        assert!(out.join("x.txt").is_file());
+        assert!(!out.join("y.txt").exists());
+    }
+
+    #[test]
+    fn unchecking_a_folder_leaves_out_its_contents() {
+        let root = setup();
+        let dir = root.path().join("dir");
+        let mut plan = Plan::delete(std::slice::from_ref(&dir), &AtomicBool::new(false));
+        let index = index_of(&plan.items[0], &dir.join("sub"));
+        plan.items[0].set_selected(index, false);
+        assert!(!plan.items[0].files[index + 1].selected);
+        assert_eq!(plan.count(Effect::Remove), 3);
+
+        let commands = plan.commands();
+        assert_eq!(commands, vec![Command::Delete(vec![dir.join("x.txt"), dir.join("y.txt")])]);
+        run(&root, commands);
+        assert!(dir.join("sub/z.txt").is_file());
+        assert!(!dir.join("x.txt").exists());
+    }
+
+    #[test]
+    fn a_folder_keeps_its_unchecked_file_when_moving() {
+        let root = setup();
+        let (dir, out) = (root.path().join("dir"), root.path().join("out/dir"));
+        let mut plan = Plan::transfer(&FileOperation::Cut(vec![dir.clone()]), &root.path().join("out"), &AtomicBool::new(false));
+        let index = index_of(&plan.items[0], &out.join("sub/z.txt"));
+        plan.items[0].set_selected(index, false);
+
+        run(&root, plan.commands());
+        assert!(out.join("sub").is_dir());
+        assert!(out.join("x.txt").is_file());
+        assert!(dir.join("sub/z.txt").is_file());
+        assert!(!dir.join("x.txt").exists());
+    }
+
+    #[test]
+    fn cancelling_stops_the_walk() {
+        let root = setup();
+        let plan = Plan::delete(&[root.path().join("dir")], &AtomicBool::new(true));
+        assert!(plan.items[0].files.is_empty());
+    }
+}
@@ -0,0 +1,532 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Local};
+use regex::Regex;
+use crate::error::{FileError, Operation};
+use crate::models::FileEntry;
+
+/// Builds the new name for the `index`-th file from a template. Supported
+/// tokens: `{name}` (name without extension), `{ext}` (extension without the
+/// dot), `{n}` (counter), `{n:3}` (counter zero-padded to 3 digits) and
+/// `{date}` or `{date:%Y%m%d}` (modification date, chrono format).
+pub fn render_pattern(pattern: &str, path: &Path, counter: usize) -> Result<String, String> {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok().map(DateTime::<Local>::from);
+
+    let result = render_tokens(pattern, &stem, &extension, counter, modified)?;
+    check_name(&result, path)?;
+    Ok(result)
+}
+
+fn render_tokens(pattern: &str, stem: &str, extension: &str, counter: usize, modified: Option<DateTime<Local>>) -> Result<String, String> {
+    let mut result = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| format!("Unclosed '{{' in pattern '{}'", pattern))? + start;
+        let token = &rest[start + 1..end];
+        match token.split_once(':') {
+            None if token == "name" => result.push_str(stem),
+            None if token == "ext" => result.push_str(extension),
+            None if token == "n" => result.push_str(&counter.to_string()),
+            Some(("n", width)) => {
+                let width: usize = width.parse().map_err(|_| format!("Invalid counter width '{}'", width))?;
+                result.push_str(&format!("{:0width$}", counter, width = width));
+            }
+            None if token == "date" => result.push_str(&format_date(modified, "%Y-%m-%d")?),
+            Some(("date", format)) => result.push_str(&format_date(modified, format)?),
+            _ => return Err(format!("Unknown token '{{{}}}' in pattern", token)),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+fn format_date(date: Option<DateTime<Local>>, format: &str) -> Result<String, String> {
+    use std::fmt::Write;
+    let date = date.ok_or("The modification date is unknown")?;
+    // chrono reports a bad format as a formatting error instead of panicking here
+    let mut text = String::new();
+    write!(text, "{}", date.format(format)).map_err(|_| format!("Invalid date format '{}'", format))?;
+    Ok(text)
+}
+
+fn check_name(name: &str, path: &Path) -> Result<(), String> {
+    if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+        return Err(format!("Pattern gives an invalid name '{}' for '{}'", name, path.display()));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CaseChange {
+    #[default]
+    Keep,
+    Lower,
+    Upper,
+    /// First letter of every word upper case, the rest lower case.
+    Title,
+}
+
+impl CaseChange {
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            CaseChange::Keep => text.to_string(),
+            CaseChange::Lower => text.to_lowercase(),
+            CaseChange::Upper => text.to_uppercase(),
+            CaseChange::Title => {
+                let mut result = String::new();
+                let mut word_start = true;
+                for c in text.chars() {
+                    if word_start {
+                        result.extend(c.to_uppercase());
+                    } else {
+                        result.extend(c.to_lowercase());
+                    }
+                    word_start = !c.is_alphanumeric();
+                }
+                result
+            }
+        }
+    }
+}
+
+/// Steps of the batch renamer, applied in field order to the name without
+/// its extension. Positions count characters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenameRules {
+    pub find: String,
+    /// With `use_regex`, `$1` or `${name}` refer to groups of `find`.
+    pub replace: String,
+    pub use_regex: bool,
+    pub remove_at: usize,
+    pub remove_count: usize,
+    pub insert: String,
+    pub insert_at: usize,
+    /// Count `remove_at` and `insert_at` from the end of the name.
+    pub from_end: bool,
+    pub case: CaseChange,
+    /// A `render_pattern` template, where `{name}` is the name so far.
+    pub template: String,
+    /// `{n}` of the first file and how much it grows per file.
+    pub start: usize,
+    pub step: usize,
+    /// New extension without the dot; `None` keeps it and "" removes it.
+    pub extension: Option<String>,
+}
+
+impl Default for RenameRules {
+    fn default() -> Self {
+        Self {
+            find: String::new(),
+            replace: String::new(),
+            use_regex: false,
+            remove_at: 0,
+            remove_count: 0,
+            insert: String::new(),
+            insert_at: 0,
+            from_end: false,
+            case: CaseChange::Keep,
+            template: "{name}".to_string(),
+            start: 1,
+            step: 1,
+            extension: None,
+        }
+    }
+}
+
+/// One row of the batch rename preview.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenamePreview {
+    pub from: PathBuf,
+    /// The new path, or why no valid name came out.
+    pub to: Result<PathBuf, String>,
+    /// Set when another file has or would get the same name.
+    pub collision: Option<String>,
+}
+
+impl RenamePreview {
+    pub fn is_ok(&self) -> bool {
+        self.to.is_ok() && self.collision.is_none()
+    }
+
+    pub fn is_unchanged(&self) -> bool {
+        self.to.as_ref().is_ok_and(|to| to == &self.from)
+    }
+}
+
+impl RenameRules {
+    /// The `find` expression, when regular expressions are on.
+    pub fn regex(&self) -> Result<Option<Regex>, String> {
+        if !self.use_regex || self.find.is_empty() {
+            return Ok(None);
+        }
+        Regex::new(&self.find).map(Some).map_err(|e| e.to_string())
+    }
+
+    /// The new name of `entry`, the `index`-th file of the batch. `regex` is
+    /// what `regex()` returned.
+    pub fn new_name(&self, entry: &FileEntry, index: usize, regex: Option<&Regex>) -> Result<String, String> {
+        // Folders keep dots in their names as they are
+        let (stem, extension) = if entry.is_dir {
+            (entry.name.clone(), String::new())
+        } else {
+            let path = Path::new(&entry.name);
+            let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            (stem, entry.extension.clone())
+        };
+
+        let mut name = match regex {
+            Some(regex) => regex.replace_all(&stem, self.replace.as_str()).to_string(),
+            None if !self.find.is_empty() => stem.replace(&self.find, &self.replace),
+            None => stem,
+        };
+        if self.remove_count > 0 {
+            name = remove_chars(&name, self.position(&name, self.remove_at, self.remove_count), self.remove_count);
+        }
+        if !self.insert.is_empty() {
+            let at = self.position(&name, self.insert_at, 0);
+            name = insert_chars(&name, at, &self.insert);
+        }
+        name = self.case.apply(&name);
+
+        let counter = self.start + index * self.step;
+        let mut name = render_tokens(&self.template, &name, &extension, counter, Some(entry.modified))?;
+        let extension = match &self.extension {
+            Some(new) if !entry.is_dir => new.trim_start_matches('.').to_string(),
+            _ => extension,
+        };
+        if !extension.is_empty() {
+            name.push('.');
+            name.push_str(&extension);
+        }
+        check_name(&name, &entry.path)?;
+        Ok(name)
+    }
+
+    /// Where a step at `at` starts in `name`, for a span of `length`.
+    fn position(&self, name: &str, at: usize, length: usize) -> usize {
+        let count = name.chars().count();
+        if self.from_end { count.saturating_sub(at + length) } else { at.min(count) }
+    }
+
+    /// New names for all of `entries`, with clashes flagged. Fails only when
+    /// the `find` expression is invalid.
+    pub fn preview(&self, entries: &[FileEntry]) -> Result<Vec<RenamePreview>, String> {
+        let regex = self.regex()?;
+        let mut rows: Vec<RenamePreview> = entries.iter().enumerate().map(|(i, entry)| RenamePreview {
+            from: entry.path.clone(),
+            to: self.new_name(entry, i, regex.as_ref()).map(|name| entry.path.with_file_name(name)),
+            collision: None,
+        }).collect();
+
+        let sources: HashSet<&PathBuf> = entries.iter().map(|e| &e.path).collect();
+        let mut seen: Vec<&PathBuf> = Vec::new();
+        let mut collisions = Vec::new();
+        for row in &rows {
+            let collision = match &row.to {
+                Ok(to) if to == &row.from => None,
+                Ok(to) if seen.contains(&to) => Some("another file gets this name".to_string()),
+                Ok(to) if fs::symlink_metadata(to).is_ok() && !sources.contains(to) => Some("a file with this name exists".to_string()),
+                _ => None,
+            };
+            if let Ok(to) = &row.to {
+                seen.push(to);
+            }
+            collisions.push(collision);
+        }
+        for (row, collision) in rows.iter_mut().zip(collisions) {
+            row.collision = collision;
+        }
+        Ok(rows)
+    }
+}
+
+fn remove_chars(text: &str, at: usize, count: usize) -> String {
+    text.chars().enumerate().filter(|(i, _)| *i < at || *i >= at + count).map(|(_, c)| c).collect()
+}
+
+fn insert_chars(text: &str, at: usize, insert: &str) -> String {
+    let split = text.char_indices().nth(at).map_or(text.len(), |(i, _)| i);
+    format!("{}{}{}", &text[..split], insert, &text[split..])
+}
+
+/// Renames to perform as (old path, new path) pairs, skipping unchanged names.
+/// Fails before touching anything if two files would get the same name or a
+/// new name is taken by a file that isn't being renamed.
+pub fn plan_renames(renames: Vec<(PathBuf, PathBuf)>) -> Result<Vec<(PathBuf, PathBuf)>, FileError> {
+    let renames: Vec<(PathBuf, PathBuf)> = renames.into_iter().filter(|(from, to)| from != to).collect();
+    let sources: HashSet<&PathBuf> = renames.iter().map(|(from, _)| from).collect();
+
+    let mut targets = HashSet::new();
+    for (from, to) in &renames {
+        if !targets.insert(to) {
+            return Err(FileError::invalid(Operation::Rename, from, format!("another file would also be renamed to '{}'", to.display())));
+        }
+        if fs::symlink_metadata(to).is_ok() && !sources.contains(to) {
+            return Err(FileError::invalid(Operation::Rename, from, format!("'{}' already exists", to.display())));
+        }
+    }
+    Ok(renames)
+}
+
+/// Gives every file of `renames` a temporary name, so swaps and cycles like
+/// a→b, b→a work. On failure the files already moved get their names back.
+fn to_temporary(renames: &[(PathBuf, PathBuf)]) -> Result<Vec<PathBuf>, FileError> {
+    let temporary: Vec<PathBuf> = renames.iter()
+        .enumerate()
+        .map(|(i, (from, _))| from.with_file_name(format!(".fileexp-rename-{}-{}", std::process::id(), i)))
+        .collect();
+
+    for (i, (from, _)) in renames.iter().enumerate() {
+        if let Err(e) = fs::rename(from, &temporary[i]) {
+            for (j, (earlier, _)) in renames.iter().enumerate().take(i) {
+                let _ = fs::rename(&temporary[j], earlier);
+            }
+            return Err(FileError::io(Operation::Rename, from, &e));
+        }
+    }
+    Ok(temporary)
+}
+
+/// Applies planned renames through temporary names. If a step fails, that
+/// file is put back under its old name and the rest carry on. `on_done` is
+/// called once per rename with its outcome; the first failure is also
+/// returned.
+pub fn apply_renames(renames: &[(PathBuf, PathBuf)], mut on_done: impl FnMut(&Path, &Path, &Result<(), FileError>)) -> Result<(), FileError> {
+    let temporary = to_temporary(renames)?;
+
+    let mut first_error = None;
+    for (i, (from, to)) in renames.iter().enumerate() {
+        let result = fs::rename(&temporary[i], to).map_err(|e| {
+            let _ = fs::rename(&temporary[i], from);
+            FileError::io(Operation::Rename, from, &e)
+        });
+        on_done(from, to, &result);
+        if let Err(e) = result {
+            first_error.get_or_insert(e);
+        }
+    }
+
+    first_error.map_or(Ok(()), Err)
+}
+
+/// Applies planned renames all or nothing: if any step fails, every file
+/// gets its old name back and the failure is returned.
+pub fn apply_renames_atomically(renames: &[(PathBuf, PathBuf)]) -> Result<(), FileError> {
+    let temporary = to_temporary(renames)?;
+
+    for (i, (from, to)) in renames.iter().enumerate() {
+        if let Err(e) = fs::rename(&temporary[i], to) {
+            for (j, (earlier, renamed)) in renames.iter().enumerate() {
+                let current = if j < i { renamed } else { &temporary[j] };
+                let _ = fs::rename(current, earlier);
+            }
+            return Err(FileError::io(Operation::Rename, from, &e));
+        }
+    }
+    Ok(())
+}
+
+/// The text handed to an editor to rename `paths`: one `number<TAB>name`
+/// line per path, under a comment explaining the format. Paths whose names
+/// can't sit on one line are left out.
+pub fn edit_listing(paths: &[PathBuf]) -> String {
+    let mut text = String::new();
+    text.push_str("# Edit the names after the numbers, then save and quit to rename.\n");
+    text.push_str("# Deleting a line trashes its file if that is turned on in the settings; keep the numbers as they are.\n");
+    for (i, path) in paths.iter().enumerate() {
+        if let Some(name) = listed_name(path) {
+            text.push_str(&format!("{}\t{}\n", i + 1, name));
+        }
+    }
+    text
+}
+
+fn listed_name(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    (!name.contains(['\n', '\t'])).then_some(name)
+}
+
+/// What an edited listing asks for.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ListingChanges {
+    pub renames: Vec<(PathBuf, PathBuf)>,
+    /// Paths whose lines were deleted.
+    pub removed: Vec<PathBuf>,
+}
+
+/// Compares `edited` with the listing `edit_listing(paths)` gave. Blank and
+/// `#` lines are ignored; a malformed line fails the whole edit.
+pub fn diff_listing(paths: &[PathBuf], edited: &str) -> Result<ListingChanges, String> {
+    // Paths left out of the listing can't be removed by a missing line
+    let mut remaining: Vec<Option<&PathBuf>> = paths.iter()
+        .map(|path| listed_name(path).map(|_| path))
+        .collect();
+
+    let mut changes = ListingChanges::default();
+    let mut seen = HashSet::new();
+    for (line_number, line) in edited.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line_error = |reason: &str| format!("Line {}: {}", line_number + 1, reason);
+        let (number, name) = line.split_once('\t').ok_or_else(|| line_error("expected a number, a tab and a name"))?;
+        let index = number.trim().parse::<usize>().ok()
+            .and_then(|n| n.checked_sub(1))
+            .filter(|&i| i < paths.len())
+            .ok_or_else(|| line_error(&format!("unknown number '{}'", number.trim())))?;
+        if !seen.insert(index) {
+            return Err(line_error(&format!("number {} is used twice", index + 1)));
+        }
+        if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+            return Err(line_error(&format!("'{}' is not a valid name", name)));
+        }
+        remaining[index] = None;
+        let from = &paths[index];
+        let to = from.with_file_name(name);
+        if &to != from {
+            changes.renames.push((from.clone(), to));
+        }
+    }
+    changes.removed = remaining.into_iter().flatten().cloned().collect();
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A temporary folder with files `a` and `b` holding their own names.
+    fn setup() -> (TempDir, PathBuf, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+        fs::write(&a, "a").unwrap();
+        fs::write(&b, "b").unwrap();
+        (dir, a, b)
+    }
+
+    fn contents(path: &Path) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    /// Names left in `dir`, so stray temporary files show up too.
+    fn names(dir: &TempDir) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir.path()).unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn swapping_two_names() {
+        let (dir, a, b) = setup();
+        let planned = plan_renames(vec![(a.clone(), b.clone()), (b.clone(), a.clone())]).unwrap();
+        apply_renames_atomically(&planned).unwrap();
+        assert_eq!(contents(&a), "b");
+        assert_eq!(contents(&b), "a");
+        assert_eq!(names(&dir), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn unchanged_names_are_skipped() {
+        let (_dir, a, b) = setup();
+        let planned = plan_renames(vec![(a.clone(), a.clone()), (b.clone(), b.with_file_name("c"))]).unwrap();
+        assert_eq!(planned, vec![(b.clone(), b.with_file_name("c"))]);
+    }
+
+    #[test]
+    fn duplicate_targets_are_refused() {
+        let (dir, a, b) = setup();
+        let c = dir.path().join("c");
+        let error = plan_renames(vec![(a, c.clone()), (b.clone(), c)]).unwrap_err();
+        assert_eq!(error.path(), b.as_path());
+        assert!(error.reason().contains("another file"));
+    }
+
+    #[test]
+    fn existing_targets_are_refused() {
+        let (dir, a, b) = setup();
+        fs::write(dir.path().join("c"), "c").unwrap();
+        // `b` is taken, but only by a file that isn't renamed
+        let error = plan_renames(vec![(a.clone(), b.clone())]).unwrap_err();
+        assert_eq!(error.path(), a.as_path());
+        assert!(error.reason().contains("already exists"));
+        assert!(plan_renames(vec![(a.clone(), b.clone()), (b, dir.path().join("d"))]).is_ok());
+        assert!(plan_renames(vec![(a, dir.path().join("c"))]).is_err());
+    }
+
+    #[test]
+    fn a_failure_partway_rolls_everything_back() {
+        let (dir, a, b) = setup();
+        let c = dir.path().join("c");
+        // The first rename succeeds; the second goes into a folder that doesn't exist
+        let renames = vec![(a.clone(), c.clone()), (b.clone(), dir.path().join("missing/b"))];
+        let planned = plan_renames(renames).unwrap();
+
+        let error = apply_renames_atomically(&planned).unwrap_err();
+        assert_eq!(error.path(), b.as_path());
+        assert_eq!(contents(&a), "a");
+        assert_eq!(contents(&b), "b");
+        assert_eq!(names(&dir), vec!["a", "b"]);
+    }
+
+    fn listed(paths: &[PathBuf]) -> Vec<String> {
+        edit_listing(paths).lines().filter(|l| !l.starts_with('#')).map(str::to_string).collect()
+    }
+
+    #[test]
+    fn listing_numbers_names_and_leaves_out_unlistable_ones() {
+        let paths = vec![PathBuf::from("/d/a.txt"), PathBuf::from("/d/two\nlines"), PathBuf::from("/d/b c")];
+        assert_eq!(listed(&paths), vec!["1\ta.txt", "3\tb c"]);
+    }
+
+    #[test]
+    fn edited_listing_gives_renames_and_removals() {
+        let paths = vec![PathBuf::from("/d/a"), PathBuf::from("/d/b"), PathBuf::from("/d/c")];
+        let edited = "# comment\n1\tb\n\n2\ta\n";
+        let changes = diff_listing(&paths, edited).unwrap();
+        assert_eq!(changes.renames, vec![
+            (PathBuf::from("/d/a"), PathBuf::from("/d/b")),
+            (PathBuf::from("/d/b"), PathBuf::from("/d/a")),
+        ]);
+        assert_eq!(changes.removed, vec![PathBuf::from("/d/c")]);
+    }
+
+    #[test]
+    fn unlisted_paths_are_not_removed() {
+        let paths = vec![PathBuf::from("/d/a"), PathBuf::from("/d/two\nlines")];
+        let changes = diff_listing(&paths, &edit_listing(&paths)).unwrap();
+        assert_eq!(changes, ListingChanges::default());
+    }
+
+    #[test]
+    fn malformed_listings_are_refused() {
+        let paths = vec![PathBuf::from("/d/a"), PathBuf::from("/d/b")];
+        for (edited, error) in [
+            ("1 a", "Line 1: expected a number, a tab and a name"),
+            ("3\ta", "Line 1: unknown number '3'"),
+            ("0\ta", "Line 1: unknown number '0'"),
+            ("1\tx\n1\ty", "Line 2: number 1 is used twice"),
+            ("1\tx/y", "Line 1: 'x/y' is not a valid name"),
+            ("1\t..", "Line 1: '..' is not a valid name"),
+        ] {
+            assert_eq!(diff_listing(&paths, edited).unwrap_err(), error, "{:?}", edited);
+        }
+    }
+
+    #[test]
+    fn edited_listing_swaps_on_disk() {
+        let (_dir, a, b) = setup();
+        let paths = vec![a.clone(), b.clone()];
+        let changes = diff_listing(&paths, "1\tb\n2\ta\n").unwrap();
+        apply_renames_atomically(&plan_renames(changes.renames).unwrap()).unwrap();
+        assert_eq!(contents(&a), "b");
+        assert_eq!(contents(&b), "a");
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use crate::error::Operation;
+use crate::explorer::Change;
+
+/// A change that succeeded, in a form that can be replayed elsewhere.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    CreateFile(PathBuf),
+    CreateFolder(PathBuf),
+    Rename { from: PathBuf, to: PathBuf },
+    /// Renames made together, which may swap names or go round in a cycle.
+    RenameBatch(Vec<(PathBuf, PathBuf)>),
+    Copy { from: PathBuf, to: PathBuf },
+    Move { from: PathBuf, to: PathBuf },
+    Delete(PathBuf),
+}
+
+impl Action {
+    /// The action behind a successful change. Call it right after the
+    /// change, as telling a new file from a new folder looks at the disk.
+    pub fn from_change(change: &Change) -> Option<Self> {
+        if change.error.is_some() {
+            return None;
+        }
+        let from = change.source.clone();
+        let to = change.destination.clone();
+        match change.operation {
+            Operation::Create if from.is_dir() => Some(Action::CreateFolder(from)),
+            Operation::Create => Some(Action::CreateFile(from)),
+            Operation::Rename => Some(Action::Rename { from, to: to? }),
+            Operation::Copy => Some(Action::Copy { from, to: to? }),
+            Operation::Move => Some(Action::Move { from, to: to? }),
+            Operation::Delete => Some(Action::Delete(from)),
+            _ => None,
+        }
+    }
+
+    /// The actions behind the changes of one command. Its renames were made
+    /// together, so several of them become one `RenameBatch`.
+    pub fn from_changes(changes: &[Change]) -> Vec<Self> {
+        let mut actions: Vec<Action> = changes.iter().filter_map(Action::from_change).collect();
+        let renames: Vec<(PathBuf, PathBuf)> = actions.iter()
+            .filter_map(|action| match action {
+                Action::Rename { from, to } => Some((from.clone(), to.clone())),
+                _ => None,
+            })
+            .collect();
+        if renames.len() > 1 {
+            let first = actions.iter().position(|a| matches!(a, Action::Rename { .. })).unwrap_or_default();
+            actions.retain(|a| !matches!(a, Action::Rename { .. }));
+            actions.insert(first, Action::RenameBatch(renames));
+        }
+        actions
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Action::CreateFile(path) => format!("Create file {}", path.display()),
+            Action::CreateFolder(path) => format!("Create folder {}", path.display()),
+            Action::Rename { from, to } => format!("Rename {} to {}", from.display(), name(to)),
+            Action::RenameBatch(renames) => {
+                let renames: Vec<String> = renames.iter().map(|(from, to)| format!("{} to {}", from.display(), name(to))).collect();
+                format!("Rename {}", renames.join(", "))
+            }
+            Action::Copy { from, to } => format!("Copy {} to {}", from.display(), to.display()),
+            Action::Move { from, to } => format!("Move {} to {}", from.display(), to.display()),
+            Action::Delete(path) => format!("Delete {}", path.display()),
+        }
+    }
+
+    /// Paths that must exist before the action runs.
+    fn sources(&self) -> Vec<&Path> {
+        match self {
+            Action::CreateFile(_) | Action::CreateFolder(_) => Vec::new(),
+            Action::Rename { from, .. } | Action::Copy { from, .. } | Action::Move { from, .. } => vec![from],
+            Action::RenameBatch(renames) => renames.iter().map(|(from, _)| from.as_path()).collect(),
+            Action::Delete(path) => vec![path],
+        }
+    }
+
+    /// Paths the action brings into existence.
+    fn products(&self) -> Vec<&Path> {
+        match self {
+            Action::CreateFile(path) | Action::CreateFolder(path) => vec![path],
+            Action::Rename { to, .. } | Action::Copy { to, .. } | Action::Move { to, .. } => vec![to],
+            Action::RenameBatch(renames) => renames.iter().map(|(_, to)| to.as_path()).collect(),
+            Action::Delete(_) => Vec::new(),
+        }
+    }
+
+    fn paths(&self) -> Vec<&Path> {
+        let mut paths = self.sources();
+        paths.extend(self.products());
+        paths
+    }
+
+    /// Script lines for the action. Everything it creates goes through
+    /// `create`, so an existing item stops the script instead of being
+    /// replaced or getting the new one nested inside it.
+    fn to_shell(&self, base: &Path) -> String {
+        let path = |p: &Path| shell_quote(&relative(p, base));
+        let put = |command: &str, from: &Path, to: &Path| format!("create {} {} -- {} {}", path(to), command, path(from), path(to));
+        match self {
+            Action::CreateFile(p) => format!("create {} touch -- {}", path(p), path(p)),
+            Action::CreateFolder(p) => format!("run mkdir -- {}", path(p)),
+            Action::Rename { from, to } | Action::Move { from, to } => put("mv", from, to),
+            // Through temporary names first, as a→b, b→a would otherwise
+            // move one file onto the other
+            Action::RenameBatch(renames) => {
+                let temporary = |from: &Path| from.with_file_name(format!(".{}.fileexp-replay", name(from)));
+                let away = renames.iter().map(|(from, _)| put("mv", from, &temporary(from)));
+                let back = renames.iter().map(|(from, to)| put("mv", &temporary(from), to));
+                away.chain(back).collect::<Vec<_>>().join("\n")
+            }
+            Action::Copy { from, to } => put("cp -a", from, to),
+            Action::Delete(p) => format!("run rm -r -- {}", path(p)),
+        }
+    }
+}
+
+fn name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// Quotes `text` for a POSIX shell.
+pub fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// `path` below `base`, starting with "./" so it can never look like an option.
+/// A path outside `base` stays absolute, which can't look like one either;
+/// `shell_script` never has such paths since its base contains them all.
+fn relative(path: &Path, base: &Path) -> String {
+    match path.strip_prefix(base) {
+        Ok(rest) if rest.as_os_str().is_empty() => ".".to_string(),
+        Ok(rest) => format!("./{}", rest.display()),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+/// The deepest folder containing every path the actions touch.
+fn common_base(actions: &[Action]) -> PathBuf {
+    let mut paths = actions.iter().flat_map(Action::paths);
+    let Some(first) = paths.next() else {
+        return PathBuf::from("/");
+    };
+    let mut base = first.parent().unwrap_or(first).to_path_buf();
+    for path in paths {
+        while !path.starts_with(&base) {
+            if !base.pop() {
+                return PathBuf::from("/");
+            }
+        }
+    }
+    base
+}
+
+/// A POSIX shell script that repeats `actions` in order. Paths are relative
+/// to the folder they have in common, which the script changes into first;
+/// `FILEEXP_BASE` points it somewhere else. Before touching anything it
+/// checks that every source not made by an earlier step exists, and with
+/// `-n` it stops after that check and only prints the commands. A step that
+/// would land on an existing item stops the script there.
+pub fn shell_script(actions: &[Action]) -> String {
+    let base = common_base(actions);
+
+    let mut produced: HashSet<&Path> = HashSet::new();
+    let mut required = Vec::new();
+    for action in actions {
+        for source in action.sources() {
+            if !produced.iter().any(|p| source.starts_with(p)) && !required.contains(&source) {
+                required.push(source);
+            }
+        }
+        produced.extend(action.products());
+    }
+
+    let mut script = String::new();
+    script.push_str("#!/bin/sh\n");
+    script.push_str(&format!("# Replays {} file operations recorded by fileexp.\n", actions.len()));
+    script.push_str("# Usage: sh script.sh [-n]\n");
+    script.push_str("#   -n  check the sources and print the commands without running them\n");
+    script.push_str("# Set FILEEXP_BASE to replay in another folder.\n");
+    script.push_str("set -eu\n\n");
+    script.push_str(&format!("base={}\n", shell_quote(&base.display().to_string())));
+    script.push_str("if [ -n \"${FILEEXP_BASE:-}\" ]; then base=$FILEEXP_BASE; fi\n");
+    script.push_str("cd -- \"$base\"\n\n");
+    script.push_str("dry_run=false\n");
+    script.push_str("if [ \"${1:-}\" = \"-n\" ]; then dry_run=true; fi\n\n");
+
+    if !required.is_empty() {
+        script.push_str("missing=0\n");
+        script.push_str("for source in");
+        for source in &required {
+            script.push_str(" \\\n    ");
+            script.push_str(&shell_quote(&relative(source, &base)));
+        }
+        script.push_str("\ndo\n");
+        script.push_str("    if [ ! -e \"$source\" ] && [ ! -L \"$source\" ]; then\n");
+        script.push_str("        echo \"missing: $source\" >&2\n");
+        script.push_str("        missing=1\n");
+        script.push_str("    fi\n");
+        script.push_str("done\n");
+        script.push_str("if [ \"$missing\" -ne 0 ]; then\n");
+        script.push_str("    echo \"Nothing was changed because sources are missing.\" >&2\n");
+        script.push_str("    exit 1\n");
+        script.push_str("fi\n\n");
+    }
+
+    script.push_str("run() {\n");
+    script.push_str("    if $dry_run; then\n");
+    script.push_str("        echo \"$*\"\n");
+    script.push_str("    else\n");
+    script.push_str("        \"$@\"\n");
+    script.push_str("    fi\n");
+    script.push_str("}\n\n");
+
+    script.push_str("# Runs a command that makes $1, unless something is already there.\n");
+    script.push_str("create() {\n");
+    script.push_str("    if ! $dry_run && { [ -e \"$1\" ] || [ -L \"$1\" ]; }; then\n");
+    script.push_str("        echo \"already exists: $1\" >&2\n");
+    script.push_str("        exit 1\n");
+    script.push_str("    fi\n");
+    script.push_str("    shift\n");
+    script.push_str("    run \"$@\"\n");
+    script.push_str("}\n\n");
+
+    for action in actions {
+        script.push_str(&format!("# {}\n", action.describe().replace('\n', " ")));
+        script.push_str(&action.to_shell(&base));
+        script.push('\n');
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::{Command, Output};
+
+    const AWKWARD: &str = "-dash 'quoted'";
+
+    fn sh(args: &[&str], base: &Path) -> Output {
+        Command::new("sh").args(args).env("FILEEXP_BASE", base).output().unwrap()
+    }
+
+    #[test]
+    fn quoting_survives_the_shell() {
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        for text in ["it's", "two words", "-n", "line\nbreak", "''", "$HOME `x`"] {
+            let output = Command::new("sh").args(["-c", &format!("printf %s {}", shell_quote(text))]).output().unwrap();
+            assert_eq!(String::from_utf8(output.stdout).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn paths_are_relative_to_the_base() {
+        let base = Path::new("/base");
+        assert_eq!(relative(Path::new("/base/-a b"), base), "./-a b");
+        assert_eq!(relative(base, base), ".");
+        assert_eq!(relative(Path::new("/elsewhere/x"), base), "/elsewhere/x");
+    }
+
+    /// Recorded under `recorded`: a new folder that a copy of an awkwardly
+    /// named file goes into and is then renamed in, a move and a delete.
+    fn actions(recorded: &Path) -> Vec<Action> {
+        let folder = recorded.join("new dir");
+        vec![
+            Action::CreateFolder(folder.clone()),
+            Action::Copy { from: recorded.join(AWKWARD), to: folder.join(AWKWARD) },
+            Action::Rename { from: folder.join(AWKWARD), to: folder.join("line\nbreak") },
+            Action::Move { from: recorded.join("plain.txt"), to: folder.join("plain.txt") },
+            Action::Delete(recorded.join("old")),
+        ]
+    }
+
+    fn write_script(dir: &Path) -> PathBuf {
+        let script = dir.join("replay.sh");
+        fs::write(&script, shell_script(&actions(Path::new("/recorded")))).unwrap();
+        script
+    }
+
+    #[test]
+    fn only_sources_not_made_earlier_are_required() {
+        let script = shell_script(&actions(Path::new("/recorded")));
+        let check = &script[script.find("for source in").unwrap()..script.find("do\n").unwrap()];
+        assert!(check.contains(&shell_quote(&format!("./{}", AWKWARD))));
+        assert!(check.contains("'./plain.txt'"));
+        assert!(check.contains("'./old'"));
+        assert!(!check.contains("new dir"));
+    }
+
+    #[test]
+    fn script_replays_in_another_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = write_script(dir.path());
+        let base = dir.path().join("replay");
+        fs::create_dir_all(base.join("old")).unwrap();
+        fs::write(base.join(AWKWARD), "awkward").unwrap();
+        fs::write(base.join("plain.txt"), "plain").unwrap();
+
+        let output = sh(&[script.to_str().unwrap()], &base);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(fs::read_to_string(base.join("new dir/line\nbreak")).unwrap(), "awkward");
+        assert_eq!(fs::read_to_string(base.join("new dir/plain.txt")).unwrap(), "plain");
+        assert!(base.join(AWKWARD).exists());
+        assert!(!base.join("plain.txt").exists());
+        assert!(!base.join("old").exists());
+    }
+
+    #[test]
+    fn missing_sources_stop_the_script_before_any_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = write_script(dir.path());
+        let base = dir.path().join("replay");
+        fs::create_dir(&base).unwrap();
+        fs::write(base.join("plain.txt"), "plain").unwrap();
+
+        let output = sh(&[script.to_str().unwrap()], &base);
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains(&format!("missing: ./{}", AWKWARD)));
+        assert!(!base.join("new dir").exists());
+        assert!(base.join("plain.txt").exists());
+    }
+
+    #[test]
+    fn renames_of_one_command_are_replayed_together() {
+        let recorded = Path::new("/recorded");
+        let change = |from: &str, to: &str| Change {
+            operation: Operation::Rename,
+            source: recorded.join(from),
+            destination: Some(recorded.join(to)),
+            error: None,
+        };
+        let renames = vec![(recorded.join("a"), recorded.join("b")), (recorded.join("b"), recorded.join("a"))];
+        assert_eq!(Action::from_changes(&[change("a", "b"), change("b", "a")]), vec![Action::RenameBatch(renames)]);
+        assert_eq!(Action::from_changes(&[change("a", "b")]), vec![Action::Rename { from: recorded.join("a"), to: recorded.join("b") }]);
+    }
+
+    #[test]
+    fn swapped_names_survive_the_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorded = Path::new("/recorded");
+        let swap = Action::RenameBatch(vec![(recorded.join("a"), recorded.join("b")), (recorded.join("b"), recorded.join("a"))]);
+        let script = dir.path().join("replay.sh");
+        fs::write(&script, shell_script(&[swap])).unwrap();
+        let base = dir.path().join("replay");
+        fs::create_dir(&base).unwrap();
+        fs::write(base.join("a"), "was a").unwrap();
+        fs::write(base.join("b"), "was b").unwrap();
+
+        let output = sh(&[script.to_str().unwrap()], &base);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(fs::read_to_string(base.join("a")).unwrap(), "was b");
+        assert_eq!(fs::read_to_string(base.join("b")).unwrap(), "was a");
+        assert_eq!(fs::read_dir(&base).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn existing_targets_stop_the_script() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorded = Path::new("/recorded");
+        let copy = Action::Copy { from: recorded.join("dir"), to: recorded.join("copy") };
+        let script = dir.path().join("replay.sh");
+        fs::write(&script, shell_script(&[copy])).unwrap();
+        let base = dir.path().join("replay");
+        fs::create_dir_all(base.join("dir")).unwrap();
+        fs::create_dir(base.join("copy")).unwrap();
+
+        let output = sh(&[script.to_str().unwrap()], &base);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(String::from_utf8(output.stderr).unwrap().contains("already exists: ./copy"));
+        assert!(!base.join("copy/dir").exists());
+    }
+
+    #[test]
+    fn dry_run_only_prints() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = write_script(dir.path());
+        let base = dir.path().join("replay");
+        fs::create_dir_all(base.join("old")).unwrap();
+        fs::write(base.join(AWKWARD), "awkward").unwrap();
+        fs::write(base.join("plain.txt"), "plain").unwrap();
+
+        let output = sh(&[script.to_str().unwrap(), "-n"], &base);
+        assert!(output.status.success());
+        assert!(String::from_utf8(output.stdout).unwrap().contains("mkdir -- ./new dir"));
+        assert!(!base.join("new dir").exists());
+    }
+}
+
@@ -0,0 +1,105 @@
+use std::path::PathBuf;
+use crate::models::FileEntry;
+
+/// Selected items, as indices into the current listing.
+#[derive(Clone, Debug, Default)]
+pub struct Selection {
+    pub indices: Vec<usize>,
+}
+
+impl Selection {
+    pub fn clear(&mut self) {
+        self.indices.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.indices.contains(&index)
+    }
+
+    pub fn select_only(&mut self, index: usize) {
+        self.indices = vec![index];
+    }
+
+    pub fn toggle(&mut self, index: usize) {
+        match self.indices.iter().position(|&i| i == index) {
+            Some(pos) => {
+                self.indices.remove(pos);
+            }
+            None => self.indices.push(index),
+        }
+    }
+
+    /// Selects every entry of `entries` whose path is in `paths`.
+    pub fn select_paths(&mut self, entries: &[FileEntry], paths: &[PathBuf]) {
+        self.indices = entries.iter()
+            .enumerate()
+            .filter(|(_, e)| paths.contains(&e.path))
+            .map(|(i, _)| i)
+            .collect();
+    }
+
+    pub fn paths(&self, entries: &[FileEntry]) -> Vec<PathBuf> {
+        self.indices.iter()
+            .filter_map(|&i| entries.get(i))
+            .map(|e| e.path.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+
+    fn entry(path: &str) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from(path),
+            is_dir: false,
+            size: 0,
+            modified: Local::now(),
+            name: path.trim_start_matches('/').to_string(),
+            extension: String::new(),
+        }
+    }
+
+    #[test]
+    fn toggle_adds_and_removes() {
+        let mut selection = Selection::default();
+        selection.toggle(2);
+        selection.toggle(0);
+        assert_eq!(selection.indices, vec![2, 0]);
+        selection.toggle(2);
+        assert_eq!(selection.indices, vec![0]);
+    }
+
+    #[test]
+    fn select_only_replaces_the_selection() {
+        let mut selection = Selection { indices: vec![1, 3] };
+        selection.select_only(2);
+        assert_eq!(selection.indices, vec![2]);
+        assert!(selection.contains(2) && !selection.contains(1));
+    }
+
+    #[test]
+    fn paths_round_trip() {
+        let entries = vec![entry("/a"), entry("/b"), entry("/c")];
+        let mut selection = Selection::default();
+        selection.select_paths(&entries, &[PathBuf::from("/c"), PathBuf::from("/a"), PathBuf::from("/missing")]);
+        assert_eq!(selection.indices, vec![0, 2]);
+        assert_eq!(selection.paths(&entries), vec![PathBuf::from("/a"), PathBuf::from("/c")]);
+    }
+
+    #[test]
+    fn paths_skip_indices_past_the_listing() {
+        let selection = Selection { indices: vec![0, 7] };
+        assert_eq!(selection.paths(&[entry("/a")]), vec![PathBuf::from("/a")]);
+    }
+}
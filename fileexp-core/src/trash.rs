@@ -0,0 +1,141 @@
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use chrono::Local;
+use crate::error::{FileError, Operation};
+use crate::models::ConflictPolicy;
+use crate::operations;
+use crate::utils;
+
+/// An item in the trash, following the freedesktop.org Trash specification.
+#[derive(Serialize, Clone, Debug)]
+pub struct TrashEntry {
+    /// Name under `Trash/files`, unique within the trash.
+    pub name: String,
+    pub original_path: PathBuf,
+    pub deletion_date: String,
+}
+
+/// The user's home trash, `$XDG_DATA_HOME/Trash`.
+pub fn trash_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from(".local/share"))
+        .join("Trash")
+}
+
+fn info_path(name: &str) -> PathBuf {
+    trash_dir().join("info").join(format!("{}.trashinfo", name))
+}
+
+/// Absolute path of `path` without resolving a symlink at the end.
+fn absolute(path: &Path) -> Result<PathBuf, FileError> {
+    let name = path.file_name().ok_or_else(|| FileError::invalid(Operation::Trash, path, "not a file or folder name"))?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let parent = parent.canonicalize().map_err(|e| FileError::io(Operation::Trash, path, &e))?;
+    Ok(parent.join(name))
+}
+
+/// Moves `path` to the trash and returns the name it got there.
+pub fn trash_item(path: &Path) -> Result<String, FileError> {
+    if let Err(e) = fs::symlink_metadata(path) {
+        return Err(FileError::io(Operation::Trash, path, &e));
+    }
+    let path = absolute(path)?;
+    let files_dir = trash_dir().join("files");
+    fs::create_dir_all(&files_dir).map_err(|e| FileError::io(Operation::Create, &files_dir, &e))?;
+    let info_dir = trash_dir().join("info");
+    fs::create_dir_all(&info_dir).map_err(|e| FileError::io(Operation::Create, &info_dir, &e))?;
+
+    // Creating the info file with create_new reserves the name
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let mut n = 1;
+    let (name, mut info) = loop {
+        let name = if n == 1 { file_name.clone() } else { format!("{}.{}", file_name, n) };
+        match OpenOptions::new().write(true).create_new(true).open(info_path(&name)) {
+            Ok(file) if fs::symlink_metadata(files_dir.join(&name)).is_err() => break (name, file),
+            Ok(_) => {
+                let _ = fs::remove_file(info_path(&name));
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(FileError::io(Operation::Write, &info_path(&name), &e)),
+        }
+        n += 1;
+    };
+
+    let contents = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        utils::percent_encode(&path.to_string_lossy()),
+        Local::now().format("%Y-%m-%dT%H:%M:%S"),
+    );
+    let result = info.write_all(contents.as_bytes())
+        .map_err(|e| FileError::io(Operation::Write, &info_path(&name), &e))
+        .and_then(|_| operations::move_item(&path, &files_dir.join(&name), ConflictPolicy::Fail));
+    if let Err(e) = result {
+        let _ = fs::remove_file(info_path(&name));
+        return Err(e);
+    }
+    Ok(name)
+}
+
+fn read_entry(name: &str) -> Option<TrashEntry> {
+    let contents = fs::read_to_string(info_path(name)).ok()?;
+    let mut original_path = None;
+    let mut deletion_date = String::new();
+    for line in contents.lines() {
+        if let Some(path) = line.strip_prefix("Path=") {
+            original_path = Some(PathBuf::from(utils::percent_decode(path)));
+        } else if let Some(date) = line.strip_prefix("DeletionDate=") {
+            deletion_date = date.to_string();
+        }
+    }
+    Some(TrashEntry {
+        name: name.to_string(),
+        original_path: original_path?,
+        deletion_date,
+    })
+}
+
+/// Everything in the trash, most recently deleted first.
+pub fn list_trash() -> Vec<TrashEntry> {
+    let Ok(infos) = fs::read_dir(trash_dir().join("info")) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<TrashEntry> = infos
+        .filter_map(Result::ok)
+        .filter_map(|info| {
+            let file_name = info.file_name().to_string_lossy().to_string();
+            read_entry(file_name.strip_suffix(".trashinfo")?)
+        })
+        .collect();
+    entries.sort_by(|a, b| b.deletion_date.cmp(&a.deletion_date));
+    entries
+}
+
+/// Finds a trashed item by its trash name, or by the path it was deleted
+/// from (the most recent one wins).
+pub fn find_entry(name_or_path: &str) -> Option<TrashEntry> {
+    if let Some(entry) = read_entry(name_or_path) {
+        return Some(entry);
+    }
+    let path = Path::new(name_or_path);
+    let path = absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    list_trash().into_iter().find(|e| e.original_path == path)
+}
+
+/// Puts a trashed item back where it came from. Returns where it ended up,
+/// or `None` if the conflict policy skipped it.
+pub fn restore_item(entry: &TrashEntry, policy: ConflictPolicy) -> Result<Option<PathBuf>, FileError> {
+    let Some(destination) = operations::resolve_conflict(&entry.original_path, policy, Operation::Restore)? else {
+        return Ok(None);
+    };
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent).map_err(|e| FileError::io(Operation::Create, parent, &e))?;
+    }
+    operations::move_item(&trash_dir().join("files").join(&entry.name), &destination, ConflictPolicy::Fail)?;
+    let _ = fs::remove_file(info_path(&entry.name));
+    Ok(Some(destination))
+}
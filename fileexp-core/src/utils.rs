@@ -0,0 +1,208 @@
+use crate::models::{FileEntry, SortBy};
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub fn format_file_size(size: u64) -> String {
+    if size < 1024 {
+        format!("{} B", size)
+    } else if size < 1024 * 1024 {
+        format!("{:.1} KB", size as f64 / 1024.0)
+    } else if size < 1024 * 1024 * 1024 {
+        format!("{:.1} MB", size as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.1} GB", size as f64 / (1024.0 * 1024.0 * 1024.0))
+    }
+}
+
+pub fn generate_breadcrumbs(path: &Path) -> Vec<(String, PathBuf)> {
+    let mut breadcrumbs = Vec::new();
+    let mut current = path.to_path_buf();
+    
+    while let Some(parent) = current.parent() {
+        if let Some(name) = current.file_name() {
+            breadcrumbs.insert(0, (name.to_string_lossy().to_string(), current.clone()));
+        }
+        current = parent.to_path_buf();
+    }
+    
+    if current.to_string_lossy() == "/" {
+        breadcrumbs.insert(0, ("/".to_string(), current));
+    }
+    
+    breadcrumbs
+}
+
+pub fn filter_matches(entry: &FileEntry, filter: &str) -> bool {
+    filter.is_empty() || entry.name.to_lowercase().contains(&filter.to_lowercase())
+}
+
+pub fn sort_entries(entries: &mut [FileEntry], sort_by: SortBy, ascending: bool) {
+    entries.sort_by(|a, b| {
+        // Folders always stay on top regardless of direction
+        if a.is_dir != b.is_dir {
+            return if a.is_dir { Ordering::Less } else { Ordering::Greater };
+        }
+        
+        let ordering = match sort_by {
+            SortBy::Name => a.name.cmp(&b.name),
+            SortBy::Size => a.size.cmp(&b.size),
+            SortBy::Modified => a.modified.cmp(&b.modified),
+            SortBy::Type => a.extension.to_lowercase().cmp(&b.extension.to_lowercase()),
+        }.then_with(|| a.name.cmp(&b.name));
+        
+        if ascending { ordering } else { ordering.reverse() }
+    });
+}
+
+/// Completes the last component of `partial`, resolved against `base_dir`.
+/// Directories are returned with a trailing slash.
+pub fn complete_path(base_dir: &Path, partial: &str, dirs_only: bool) -> Vec<String> {
+    let (path_part, file_part) = match partial.rfind('/') {
+        Some(slash_pos) => (&partial[..slash_pos + 1], &partial[slash_pos + 1..]),
+        None => ("", partial),
+    };
+
+    let search_dir = if path_part.is_empty() {
+        base_dir.to_path_buf()
+    } else if path_part.starts_with('/') {
+        PathBuf::from(path_part)
+    } else {
+        base_dir.join(path_part)
+    };
+
+    let mut suggestions = Vec::new();
+    if let Ok(entries) = fs::read_dir(&search_dir) {
+        for entry in entries.flatten() {
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else { continue };
+            if !name.starts_with(file_part) {
+                continue;
+            }
+            
+            let is_dir = entry.file_type().is_ok_and(|ft| ft.is_dir());
+            if is_dir {
+                suggestions.push(format!("{}{}/", path_part, name));
+            } else if !dirs_only {
+                suggestions.push(format!("{}{}", path_part, name));
+            }
+        }
+    }
+    suggestions.sort();
+    suggestions
+}
+
+/// Turns what the user typed into the path bar into a path: accepts
+/// `file://` URIs and expands `~`, `$VAR` and `${VAR}`. Relative paths are
+/// resolved against `base_dir`.
+pub fn expand_path(input: &str, base_dir: &Path) -> PathBuf {
+    let input = input.trim();
+    let input = match path_from_uri(input) {
+        Some(path) => path.to_string_lossy().to_string(),
+        None => input.to_string(),
+    };
+    
+    let expanded = expand_env_vars(&input);
+    let expanded = if expanded == "~" {
+        dirs::home_dir().unwrap_or_else(|| PathBuf::from("/")).to_string_lossy().to_string()
+    } else if let Some(rest) = expanded.strip_prefix("~/") {
+        dirs::home_dir().unwrap_or_else(|| PathBuf::from("/")).join(rest).to_string_lossy().to_string()
+    } else {
+        expanded
+    };
+    
+    let path = PathBuf::from(expanded);
+    if path.is_absolute() { path } else { base_dir.join(path) }
+}
+
+/// Path named by a `file://` URI, or `None` for anything else.
+pub fn path_from_uri(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    // Skip an optional host part, e.g. file://localhost/tmp
+    Some(PathBuf::from(percent_decode(&rest[rest.find('/').unwrap_or(rest.len())..])))
+}
+
+fn expand_env_vars(input: &str) -> String {
+    let mut result = String::new();
+    let mut chars = input.chars().peekable();
+    
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            result.push(c);
+            continue;
+        }
+        
+        let braced = chars.peek() == Some(&'{');
+        if braced {
+            chars.next();
+        }
+        let mut name = String::new();
+        while let Some(&next) = chars.peek() {
+            if next.is_alphanumeric() || next == '_' {
+                name.push(next);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        if braced && chars.peek() == Some(&'}') {
+            chars.next();
+        }
+        
+        match std::env::var(&name) {
+            Ok(value) if !name.is_empty() => result.push_str(&value),
+            // Unknown variables are left untouched
+            _ if braced => result.push_str(&format!("${{{}}}", name)),
+            _ => result.push_str(&format!("${}", name)),
+        }
+    }
+    result
+}
+
+pub fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        if bytes[i] == b'%'
+            && let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Escapes everything but unreserved characters and `/`, as in `file://` URIs.
+pub fn percent_encode(input: &str) -> String {
+    let mut encoded = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Longest prefix shared by all of `strings`.
+pub fn common_prefix(strings: &[String]) -> String {
+    let Some(first) = strings.first() else {
+        return String::new();
+    };
+    let mut prefix_len = first.len();
+    for s in &strings[1..] {
+        prefix_len = first.char_indices()
+            .zip(s.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8())
+            .min(prefix_len);
+    }
+    first[..prefix_len].to_string()
+}
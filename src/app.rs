@@ -1,8 +1,9 @@
 use eframe::egui::{self, Context, Response};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use arboard::Clipboard;
 
-use crate::models::{Bookmark, FileEntry, FileOperation, Theme};
+use crate::models::{FileEntry, PaneSide, Theme, ViewMode};
 use crate::operations;
 use crate::ui;
 use crate::utils;
@@ -10,28 +11,76 @@ use crate::terminal::TerminalState;
 use crate::terminal_ui;
 use crate::context_menu::{ContextMenuState, ContextMenuAction, NewItemType};
 use crate::settings::{AppSettings, SettingsWindow};
+use crate::frecency::FrecencyDb;
+use crate::tabs::Tab;
+use crate::sidebar::{self, SidebarState};
+use crate::columns::ColumnsState;
+use crate::session::Session;
+use crate::cli::Args;
+use crate::ipc::{IpcCommand, IpcServer};
+use crate::dbus::DbusService;
+use crate::picker::{self, PickerState};
+use crate::notifications::{self, NotificationCenter};
+use crate::audit_ui::AuditViewer;
+use crate::journal_ui::{self, SessionJournal};
+use crate::plan_ui::{self, PlanPreview};
+use crate::archive_ui::{self, CompressDialog, ExtractDialog};
+use crate::jobs::{self, JobList};
+use crate::editor_rename::EditorRename;
+use crate::rename_ui::{self, BatchRenamer};
+use crate::protection::{self, PendingConfirmation, Protection};
+use fileexp_core::archive::{self, ArchiveKind};
+use fileexp_core::replay::Action;
+use fileexp_core::audit::{AuditLog, AuditRecord};
+use fileexp_core::bookmarks::BookmarkStore;
+use fileexp_core::models::{ConflictPolicy, FileOperation};
+use fileexp_core::plan::Plan;
+use fileexp_core::{Change, Command, Event, Explorer};
 
 pub struct FileExplorerApp {
-    pub current_path: PathBuf,
-    pub entries: Vec<FileEntry>,
-    pub selected_entries: Vec<usize>,
-    pub error: Option<String>,
-    pub status_message: Option<String>,
+    // Listing, selection, history and file clipboard of the active tab
+    pub explorer: Explorer,
+    pub notifications: NotificationCenter,
+    pub audit: AuditLog,
+    pub audit_viewer: AuditViewer,
+    pub journal: SessionJournal,
+    // Paste or delete waiting in the preview window
+    pub pending_plan: Option<PlanPreview>,
+    // Change to a protected folder waiting for its name to be typed
+    pub pending_confirmation: Option<PendingConfirmation>,
     
-    // File operations
-    pub clipboard_operation: Option<FileOperation>,
+    // System clipboard for copied paths
     pub clipboard: Result<Clipboard, arboard::Error>,
     
     // Navigation
-    pub navigation_history: Vec<PathBuf>,
-    pub history_index: usize,
     pub breadcrumbs: Vec<(String, PathBuf)>,
+    pub path_bar_editing: bool,
+    pub path_bar_text: String,
+    pub path_bar_suggestions: Vec<String>,
+    pub path_bar_focus_pending: bool,
+    pub frecency: Arc<Mutex<FrecencyDb>>,
+    pub jump_input: String,
+    
+    // Tabs - the active tab's state is mirrored by `explorer` and the fields below
+    pub tabs: Vec<Tab>,
+    pub active_tab: usize,
+    pub view_mode: ViewMode,
+    pub columns: ColumnsState,
+    
+    // Commander mode - the focused pane is always the active tab, the other one lives here
+    pub dual_pane: bool,
+    pub other_pane: Tab,
+    pub other_entries: Vec<FileEntry>,
+    pub active_side: PaneSide,
     
     // Bookmarks
-    pub bookmarks: Vec<Bookmark>,
+    pub bookmarks: BookmarkStore,
     pub show_bookmarks: bool,
     pub bookmark_name_input: String,
     
+    // Folder tree
+    pub sidebar: SidebarState,
+    
     // Terminal - new improved terminal
     pub terminal: TerminalState,
     
@@ -48,37 +97,93 @@ pub struct FileExplorerApp {
     pub show_rename_dialog: bool,
     pub rename_text: String,
     pub rename_index: Option<usize>,
+    pub batch_renamer: BatchRenamer,
+    pub editor_rename: EditorRename,
+    pub compress_dialog: CompressDialog,
+    pub extract_dialog: ExtractDialog,
+    // Archive work running on other threads
+    pub jobs: JobList,
     
     // New file/folder dialogs
     pub show_new_file_dialog: bool,
     pub show_new_folder_dialog: bool,
     pub new_name_input: String,
+    
+    // Set in `--pick` mode
+    pub picker: Option<PickerState>,
+    // Control socket for later launches and scripts
+    pub ipc: Option<IpcServer>,
+    // Kept alive to own org.freedesktop.FileManager1 on the session bus
+    pub dbus: Option<DbusService>,
+    
+    // Window state for session restore
+    pub window_maximized: bool,
+    pub restore_maximized: bool,
 }
 
 impl FileExplorerApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    /// Restores the previous session, then applies the command-line `args`.
+    /// A path given there replaces the restored directory of the active tab.
+    pub fn new(cc: &eframe::CreationContext<'_>, args: &Args, picker: Option<PickerState>) -> Self {
         let path = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
         let settings = AppSettings::load();
+        let frecency = Arc::new(Mutex::new(FrecencyDb::load()));
+        let session = Session::restore(cc.storage).unwrap_or_default();
+        let (tabs, active_tab) = if session.tabs.is_empty() {
+            (vec![Tab::new(path.clone(), settings.view_mode.clone())], 0)
+        } else {
+            (session.tabs, session.active_tab)
+        };
+        let view_mode = settings.view_mode.clone();
+        // Each endpoint works without the other; failures only cost that endpoint
+        let (ipc, dbus) = if picker.is_none() && !args.new_instance {
+            let ipc = IpcServer::start(cc.egui_ctx.clone())
+                .map_err(|e| eprintln!("{}", e))
+                .ok();
+            let dbus = DbusService::start(cc.egui_ctx.clone())
+                .map_err(|e| eprintln!("{}; \"Show in folder\" requests won't reach this window", e))
+                .ok();
+            (ipc, dbus)
+        } else {
+            (None, None)
+        };
+        let other_pane = Tab::new(path.clone(), settings.view_mode.clone());
         
         let mut app = Self {
-            current_path: path.clone(),
-            entries: Vec::new(),
-            selected_entries: Vec::new(),
-            error: None,
-            status_message: None,
+            explorer: Explorer::new(path.clone()),
+            notifications: NotificationCenter::new(),
+            audit: AuditLog::new(AppSettings::config_dir()),
+            audit_viewer: AuditViewer::new(),
+            journal: SessionJournal::new(),
+            pending_plan: None,
+            pending_confirmation: None,
             
-            clipboard_operation: None,
             clipboard: Clipboard::new(),
             
-            navigation_history: vec![path.clone()],
-            history_index: 0,
             breadcrumbs: Vec::new(),
+            path_bar_editing: false,
+            path_bar_text: String::new(),
+            path_bar_suggestions: Vec::new(),
+            path_bar_focus_pending: false,
+            frecency: Arc::clone(&frecency),
+            jump_input: String::new(),
+            
+            tabs,
+            active_tab,
+            view_mode,
+            columns: ColumnsState::new(),
             
-            bookmarks: Vec::new(),
+            dual_pane: false,
+            other_pane,
+            other_entries: Vec::new(),
+            active_side: PaneSide::Left,
+            
+            bookmarks: BookmarkStore::load_from(AppSettings::config_dir().join("bookmarks.json")),
             show_bookmarks: false,
             bookmark_name_input: String::new(),
             
-            terminal: TerminalState::new(),
+            sidebar: SidebarState::new(),
+            terminal: TerminalState::new(frecency),
             context_menu: ContextMenuState::new(),
             settings,
             settings_window: SettingsWindow::new(),
@@ -88,127 +193,482 @@ impl FileExplorerApp {
             show_rename_dialog: false,
             rename_text: String::new(),
             rename_index: None,
+            batch_renamer: BatchRenamer::new(),
+            editor_rename: EditorRename::new(),
+            compress_dialog: CompressDialog::new(),
+            extract_dialog: ExtractDialog::new(),
+            jobs: JobList::new(),
             
             show_new_file_dialog: false,
             show_new_folder_dialog: false,
             new_name_input: String::new(),
+            
+            picker,
+            ipc,
+            dbus,
+            window_maximized: false,
+            restore_maximized: false,
         };
         
-        app.load_bookmarks();
-        app.read_directory();
-        app.update_breadcrumbs();
+        app.terminal.panel_height = session.terminal_height;
+        app.terminal.notifier = Some(app.notifications.notifier(&cc.egui_ctx));
+        app.terminal.audit = Some(app.audit.clone());
+        app.restore_maximized = session.maximized && app.settings.remember_window_size;
+        app.load_active_tab();
+        
+        if let Ok(Some((dir, selected))) = args.start_location() {
+            app.navigate_to(dir);
+            if let Some(file) = selected {
+                app.select_paths(&[file]);
+            }
+        }
+        if let Some(command) = &args.terminal_cmd {
+            app.terminal.execute_command(command);
+        }
         app
     }
 
-    pub fn read_directory(&mut self) {
-        self.error = None;
-        self.status_message = None;
-        self.selected_entries.clear();
-        
-        match operations::read_directory(&self.current_path, self.settings.show_hidden_files) {
-            Ok(entries) => {
-                self.entries = entries;
-            },
-            Err(e) => {
-                self.error = Some(e);
+    /// Runs `command` unless safe mode or a protected path stops it.
+    pub fn execute(&mut self, command: Command) {
+        if command.changes_files() {
+            if self.settings.safe_mode {
+                self.notifications.warn("Safe mode is on, so files can't be changed");
+                return;
+            }
+            let paths = self.explorer.paths_at_risk(&command);
+            match protection::check(&self.settings.protected_paths, &paths) {
+                Some((Protection::Block, folder)) => {
+                    self.notifications.error(format!("{} is protected and can't be changed", folder.display()));
+                    return;
+                }
+                Some((Protection::Confirm, folder)) => {
+                    self.pending_confirmation = Some(PendingConfirmation::new(command, folder));
+                    return;
+                }
+                None => {}
             }
         }
+        self.run_command(command);
     }
 
-    pub fn update_breadcrumbs(&mut self) {
-        self.breadcrumbs = utils::generate_breadcrumbs(&self.current_path);
+    /// Runs `commands` one after another through `execute`. Once one waits
+    /// for confirmation, the rest wait with it.
+    pub fn execute_all(&mut self, commands: Vec<Command>) {
+        let mut commands = commands.into_iter();
+        while let Some(command) = commands.next() {
+            self.execute(command);
+            if let Some(pending) = &mut self.pending_confirmation {
+                pending.then.extend(commands);
+                return;
+            }
+        }
     }
 
-    pub fn navigate_to(&mut self, path: PathBuf) {
-        if path.exists() && path.is_dir() {
-            self.current_path = path.clone();
-            
-            // Update history
-            if self.history_index < self.navigation_history.len() - 1 {
-                self.navigation_history.truncate(self.history_index + 1);
+    /// Runs `command` without the checks of `execute`.
+    pub fn run_command(&mut self, command: Command) {
+        let events = self.explorer.execute(command);
+        let changes: Vec<Change> = events.iter()
+            .filter_map(|event| match event {
+                Event::Changed(change) => Some(change.clone()),
+                _ => None,
+            })
+            .collect();
+        for event in events {
+            self.handle_event(event);
+        }
+        self.record_changes(&changes);
+    }
+
+    /// Adds the changes of one command to the session journal.
+    fn record_changes(&mut self, changes: &[Change]) {
+        for action in Action::from_changes(changes) {
+            self.journal.record(action);
+        }
+    }
+
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::Navigated(path) => {
+                // Neither the terminal nor a jump can go into an archive
+                if archive::locate(&path).is_none() {
+                    self.frecency.lock().unwrap().add(&path);
+                    self.terminal.current_dir = path;
+                }
+                self.update_breadcrumbs();
             }
-            self.navigation_history.push(path.clone());
-            self.history_index = self.navigation_history.len() - 1;
-            
-            // Update terminal directory
-            self.terminal.current_dir = path;
-            
-            self.read_directory();
-            self.update_breadcrumbs();
+            Event::Refreshed => {
+                self.sidebar.invalidate(&self.explorer.current_path);
+                if let Some(picker) = &self.picker {
+                    self.explorer.entries.retain(|e| picker.accepts(e));
+                }
+            }
+            Event::Changed(change) => {
+                if let Err(e) = self.audit.record(&AuditRecord::from_change(&change)) {
+                    self.notifications.file_error(e, None, None);
+                }
+            }
+            Event::SelectionChanged | Event::ClipboardChanged => {}
+            Event::Message(message) => self.notifications.info(message),
+            Event::Error { error, retry, skip } => self.notifications.file_error(error, retry, skip),
         }
     }
 
-    pub fn go_back(&mut self) {
-        if self.history_index > 0 {
-            self.history_index -= 1;
-            self.current_path = self.navigation_history[self.history_index].clone();
-            self.terminal.current_dir = self.current_path.clone();
-            self.read_directory();
-            self.update_breadcrumbs();
+    pub fn read_directory(&mut self) {
+        self.explorer.show_hidden = self.settings.show_hidden_files;
+        self.execute(Command::Refresh);
+    }
+
+    pub fn update_breadcrumbs(&mut self) {
+        self.breadcrumbs = utils::generate_breadcrumbs(&self.explorer.current_path);
+    }
+
+    pub fn navigate_to(&mut self, path: PathBuf) {
+        self.explorer.show_hidden = self.settings.show_hidden_files;
+        self.execute(Command::Navigate(path));
+    }
+
+    /// Turns the breadcrumbs into an editable text field (Ctrl+L).
+    pub fn start_path_edit(&mut self) {
+        let mut text = self.explorer.current_path.to_string_lossy().to_string();
+        if !text.ends_with('/') {
+            text.push('/');
         }
+        self.path_bar_text = text;
+        self.path_bar_suggestions.clear();
+        self.path_bar_editing = true;
+        self.path_bar_focus_pending = true;
     }
 
-    pub fn go_forward(&mut self) {
-        if self.history_index < self.navigation_history.len() - 1 {
-            self.history_index += 1;
-            self.current_path = self.navigation_history[self.history_index].clone();
-            self.terminal.current_dir = self.current_path.clone();
-            self.read_directory();
-            self.update_breadcrumbs();
+    pub fn cancel_path_edit(&mut self) {
+        self.path_bar_editing = false;
+        self.path_bar_suggestions.clear();
+    }
+
+    /// Tab-completes the directory name being typed in the path bar.
+    pub fn complete_path_bar(&mut self) {
+        let keep_slash = self.path_bar_text.ends_with('/');
+        let mut expanded = utils::expand_path(&self.path_bar_text, &self.explorer.current_path).to_string_lossy().to_string();
+        if keep_slash && !expanded.ends_with('/') {
+            expanded.push('/');
+        }
+        
+        let suggestions = utils::complete_path(&self.explorer.current_path, &expanded, true);
+        match suggestions.len() {
+            0 => self.path_bar_text = expanded,
+            1 => {
+                self.path_bar_text = suggestions[0].clone();
+                self.path_bar_suggestions.clear();
+            }
+            _ => {
+                self.path_bar_text = utils::common_prefix(&suggestions);
+                self.path_bar_suggestions = suggestions;
+            }
         }
     }
 
-    pub fn copy_selected(&mut self) {
-        if !self.selected_entries.is_empty() {
-            let paths: Vec<PathBuf> = self.selected_entries.iter()
-                .map(|&i| self.entries[i].path.clone())
-                .collect();
-            self.clipboard_operation = Some(FileOperation::Copy(paths));
-            self.status_message = Some(format!("Copied {} items", self.selected_entries.len()));
+    /// Opens what was typed in the path bar. A file path opens its folder
+    /// with the file selected.
+    pub fn submit_path_bar(&mut self) {
+        let path = utils::expand_path(&self.path_bar_text, &self.explorer.current_path);
+        self.cancel_path_edit();
+        
+        if path.is_dir() {
+            self.navigate_to(path);
+        } else if path.is_file() {
+            if let Some(parent) = path.parent() {
+                self.navigate_to(parent.to_path_buf());
+                self.select_paths(&[path]);
+            }
+        } else if operations::is_folder(&path) {
+            self.navigate_to(path);
+        } else {
+            self.notifications.error(format!("Path does not exist: {}", path.display()));
         }
     }
 
-    pub fn cut_selected(&mut self) {
-        if !self.selected_entries.is_empty() {
-            let paths: Vec<PathBuf> = self.selected_entries.iter()
-                .map(|&i| self.entries[i].path.clone())
-                .collect();
-            self.clipboard_operation = Some(FileOperation::Cut(paths));
-            self.status_message = Some(format!("Cut {} items", self.selected_entries.len()));
+    pub fn jump_to(&mut self, query: &str) {
+        let best = self.frecency.lock().unwrap().best_match(query, &self.explorer.current_path);
+        match best {
+            Some(path) => self.navigate_to(path),
+            None => self.notifications.error(format!("No visited directory matches '{}'", query)),
         }
     }
 
-    pub fn paste(&mut self) {
-        if let Some(operation) = &self.clipboard_operation.clone() {
-            match operation {
-                FileOperation::Copy(paths) => {
-                    for path in paths {
-                        let file_name = path.file_name().unwrap().to_string_lossy();
-                        let dest_path = self.current_path.join(&*file_name);
-                        
-                        if let Err(e) = operations::copy_item(path, &dest_path) {
-                            self.error = Some(e);
-                            return;
-                        }
+    pub fn go_back(&mut self) {
+        self.execute(Command::Back);
+    }
+
+    pub fn go_forward(&mut self) {
+        self.execute(Command::Forward);
+    }
+
+    /// Jumps straight to an entry of the back/forward history.
+    pub fn go_to_history(&mut self, index: usize) {
+        self.execute(Command::GoToHistory(index));
+    }
+
+    /// Applies the commands received on the control socket or over D-Bus.
+    pub fn handle_ipc_commands(&mut self, ctx: &Context) {
+        let mut commands: Vec<IpcCommand> = Vec::new();
+        if let Some(ipc) = &self.ipc {
+            commands.extend(ipc.commands.try_iter());
+        }
+        if let Some(dbus) = &self.dbus {
+            commands.extend(dbus.commands.try_iter());
+        }
+        
+        for command in commands {
+            match command {
+                IpcCommand::Navigate { path } => {
+                    if path.is_file() {
+                        self.reveal_paths(&[path]);
+                    } else {
+                        self.navigate_to(path);
                     }
-                    self.status_message = Some("Paste completed".to_string());
                 }
-                FileOperation::Cut(paths) => {
-                    for path in paths {
-                        let file_name = path.file_name().unwrap().to_string_lossy();
-                        let dest_path = self.current_path.join(&*file_name);
-                        
-                        if let Err(e) = operations::move_item(path, &dest_path) {
-                            self.error = Some(e);
-                            return;
-                        }
+                IpcCommand::Select { paths } => self.reveal_paths(&paths),
+                IpcCommand::Refresh => {
+                    self.read_directory();
+                    self.sidebar.refresh();
+                    if self.dual_pane {
+                        self.refresh_other_pane();
                     }
-                    self.clipboard_operation = None;
-                    self.status_message = Some("Move completed".to_string());
+                    continue;
                 }
+                IpcCommand::Raise => {}
+            }
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+    }
+
+    /// Opens the folder of the first path and selects every path in it.
+    pub fn reveal_paths(&mut self, paths: &[PathBuf]) {
+        let Some(parent) = paths.first().and_then(|p| p.parent()) else { return };
+        if parent != self.explorer.current_path {
+            self.navigate_to(parent.to_path_buf());
+        }
+        self.select_paths(paths);
+    }
+
+    pub fn selected_paths(&self) -> Vec<PathBuf> {
+        self.explorer.selected_paths()
+    }
+
+    pub fn select_paths(&mut self, paths: &[PathBuf]) {
+        self.execute(Command::Select(paths.to_vec()));
+    }
+
+    /// Writes the live browsing state back into the active tab.
+    fn store_active_tab(&mut self) {
+        let selected_paths = self.selected_paths();
+        let tab = &mut self.tabs[self.active_tab];
+        tab.path = self.explorer.current_path.clone();
+        tab.history = self.explorer.history.entries.clone();
+        tab.history_index = self.explorer.history.index;
+        tab.selected_paths = selected_paths;
+        tab.filter = self.explorer.filter.clone();
+        tab.sort_by = self.explorer.sort_by;
+        tab.sort_ascending = self.explorer.sort_ascending;
+        tab.view_mode = self.view_mode.clone();
+    }
+
+    fn load_active_tab(&mut self) {
+        let tab = self.tabs[self.active_tab].clone();
+        self.explorer.current_path = tab.path;
+        self.explorer.history.entries = tab.history;
+        self.explorer.history.index = tab.history_index;
+        self.explorer.filter = tab.filter;
+        self.explorer.sort_by = tab.sort_by;
+        self.explorer.sort_ascending = tab.sort_ascending;
+        self.view_mode = tab.view_mode;
+        if !self.browsing_archive() {
+            self.terminal.current_dir = self.explorer.current_path.clone();
+        }
+        
+        self.read_directory();
+        self.update_breadcrumbs();
+        self.select_paths(&tab.selected_paths);
+    }
+
+    pub fn tab_title(&self, index: usize) -> String {
+        if index == self.active_tab {
+            Tab::new(self.explorer.current_path.clone(), self.view_mode.clone()).title()
+        } else {
+            self.tabs[index].title()
+        }
+    }
+
+    /// Opens `path` in a new tab right after the active one.
+    pub fn open_tab(&mut self, path: PathBuf, activate: bool) {
+        let tab = Tab::new(path, self.settings.view_mode.clone());
+        self.tabs.insert(self.active_tab + 1, tab);
+        if activate {
+            self.switch_tab(self.active_tab + 1);
+        }
+    }
+
+    pub fn switch_tab(&mut self, index: usize) {
+        if index < self.tabs.len() && index != self.active_tab {
+            self.store_active_tab();
+            self.active_tab = index;
+            self.load_active_tab();
+        }
+    }
+
+    pub fn close_tab(&mut self, index: usize) {
+        if self.tabs.len() <= 1 || index >= self.tabs.len() {
+            return;
+        }
+        
+        self.tabs.remove(index);
+        if index < self.active_tab {
+            self.active_tab -= 1;
+        } else if index == self.active_tab {
+            self.active_tab = index.min(self.tabs.len() - 1);
+            self.load_active_tab();
+        }
+    }
+
+    pub fn move_tab(&mut self, from: usize, to: usize) {
+        if from == to || from >= self.tabs.len() || to >= self.tabs.len() {
+            return;
+        }
+        
+        self.store_active_tab();
+        let tab = self.tabs.remove(from);
+        self.tabs.insert(to, tab);
+        
+        if self.active_tab == from {
+            self.active_tab = to;
+        } else if from < self.active_tab && to >= self.active_tab {
+            self.active_tab -= 1;
+        } else if from > self.active_tab && to <= self.active_tab {
+            self.active_tab += 1;
+        }
+    }
+
+    pub fn cycle_tab(&mut self, forward: bool) {
+        let count = self.tabs.len();
+        let next = if forward {
+            (self.active_tab + 1) % count
+        } else {
+            (self.active_tab + count - 1) % count
+        };
+        self.switch_tab(next);
+    }
+
+    fn session(&mut self) -> Session {
+        self.store_active_tab();
+        Session {
+            tabs: self.tabs.clone(),
+            active_tab: self.active_tab,
+            terminal_height: self.terminal.panel_height,
+            maximized: self.window_maximized,
+        }
+    }
+
+    pub fn toggle_dual_pane(&mut self) {
+        self.dual_pane = !self.dual_pane;
+        if self.dual_pane {
+            self.refresh_other_pane();
+        }
+    }
+
+    pub fn refresh_other_pane(&mut self) {
+        let pane = &self.other_pane;
+        match operations::read_directory(&pane.path, self.settings.show_hidden_files) {
+            Ok(mut entries) => {
+                entries.retain(|e| utils::filter_matches(e, &pane.filter));
+                utils::sort_entries(&mut entries, pane.sort_by, pane.sort_ascending);
+                self.other_entries = entries;
             }
-            self.read_directory();
+            Err(e) => {
+                self.other_entries.clear();
+                self.notifications.file_error(e, None, None);
+            }
+        }
+    }
+
+    /// Moves keyboard focus to the other pane.
+    pub fn switch_pane(&mut self) {
+        self.store_active_tab();
+        std::mem::swap(&mut self.tabs[self.active_tab], &mut self.other_pane);
+        self.active_side = match self.active_side {
+            PaneSide::Left => PaneSide::Right,
+            PaneSide::Right => PaneSide::Left,
+        };
+        self.load_active_tab();
+        self.refresh_other_pane();
+    }
+
+    /// Exchanges the directories shown by the two panes; focus stays on its side.
+    pub fn swap_panes(&mut self) {
+        self.store_active_tab();
+        std::mem::swap(&mut self.tabs[self.active_tab], &mut self.other_pane);
+        self.load_active_tab();
+        self.refresh_other_pane();
+    }
+
+    /// Points the other pane at the focused pane's directory.
+    pub fn sync_panes(&mut self) {
+        let pane = &mut self.other_pane;
+        if pane.path != self.explorer.current_path {
+            pane.history.truncate(pane.history_index + 1);
+            pane.history.push(self.explorer.current_path.clone());
+            pane.history_index = pane.history.len() - 1;
+            pane.path = self.explorer.current_path.clone();
+            pane.selected_paths.clear();
+        }
+        self.refresh_other_pane();
+    }
+
+    /// Copies (F5) or moves (F6) the selection into the other pane's directory.
+    pub fn transfer_to_other_pane(&mut self, move_items: bool) {
+        let sources = self.selected_paths();
+        if sources.is_empty() {
+            return;
+        }
+        
+        let target = self.other_pane.path.clone();
+        self.transfer_paths(&sources, &target, move_items);
+    }
+
+    /// Handles files dropped onto a folder, e.g. in the sidebar tree.
+    pub fn drop_paths(&mut self, sources: &[PathBuf], target: &Path, copy: bool) {
+        self.transfer_paths(sources, target, !copy);
+        self.sidebar.refresh();
+    }
+
+    fn transfer_paths(&mut self, sources: &[PathBuf], target: &Path, move_items: bool) {
+        let operation = if move_items {
+            FileOperation::Cut(sources.to_vec())
+        } else {
+            FileOperation::Copy(sources.to_vec())
+        };
+        self.execute(Command::Transfer { operation, target: target.to_path_buf(), conflict: ConflictPolicy::Fail });
+        if self.dual_pane {
+            self.refresh_other_pane();
+        }
+    }
+
+    pub fn copy_selected(&mut self) {
+        self.execute(Command::CopySelection);
+    }
+
+    pub fn cut_selected(&mut self) {
+        self.execute(Command::CutSelection);
+    }
+
+    pub fn paste(&mut self) {
+        if self.settings.preview_bulk_operations
+            && let Some(operation) = &self.explorer.clipboard.operation
+        {
+            let (operation, target) = (operation.clone(), self.explorer.current_path.clone());
+            self.pending_plan = Some(PlanPreview::build(move |cancel| Plan::transfer(&operation, &target, cancel)));
+            return;
         }
+        self.execute(Command::Paste);
     }
 
     pub fn delete_selected(&mut self) {
@@ -218,75 +678,168 @@ impl FileExplorerApp {
             // TODO: Show confirmation dialog
         }
         
-        for &index in &self.selected_entries {
-            if let Some(entry) = self.entries.get(index) {
-                if let Err(e) = operations::delete_item(&entry.path) {
-                    self.error = Some(e);
-                    return;
-                }
+        if self.settings.preview_bulk_operations {
+            let paths = self.explorer.selected_paths();
+            if !paths.is_empty() {
+                self.pending_plan = Some(PlanPreview::build(move |cancel| Plan::delete(&paths, cancel)));
             }
+            return;
         }
-        self.status_message = Some(format!("Deleted {} items", self.selected_entries.len()));
-        self.read_directory();
+        self.execute(Command::DeleteSelection);
     }
 
     pub fn create_new_file(&mut self, name: &str) {
-        let path = self.current_path.join(name);
-        if let Err(e) = std::fs::File::create(&path) {
-            self.error = Some(format!("Failed to create file: {}", e));
-        } else {
-            self.status_message = Some(format!("Created file: {}", name));
-            self.read_directory();
-        }
+        self.execute(Command::CreateFile(self.explorer.current_path.join(name)));
     }
 
     pub fn create_new_folder(&mut self, name: &str) {
-        let path = self.current_path.join(name);
-        if let Err(e) = std::fs::create_dir(&path) {
-            self.error = Some(format!("Failed to create folder: {}", e));
-        } else {
-            self.status_message = Some(format!("Created folder: {}", name));
-            self.read_directory();
+        self.execute(Command::CreateFolder(self.explorer.current_path.join(name)));
+    }
+
+    /// Opens the rename dialog for one selected item, or the batch renamer
+    /// for several (F2).
+    pub fn start_rename(&mut self) {
+        if self.explorer.selection.len() > 1 {
+            let entries = self.explorer.selected_entries();
+            self.batch_renamer.open(entries);
+        } else if let Some((index, entry)) = self.explorer.first_selected() {
+            self.rename_text = entry.name.clone();
+            self.rename_index = Some(index);
+            self.show_rename_dialog = true;
         }
     }
 
-    pub fn rename_file(&mut self, index: usize, new_name: &str) {
-        if let Some(entry) = self.entries.get(index) {
-            let new_path = self.current_path.join(new_name);
-            if let Err(e) = std::fs::rename(&entry.path, &new_path) {
-                self.error = Some(format!("Failed to rename: {}", e));
+    /// Opens the selected names, or all names of the folder, in the editor
+    /// (Shift+F2).
+    pub fn rename_in_editor(&mut self, ctx: &Context) {
+        if self.editor_rename.is_running() {
+            self.notifications.warn("The names are already open in the editor");
+            return;
+        }
+        let mut paths = self.explorer.selected_paths();
+        if paths.is_empty() {
+            paths = self.explorer.entries.iter().map(|e| e.path.clone()).collect();
+        }
+        if paths.is_empty() {
+            return;
+        }
+        if let Err(e) = self.editor_rename.start(paths, &self.settings.default_editor, ctx) {
+            self.notifications.error(e);
+        }
+    }
+
+    /// Applies the names from the editor once it has closed.
+    fn finish_editor_rename(&mut self) {
+        let Some(result) = self.editor_rename.poll() else { return };
+        let changes = match result {
+            Ok(changes) => changes,
+            Err(e) => {
+                self.notifications.error(e);
+                return;
+            }
+        };
+        if changes.renames.is_empty() && changes.removed.is_empty() {
+            self.notifications.info("No names were changed");
+            return;
+        }
+        // Trash first so a file can take the name of one whose line was deleted
+        let mut trashed = false;
+        if !changes.removed.is_empty() {
+            if self.settings.editor_rename_trashes_deleted {
+                trashed = true;
+                self.execute(Command::Trash(changes.removed));
             } else {
-                self.status_message = Some(format!("Renamed to: {}", new_name));
-                self.read_directory();
+                self.notifications.info(format!("{} deleted lines were ignored; turn on trashing them in the settings", changes.removed.len()));
+            }
+        }
+        if !changes.renames.is_empty() {
+            let rename = Command::RenameBatch(changes.renames);
+            // A trash waiting for confirmation has to finish before the names it frees are reused
+            match &mut self.pending_confirmation {
+                Some(pending) if trashed && matches!(pending.command, Command::Trash(_)) => pending.then.push(rename),
+                _ => self.execute(rename),
             }
         }
     }
 
-    pub fn add_bookmark(&mut self, name: String, path: PathBuf) {
-        self.bookmarks.push(Bookmark { name, path });
-        self.save_bookmarks();
+    /// Reports background jobs that ended and shows what they changed.
+    fn finish_jobs(&mut self) {
+        let outcomes = self.jobs.finished();
+        if outcomes.is_empty() {
+            return;
+        }
+        for outcome in outcomes {
+            let outcome = match outcome {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    self.notifications.error(e);
+                    continue;
+                }
+            };
+            for change in &outcome.changes {
+                self.handle_event(Event::Changed(change.clone()));
+            }
+            self.record_changes(&outcome.changes);
+            match outcome.result {
+                Ok(message) => self.notifications.info(message),
+                Err(e) if e.reason() == "cancelled" => self.notifications.info(format!("Cancelled: {}", e.path().display())),
+                Err(e) => self.notifications.file_error(e, None, None),
+            }
+        }
+        self.read_directory();
+    }
+
+    pub fn rename_file(&mut self, index: usize, new_name: &str) {
+        if let Some(entry) = self.explorer.entries.get(index) {
+            let path = entry.path.clone();
+            self.execute(Command::Rename { path, new_name: new_name.to_string() });
+        }
     }
 
-    pub fn save_bookmarks(&mut self) {
-        // TODO: Implement bookmark saving
+    pub fn add_bookmark(&mut self, name: String, path: PathBuf) {
+        if let Err(e) = self.bookmarks.add(name, path) {
+            self.notifications.file_error(e, None, None);
+        }
     }
 
-    pub fn load_bookmarks(&mut self) {
-        // TODO: Implement bookmark loading
+    pub fn remove_bookmark(&mut self, index: usize) {
+        if let Err(e) = self.bookmarks.remove(index) {
+            self.notifications.file_error(e, None, None);
+        }
     }
 
-    pub fn open_file(&mut self, path: &PathBuf) {
-        if let Err(e) = open::that(path) {
-            self.error = Some(format!("Failed to open file: {}", e));
+    pub fn open_file(&mut self, path: &Path) {
+        // Opening a file while picking chooses it
+        if self.picker.is_some() {
+            picker::choose(self, path);
+            return;
+        }
+        
+        if self.settings.browse_archives && path.is_file() && ArchiveKind::detect(path).is_some_and(ArchiveKind::is_browsable) {
+            self.navigate_to(path.to_path_buf());
+            return;
+        }
+        
+        if let Err(e) = operations::open_file(path) {
+            self.notifications.file_error(e, None, None);
         }
     }
 
-    pub fn handle_context_menu_action(&mut self, action: ContextMenuAction) {
+    /// Whether the current folder is inside an archive, where nothing can
+    /// be changed.
+    pub fn browsing_archive(&self) -> bool {
+        archive::locate(&self.explorer.current_path).is_some()
+    }
+
+    pub fn handle_context_menu_action(&mut self, action: ContextMenuAction, ctx: &Context) {
+        if self.settings.safe_mode && action.changes_files() {
+            self.notifications.warn("Safe mode is on, so files can't be changed");
+            return;
+        }
         match action {
             ContextMenuAction::Open => {
-                if let Some(&index) = self.selected_entries.first() {
-                    let entry_path = self.entries[index].path.clone();
-                    let is_dir = self.entries[index].is_dir;
+                if let Some((_, entry)) = self.explorer.first_selected() {
+                    let (entry_path, is_dir) = (entry.path.clone(), entry.is_dir);
                     
                     if is_dir {
                         self.navigate_to(entry_path);
@@ -299,17 +852,26 @@ impl FileExplorerApp {
             ContextMenuAction::Copy => self.copy_selected(),
             ContextMenuAction::Paste => self.paste(),
             ContextMenuAction::Delete => self.delete_selected(),
-            ContextMenuAction::Rename => {
-                if let Some(&index) = self.selected_entries.first() {
-                    self.show_rename_dialog = true;
-                    self.rename_index = Some(index);
-                    self.rename_text = self.entries[index].name.clone();
+            ContextMenuAction::Rename => self.start_rename(),
+            ContextMenuAction::RenameInEditor => self.rename_in_editor(ctx),
+            ContextMenuAction::Compress => {
+                let paths = self.explorer.selected_paths();
+                if !paths.is_empty() {
+                    self.compress_dialog.open(paths, &self.explorer.current_path);
+                }
+            }
+            ContextMenuAction::Extract => {
+                if let Some((_, entry)) = self.explorer.first_selected() {
+                    let archive = entry.path.clone();
+                    if let Err(e) = self.extract_dialog.open(&archive, &self.explorer.current_path) {
+                        self.notifications.error(e);
+                    }
                 }
             }
             ContextMenuAction::Properties => {
-                if let Some(&index) = self.selected_entries.first() {
+                if let Some((_, entry)) = self.explorer.first_selected() {
+                    self.properties_file = Some(entry.clone());
                     self.show_properties_dialog = true;
-                    self.properties_file = Some(self.entries[index].clone());
                 }
             }
             ContextMenuAction::CreateNew(item_type) => {
@@ -323,29 +885,23 @@ impl FileExplorerApp {
                         self.new_name_input.clear();
                     }
                     _ => {
-                        self.status_message = Some("Feature not implemented yet".to_string());
+                        self.notifications.info("Feature not implemented yet".to_string());
                     }
                 }
             }
             ContextMenuAction::CopyPath => {
-                if let Some(&index) = self.selected_entries.first() {
-                    let path = self.entries[index].path.to_string_lossy().to_string();
+                if let Some((_, entry)) = self.explorer.first_selected() {
+                    let path = entry.path.to_string_lossy().to_string();
                     if let Ok(ref mut clipboard) = self.clipboard {
                         let _ = clipboard.set_text(path);
-                        self.status_message = Some("Path copied to clipboard".to_string());
+                        self.notifications.info("Path copied to clipboard".to_string());
                     }
                 }
             }
             ContextMenuAction::OpenInTerminal => {
-                let path = if let Some(&index) = self.selected_entries.first() {
-                    let entry = &self.entries[index];
-                    if entry.is_dir {
-                        entry.path.clone()
-                    } else {
-                        self.current_path.clone()
-                    }
-                } else {
-                    self.current_path.clone()
+                let path = match self.explorer.first_selected() {
+                    Some((_, entry)) if entry.is_dir => entry.path.clone(),
+                    _ => self.explorer.current_path.clone(),
                 };
                 
                 // Change terminal directory
@@ -353,69 +909,112 @@ impl FileExplorerApp {
                 self.navigate_to(path);
             }
             ContextMenuAction::AddToBookmarks => {
-                let path = if let Some(&index) = self.selected_entries.first() {
-                    self.entries[index].path.clone()
-                } else {
-                    self.current_path.clone()
+                let path = match self.explorer.first_selected() {
+                    Some((_, entry)) => entry.path.clone(),
+                    None => self.explorer.current_path.clone(),
                 };
                 let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
                 self.add_bookmark(name, path);
-                self.status_message = Some("Added to bookmarks".to_string());
+                self.notifications.info("Added to bookmarks".to_string());
             }
             ContextMenuAction::OpenInEditor => {
-                if let Some(&index) = self.selected_entries.first() {
-                    let entry_path = self.entries[index].path.clone();
+                if let Some((_, entry)) = self.explorer.first_selected() {
+                    let entry_path = entry.path.clone();
                     let editor = self.settings.default_editor.clone();
                     
                     if let Err(e) = std::process::Command::new(editor)
                         .arg(&entry_path)
                         .spawn() {
-                        self.error = Some(format!("Failed to open editor: {}", e));
+                        self.notifications.error(format!("Failed to open editor: {}", e));
                     }
                 }
             }
             _ => {
-                self.status_message = Some("Feature not implemented yet".to_string());
+                self.notifications.info("Feature not implemented yet".to_string());
             }
         }
     }
 
     pub fn handle_keyboard_shortcuts(&mut self, ctx: &Context) {
+        // Shortcuts that change files do nothing in safe mode or in archives
+        let writable = !self.settings.safe_mode && !self.browsing_archive();
+        // Plain keys belong to the text field or terminal input that has focus
+        let nothing_focused = ctx.memory(|m| m.focused().is_none());
+        let mut rename_in_editor = false;
         ctx.input_mut(|i| {
             if i.consume_key(egui::Modifiers::CTRL, egui::Key::C) {
                 self.copy_selected();
             }
-            if i.consume_key(egui::Modifiers::CTRL, egui::Key::X) {
+            if writable && i.consume_key(egui::Modifiers::CTRL, egui::Key::X) {
                 self.cut_selected();
             }
-            if i.consume_key(egui::Modifiers::CTRL, egui::Key::V) {
+            if writable && i.consume_key(egui::Modifiers::CTRL, egui::Key::V) {
                 self.paste();
             }
-            if i.consume_key(egui::Modifiers::NONE, egui::Key::Delete) {
+            if writable && i.consume_key(egui::Modifiers::NONE, egui::Key::Delete) {
                 self.delete_selected();
             }
-            if i.consume_key(egui::Modifiers::NONE, egui::Key::F2) {
-                if let Some(&index) = self.selected_entries.first() {
-                    self.show_rename_dialog = true;
-                    self.rename_index = Some(index);
-                    self.rename_text = self.entries[index].name.clone();
-                }
+            if writable && i.consume_key(egui::Modifiers::SHIFT, egui::Key::F2) {
+                rename_in_editor = true;
             }
-            if i.consume_key(egui::Modifiers::CTRL, egui::Key::N) {
+            if writable && i.consume_key(egui::Modifiers::NONE, egui::Key::F2) {
+                self.start_rename();
+            }
+            if writable && i.consume_key(egui::Modifiers::CTRL, egui::Key::N) {
                 self.show_new_file_dialog = true;
                 self.new_name_input.clear();
             }
-            if i.consume_key(egui::Modifiers::CTRL | egui::Modifiers::SHIFT, egui::Key::N) {
+            if writable && i.consume_key(egui::Modifiers::CTRL | egui::Modifiers::SHIFT, egui::Key::N) {
                 self.show_new_folder_dialog = true;
                 self.new_name_input.clear();
             }
-            if i.consume_key(egui::Modifiers::NONE, egui::Key::F5) {
+            if self.dual_pane {
+                if writable && i.consume_key(egui::Modifiers::NONE, egui::Key::F5) {
+                    self.transfer_to_other_pane(false);
+                }
+                if writable && i.consume_key(egui::Modifiers::NONE, egui::Key::F6) {
+                    self.transfer_to_other_pane(true);
+                }
+                if nothing_focused && i.consume_key(egui::Modifiers::NONE, egui::Key::Tab) {
+                    self.switch_pane();
+                }
+                if nothing_focused && i.consume_key(egui::Modifiers::CTRL, egui::Key::U) {
+                    self.swap_panes();
+                }
+                if nothing_focused && i.consume_key(egui::Modifiers::ALT, egui::Key::O) {
+                    self.sync_panes();
+                }
+            } else if i.consume_key(egui::Modifiers::NONE, egui::Key::F5) {
                 self.read_directory();
             }
+            if i.consume_key(egui::Modifiers::CTRL, egui::Key::D) {
+                self.toggle_dual_pane();
+            }
+            if nothing_focused && i.consume_key(egui::Modifiers::CTRL, egui::Key::L) {
+                self.start_path_edit();
+            }
+            if i.consume_key(egui::Modifiers::CTRL, egui::Key::B) {
+                self.sidebar.show = !self.sidebar.show;
+            }
             if i.consume_key(egui::Modifiers::CTRL, egui::Key::Comma) {
                 self.settings_window.show = true;
             }
+            if i.consume_key(egui::Modifiers::CTRL, egui::Key::T) {
+                self.open_tab(self.explorer.current_path.clone(), true);
+            }
+            if i.consume_key(egui::Modifiers::CTRL, egui::Key::W) {
+                self.close_tab(self.active_tab);
+            }
+            if i.consume_key(egui::Modifiers::CTRL | egui::Modifiers::SHIFT, egui::Key::Tab) {
+                self.cycle_tab(false);
+            }
+            if i.consume_key(egui::Modifiers::CTRL, egui::Key::Tab) {
+                self.cycle_tab(true);
+            }
         });
+        if rename_in_editor {
+            self.rename_in_editor(ctx);
+        }
     }
 
     pub fn apply_theme(&self, ctx: &Context) {
@@ -429,20 +1028,15 @@ impl FileExplorerApp {
         if response.clicked() {
             if ctx.input(|i| i.modifiers.ctrl) {
                 // Ctrl+click for multi-selection
-                if let Some(pos) = self.selected_entries.iter().position(|&i| i == index) {
-                    self.selected_entries.remove(pos);
-                } else {
-                    self.selected_entries.push(index);
-                }
+                self.execute(Command::ToggleSelected(index));
             } else {
-                // Regular click
-                self.selected_entries = vec![index];
+                self.execute(Command::SelectOnly(index));
             }
         }
 
         if response.double_clicked() && self.settings.double_click_to_open {
-            let entry_path = self.entries[index].path.clone();
-            let is_dir = self.entries[index].is_dir;
+            let entry_path = self.explorer.entries[index].path.clone();
+            let is_dir = self.explorer.entries[index].is_dir;
             
             if is_dir {
                 self.navigate_to(entry_path);
@@ -451,11 +1045,16 @@ impl FileExplorerApp {
             }
         }
 
+        if response.middle_clicked() && self.explorer.entries[index].is_dir {
+            let entry_path = self.explorer.entries[index].path.clone();
+            self.open_tab(entry_path, false);
+        }
+
         if response.secondary_clicked() {
             // Right-click - show context menu
             if let Some(pos) = response.interact_pointer_pos() {
-                if !self.selected_entries.contains(&index) {
-                    self.selected_entries = vec![index];
+                if !self.explorer.selection.contains(index) {
+                    self.execute(Command::SelectOnly(index));
                 }
                 self.context_menu.show_at(pos, Some(index));
             }
@@ -464,43 +1063,75 @@ impl FileExplorerApp {
 }
 
 impl eframe::App for FileExplorerApp {
+
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
         // Apply theme
         self.apply_theme(ctx);
         
+        // eframe restores size and position but not the maximized state
+        if self.restore_maximized {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Maximized(true));
+            self.restore_maximized = false;
+        }
+        if let Some(maximized) = ctx.input(|i| i.viewport().maximized) {
+            self.window_maximized = maximized;
+        }
+        
         // Handle keyboard shortcuts
         self.handle_keyboard_shortcuts(ctx);
         
+        self.handle_ipc_commands(ctx);
+        
+        self.notifications.receive();
+        self.finish_editor_rename();
+        self.finish_jobs();
+        
         // Show main UI
         ui::show_top_panel(self, ctx);
         
         // Show settings window
         self.settings_window.show_window(ctx, &mut self.settings);
+        self.audit_viewer.show_window(ctx, &self.audit);
+        journal_ui::show_journal_window(self, ctx);
+        plan_ui::show_plan_window(self, ctx);
+        rename_ui::show_batch_rename_window(self, ctx);
+        archive_ui::show_compress_window(self, ctx);
+        archive_ui::show_extract_window(self, ctx);
+        protection::show_confirmation_window(self, ctx);
         
         // Show context menu
+        let read_only = self.settings.safe_mode || self.browsing_archive();
         if let Some(action) = crate::context_menu::show_context_menu(
             ctx,
             &mut self.context_menu,
-            &self.entries,
-            &self.selected_entries,
-            self.clipboard_operation.is_some(),
+            &self.explorer.entries,
+            &self.explorer.selection.indices,
+            self.explorer.clipboard.operation.is_some(),
+            read_only,
         ) {
-            self.handle_context_menu_action(action);
+            self.handle_context_menu_action(action, ctx);
         }
         
         // Handle empty space right-click
-        ctx.input(|i| {
-            if i.pointer.secondary_clicked() && !self.context_menu.is_visible() {
-                if let Some(pos) = i.pointer.interact_pos() {
-                    self.selected_entries.clear();
-                    self.context_menu.show_at(pos, None);
-                }
-            }
+        let empty_space_click = ctx.input(|i| {
+            i.pointer.interact_pos().filter(|_| i.pointer.secondary_clicked() && !self.context_menu.is_visible())
         });
+        if let Some(pos) = empty_space_click {
+            self.execute(Command::ClearSelection);
+            self.context_menu.show_at(pos, None);
+        }
+        
+        // Folder tree side panel
+        sidebar::show_sidebar(self, ctx);
+        notifications::show_drawer(self, ctx);
         
         // Central panel for file list
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui::show_file_list(self, ui);
+            if self.dual_pane {
+                ui::show_dual_pane(self, ui);
+            } else {
+                ui::show_file_list(self, ui);
+            }
         });
         
         // Terminal panel
@@ -508,10 +1139,35 @@ impl eframe::App for FileExplorerApp {
         
         // Show dialogs
         ui::show_dialogs(self, ctx);
+        notifications::show_toasts(self, ctx);
+        jobs::show_jobs(self, ctx);
+        
+        if self.picker.as_ref().is_some_and(|p| p.done) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
         
-        // Update current directory from terminal if changed
-        if self.terminal.current_dir != self.current_path {
+        // Update current directory from terminal if changed. While an archive
+        // is browsed the terminal stays in the last real folder.
+        if !self.browsing_archive() && self.terminal.current_dir != self.explorer.current_path {
             self.navigate_to(self.terminal.current_dir.clone());
         }
     }
-} 
\ No newline at end of file
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        archive::remove_temporary_copies();
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // Called periodically and on exit, which batches the frecency writes
+        let flushed = self.frecency.lock().unwrap().flush();
+        if let Err(e) = flushed {
+            self.notifications.error(e);
+        }
+
+        // A file dialog shouldn't replace the tabs of the normal session
+        if self.picker.is_some() {
+            return;
+        }
+        self.session().store(storage);
+    }
+}
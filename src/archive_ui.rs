@@ -0,0 +1,287 @@
+use eframe::egui::{self, Context, RichText};
+use std::path::{Path, PathBuf};
+use fileexp_core::archive::{self, ArchiveFormat, ArchiveKind, CompressOptions, ExtractSummary};
+use fileexp_core::models::ConflictPolicy;
+use fileexp_core::{Change, Operation};
+use crate::app::FileExplorerApp;
+use crate::jobs::JobOutcome;
+use crate::operations;
+use crate::protection::{self, Protection};
+
+/// Options window for packing the selection into a new archive.
+pub struct CompressDialog {
+    pub show: bool,
+    sources: Vec<PathBuf>,
+    folder: PathBuf,
+    name: String,
+    /// Kept between uses, like the format and level.
+    options: CompressOptions,
+    exclude: String,
+}
+
+impl CompressDialog {
+    pub fn new() -> Self {
+        Self {
+            show: false,
+            sources: Vec::new(),
+            folder: PathBuf::new(),
+            name: String::new(),
+            options: CompressOptions::new(ArchiveFormat::Zip),
+            exclude: String::new(),
+        }
+    }
+
+    /// Opens the window for `sources`, to create the archive in `folder`.
+    pub fn open(&mut self, sources: Vec<PathBuf>, folder: &Path) {
+        self.name = match sources.as_slice() {
+            [single] if single.is_dir() => file_name(single),
+            [single] => single.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+            _ => file_name(folder),
+        };
+        if self.name.is_empty() {
+            self.name = "Archive".to_string();
+        }
+        self.sources = sources;
+        self.folder = folder.to_path_buf();
+        self.show = true;
+    }
+
+    fn destination(&self) -> PathBuf {
+        self.folder.join(format!("{}.{}", self.name.trim(), self.options.format.extension()))
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+fn format_label(format: ArchiveFormat) -> String {
+    format!(".{}", format.extension())
+}
+
+pub fn show_compress_window(app: &mut FileExplorerApp, ctx: &Context) {
+    if !app.compress_dialog.show {
+        return;
+    }
+
+    let mut open = true;
+    let mut create = false;
+    let dialog = &mut app.compress_dialog;
+    egui::Window::new("🗜️ Compress")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!("{} items from {}", dialog.sources.len(), dialog.folder.display()));
+            egui::Grid::new("compress_options").num_columns(2).spacing([8.0, 6.0]).show(ui, |ui| {
+                ui.label("Name:");
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut dialog.name);
+                    let before = dialog.options.format;
+                    egui::ComboBox::from_id_source("compress_format")
+                        .selected_text(format_label(dialog.options.format))
+                        .show_ui(ui, |ui| {
+                            for format in ArchiveFormat::ALL {
+                                ui.selectable_value(&mut dialog.options.format, format, format_label(format));
+                            }
+                        });
+                    if dialog.options.format != before {
+                        dialog.options.level = dialog.options.format.default_level();
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Level:");
+                match dialog.options.format.levels() {
+                    Some((low, high)) => ui.add(egui::Slider::new(&mut dialog.options.level, low..=high)),
+                    None => ui.label(RichText::new("no compression").weak()),
+                };
+                ui.end_row();
+
+                ui.label("Exclude:");
+                ui.add(egui::TextEdit::singleline(&mut dialog.exclude).hint_text("*.o, target, .git"))
+                    .on_hover_text("Comma-separated globs matched against names and paths inside the archive");
+                ui.end_row();
+
+                ui.label("");
+                ui.checkbox(&mut dialog.options.preserve_permissions, "Keep permissions and owners");
+                ui.end_row();
+
+                ui.label("");
+                ui.checkbox(&mut dialog.options.preserve_symlinks, "Store symbolic links as links");
+                ui.end_row();
+            });
+
+            let destination = dialog.destination();
+            let valid = !dialog.name.trim().is_empty() && !dialog.name.contains('/');
+            if valid && destination.exists() {
+                ui.label(RichText::new(format!("{} exists; a new name will be picked", file_name(&destination))).weak());
+            }
+            ui.horizontal(|ui| {
+                if ui.add_enabled(valid, egui::Button::new("Create")).clicked() {
+                    create = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    dialog.show = false;
+                }
+            });
+        });
+    if !open {
+        app.compress_dialog.show = false;
+    }
+
+    if create {
+        app.compress_dialog.show = false;
+        // Safe mode may have been turned on while the dialog was open
+        if app.settings.safe_mode {
+            app.notifications.warn("Safe mode is on, so files can't be changed");
+            return;
+        }
+        let dialog = &mut app.compress_dialog;
+        let mut options = dialog.options.clone();
+        options.exclude = dialog.exclude.split(',').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect();
+        let sources = dialog.sources.clone();
+        let destination = operations::unique_path(&dialog.destination());
+        let title = format!("Compressing {}", file_name(&destination));
+
+        app.jobs.spawn(ctx, title, move |job| {
+            let result = archive::create_archive(&sources, &destination, &options, &mut |progress| job.report(progress), &job.cancel);
+            let changes = sources.iter().map(|source| Change {
+                operation: Operation::Compress,
+                source: source.clone(),
+                destination: Some(destination.clone()),
+                error: result.as_ref().err().cloned(),
+            }).collect();
+            JobOutcome { changes, result: result.map(|()| format!("Created {}", file_name(&destination))) }
+        });
+    }
+}
+
+/// Window for unpacking an archive next to itself.
+pub struct ExtractDialog {
+    pub show: bool,
+    archive: PathBuf,
+    folder: PathBuf,
+    /// What to do with files that exist already, kept between uses.
+    policy: ConflictPolicy,
+    /// Name typed to confirm extracting into a protected folder.
+    typed: String,
+}
+
+impl ExtractDialog {
+    pub fn new() -> Self {
+        Self { show: false, archive: PathBuf::new(), folder: PathBuf::new(), policy: ConflictPolicy::Rename, typed: String::new() }
+    }
+
+    /// Opens the window for `archive`, or says why it can't be extracted.
+    pub fn open(&mut self, archive: &Path, folder: &Path) -> Result<(), String> {
+        if ArchiveKind::detect(archive).is_none() {
+            return Err(format!("{} is not an archive that can be extracted", file_name(archive)));
+        }
+        self.archive = archive.to_path_buf();
+        self.folder = folder.to_path_buf();
+        self.typed.clear();
+        self.show = true;
+        Ok(())
+    }
+}
+
+fn policy_label(policy: ConflictPolicy) -> &'static str {
+    match policy {
+        ConflictPolicy::Rename => "Keep both",
+        ConflictPolicy::Skip => "Skip",
+        ConflictPolicy::Overwrite => "Overwrite",
+        ConflictPolicy::Fail => "Stop",
+    }
+}
+
+fn extract_message(summary: &ExtractSummary) -> String {
+    let mut message = format!("Extracted {} items to {}", summary.extracted, file_name(&summary.destination));
+    if summary.skipped > 0 {
+        message.push_str(&format!(", skipped {} that existed", summary.skipped));
+    }
+    if !summary.unsafe_entries.is_empty() {
+        message.push_str(&format!(", refused {} unsafe entries such as {}", summary.unsafe_entries.len(), summary.unsafe_entries[0]));
+    }
+    message
+}
+
+pub fn show_extract_window(app: &mut FileExplorerApp, ctx: &Context) {
+    if !app.extract_dialog.show {
+        return;
+    }
+
+    let mut open = true;
+    let mut extract = false;
+    let dialog = &mut app.extract_dialog;
+    let protected = protection::check(&app.settings.protected_paths, std::slice::from_ref(&dialog.folder));
+    egui::Window::new("📦 Extract")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!("{} into {}", file_name(&dialog.archive), dialog.folder.display()));
+            ui.label(RichText::new(format!(
+                "A single top-level folder is extracted as it is; otherwise a folder named \"{}\" is created.",
+                archive::archive_stem(&dialog.archive),
+            )).weak());
+            ui.horizontal(|ui| {
+                ui.label("When a file exists:");
+                egui::ComboBox::from_id_source("extract_policy")
+                    .selected_text(policy_label(dialog.policy))
+                    .show_ui(ui, |ui| {
+                        for policy in [ConflictPolicy::Rename, ConflictPolicy::Skip, ConflictPolicy::Overwrite, ConflictPolicy::Fail] {
+                            ui.selectable_value(&mut dialog.policy, policy, policy_label(policy));
+                        }
+                    });
+            });
+            let allowed = match &protected {
+                Some((Protection::Block, folder)) => {
+                    ui.colored_label(egui::Color32::RED, format!("{} is protected", folder.display()));
+                    false
+                }
+                Some((Protection::Confirm, folder)) => {
+                    let expected = protection::confirmation_text(folder);
+                    ui.colored_label(egui::Color32::YELLOW, format!("{} is protected; extracting adds files to it", folder.display()));
+                    ui.label(RichText::new(format!("Type \"{}\" to go ahead:", expected)).strong());
+                    ui.text_edit_singleline(&mut dialog.typed);
+                    dialog.typed == expected
+                }
+                None => true,
+            };
+            ui.horizontal(|ui| {
+                if ui.add_enabled(allowed, egui::Button::new("Extract")).clicked() {
+                    extract = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    dialog.show = false;
+                }
+            });
+        });
+    if !open {
+        app.extract_dialog.show = false;
+    }
+
+    if extract {
+        app.extract_dialog.show = false;
+        // Safe mode may have been turned on while the dialog was open
+        if app.settings.safe_mode {
+            app.notifications.warn("Safe mode is on, so files can't be changed");
+            return;
+        }
+        let dialog = &mut app.extract_dialog;
+        let (archive, folder, policy) = (dialog.archive.clone(), dialog.folder.clone(), dialog.policy);
+        let title = format!("Extracting {}", file_name(&archive));
+
+        app.jobs.spawn(ctx, title, move |job| {
+            let result = archive::extract_archive(&archive, &folder, policy, &mut |progress| job.report(progress), &job.cancel);
+            let change = Change {
+                operation: Operation::Extract,
+                source: archive.clone(),
+                destination: Some(result.as_ref().map(|s| s.destination.clone()).unwrap_or(folder)),
+                error: result.as_ref().err().cloned(),
+            };
+            JobOutcome { changes: vec![change], result: result.map(|summary| extract_message(&summary)) }
+        });
+    }
+}
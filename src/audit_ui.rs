@@ -0,0 +1,121 @@
+use eframe::egui::{self, Color32, Context, RichText, ScrollArea};
+use chrono::NaiveDate;
+use fileexp_core::audit::{AuditLog, AuditRecord};
+
+// Rows drawn at most, newest first, so a long log stays responsive
+const MAX_SHOWN_RECORDS: usize = 1000;
+
+/// Window listing the audit log, filtered by path and date range.
+pub struct AuditViewer {
+    pub show: bool,
+    path_filter: String,
+    from_date: String,
+    to_date: String,
+    records: Vec<AuditRecord>,
+}
+
+impl AuditViewer {
+    pub fn new() -> Self {
+        Self {
+            show: false,
+            path_filter: String::new(),
+            from_date: String::new(),
+            to_date: String::new(),
+            records: Vec::new(),
+        }
+    }
+
+    /// Opens the window with the log as it is on disk now.
+    pub fn open(&mut self, log: &AuditLog) {
+        self.records = log.read_all();
+        self.show = true;
+    }
+
+    pub fn show_window(&mut self, ctx: &Context, log: &AuditLog) {
+        if !self.show {
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new("📜 Audit log")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(800.0)
+            .default_height(450.0)
+            .show(ctx, |ui| {
+                let from = parse_date(&self.from_date);
+                let to = parse_date(&self.to_date);
+
+                ui.horizontal(|ui| {
+                    ui.label("Path:");
+                    ui.add(egui::TextEdit::singleline(&mut self.path_filter).hint_text("part of a path or command").desired_width(220.0));
+                    ui.label("From:");
+                    date_field(ui, &mut self.from_date, from.is_err());
+                    ui.label("To:");
+                    date_field(ui, &mut self.to_date, to.is_err());
+                    if ui.button("🔄 Reload").clicked() {
+                        self.records = log.read_all();
+                    }
+                });
+                ui.separator();
+
+                let (from, to) = (from.unwrap_or(None), to.unwrap_or(None));
+                let matching: Vec<&AuditRecord> = self.records.iter()
+                    .rev()
+                    .filter(|r| self.path_filter.is_empty() || r.mentions(&self.path_filter))
+                    .filter(|r| from.is_none_or(|from| r.timestamp.date_naive() >= from))
+                    .filter(|r| to.is_none_or(|to| r.timestamp.date_naive() <= to))
+                    .take(MAX_SHOWN_RECORDS)
+                    .collect();
+
+                if matching.is_empty() {
+                    ui.label(RichText::new("No matching records").weak());
+                    return;
+                }
+
+                ScrollArea::both().show(ui, |ui| {
+                    egui::Grid::new("audit_grid").striped(true).num_columns(6).show(ui, |ui| {
+                        for header in ["Time", "User", "Operation", "Source", "Destination", "Result"] {
+                            ui.label(RichText::new(header).strong());
+                        }
+                        ui.end_row();
+
+                        for record in matching {
+                            ui.label(record.timestamp.format("%Y-%m-%d %H:%M:%S").to_string());
+                            ui.label(&record.user);
+                            ui.label(record.operation.verb());
+                            match &record.command {
+                                Some(command) => ui.label(RichText::new(command).monospace())
+                                    .on_hover_text(format!("in {}", record.source.display())),
+                                None => ui.label(record.source.display().to_string()),
+                            };
+                            ui.label(record.destination.as_ref().map(|d| d.display().to_string()).unwrap_or_default());
+                            match &record.error {
+                                Some(error) => ui.colored_label(Color32::RED, error),
+                                None if record.ok => ui.colored_label(Color32::from_rgb(0, 150, 0), "ok"),
+                                None => ui.colored_label(Color32::RED, "failed"),
+                            };
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.show = open;
+    }
+}
+
+/// Empty means no limit.
+fn parse_date(text: &str) -> Result<Option<NaiveDate>, chrono::ParseError> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").map(Some)
+}
+
+fn date_field(ui: &mut egui::Ui, text: &mut String, invalid: bool) {
+    let mut edit = egui::TextEdit::singleline(text).hint_text("YYYY-MM-DD").desired_width(90.0);
+    if invalid {
+        edit = edit.text_color(Color32::RED);
+    }
+    ui.add(edit);
+}
@@ -0,0 +1,156 @@
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use crate::ipc::IpcCommand;
+use crate::models::ConflictPolicy;
+
+/// Command-line options of the GUI. A subcommand runs a file operation
+/// without opening a window instead.
+#[derive(Parser, Debug, Default)]
+#[command(name = "fileexp", version, about = "Rust File Explorer Pro", args_conflicts_with_subcommands = true)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Directory to open. A file opens its folder with the file selected.
+    pub path: Option<PathBuf>,
+
+    /// Reveal FILE: open its folder and select it
+    #[arg(long, value_name = "FILE")]
+    pub select: Option<PathBuf>,
+
+    /// Run CMD in the terminal panel once the window is open
+    #[arg(long, value_name = "CMD")]
+    pub terminal_cmd: Option<String>,
+
+    /// Read and write settings in DIR instead of the user config directory
+    #[arg(long, value_name = "DIR")]
+    pub config: Option<PathBuf>,
+
+    /// Open a new window even if the explorer is already running
+    #[arg(long)]
+    pub new_instance: bool,
+
+    /// Act as a file chooser: print the chosen absolute paths to stdout and
+    /// exit with status 0, or exit with status 1 when cancelled
+    #[arg(long)]
+    pub pick: bool,
+
+    /// Allow choosing more than one item
+    #[arg(long, requires = "pick")]
+    pub multiple: bool,
+
+    /// Choose folders instead of files
+    #[arg(long, requires = "pick", conflicts_with = "save")]
+    pub directory: bool,
+
+    /// Ask for a file name to save under, suggesting NAME
+    #[arg(long, value_name = "NAME", requires = "pick")]
+    pub save: Option<String>,
+
+    /// Only show files matching PATTERN, e.g. '*.png' (repeatable)
+    #[arg(long, value_name = "PATTERN", requires = "pick")]
+    pub filter: Vec<String>,
+}
+
+/// Headless file operations. Exit status is 0 when everything succeeded,
+/// 1 when every item failed and 3 when only some did.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Copy files and folders: SOURCE... DEST
+    Cp(TransferArgs),
+    /// Move files and folders: SOURCE... DEST
+    Mv(TransferArgs),
+    /// Move files and folders to the trash
+    Trash {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Print one JSON object per line instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Put trashed items back, by trash name or original path
+    Restore {
+        #[arg(required_unless_present = "list")]
+        items: Vec<String>,
+        /// List the trash instead of restoring
+        #[arg(long)]
+        list: bool,
+        /// What to do when the original location is taken: skip, overwrite, rename or fail
+        #[arg(long, default_value = "fail")]
+        conflict: ConflictPolicy,
+        /// Print one JSON object per line instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// List a folder
+    Ls {
+        path: Option<PathBuf>,
+        /// Include hidden files
+        #[arg(short, long)]
+        all: bool,
+        /// Print a JSON array instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Rename files from a template with {name}, {ext}, {n}, {n:WIDTH} and {date:FORMAT}
+    Rename {
+        #[arg(long)]
+        pattern: String,
+        /// First value of {n}
+        #[arg(long, default_value_t = 1)]
+        start: usize,
+        /// Only print what would be renamed
+        #[arg(long)]
+        dry_run: bool,
+        /// Print one JSON object per line instead of text
+        #[arg(long)]
+        json: bool,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TransferArgs {
+    /// Sources followed by the destination
+    #[arg(required = true, num_args = 2..)]
+    pub paths: Vec<PathBuf>,
+    /// What to do when a destination exists: skip, overwrite, rename or fail
+    #[arg(long, default_value = "fail")]
+    pub conflict: ConflictPolicy,
+    /// Print one JSON object per line instead of text
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl Args {
+    /// Folder to open and the file to select in it, if any. `--select` wins
+    /// over the positional path, and a path that doesn't exist is an error.
+    pub fn start_location(&self) -> Result<Option<(PathBuf, Option<PathBuf>)>, String> {
+        let Some(target) = self.select.as_ref().or(self.path.as_ref()) else {
+            return Ok(None);
+        };
+        let target = target.canonicalize().map_err(|e| format!("Cannot open {}: {}", target.display(), e))?;
+        if target.is_dir() && self.select.is_none() {
+            return Ok(Some((target, None)));
+        }
+        match target.parent() {
+            Some(parent) => Ok(Some((parent.to_path_buf(), Some(target)))),
+            None => Ok(Some((target, None))),
+        }
+    }
+
+    /// Whether to hand this launch over to an already running instance.
+    pub fn should_forward(&self) -> bool {
+        !self.pick && !self.new_instance && self.config.is_none()
+    }
+
+    /// What a running instance has to do to show this launch's location.
+    pub fn forwarded_commands(&self) -> Vec<IpcCommand> {
+        match self.start_location() {
+            Ok(Some((_, Some(file)))) => vec![IpcCommand::Select { paths: vec![file] }],
+            Ok(Some((dir, None))) => vec![IpcCommand::Navigate { path: dir }],
+            Ok(None) | Err(_) => vec![IpcCommand::Raise],
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use eframe::egui::{self, EventFilter, Id, RichText, ScrollArea, Ui};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use fileexp_core::{archive, Command};
+use crate::app::FileExplorerApp;
+use crate::models::FileEntry;
+use crate::operations;
+use crate::utils::{self, format_file_size, get_file_icon};
+
+const COLUMN_WIDTH: f32 = 220.0;
+
+/// Miller columns state. Column 0 lists `current_path`; every further column
+/// lists the directory selected in the column before it.
+pub struct ColumnsState {
+    root: PathBuf,
+    pub selection: Vec<PathBuf>,
+    pub focus_depth: usize,
+    listings: HashMap<PathBuf, Vec<FileEntry>>,
+    scroll_pending: bool,
+}
+
+enum ColumnsAction {
+    Select { depth: usize, path: PathBuf },
+    Open(PathBuf),
+}
+
+impl ColumnsState {
+    pub fn new() -> Self {
+        Self {
+            root: PathBuf::new(),
+            selection: Vec::new(),
+            focus_depth: 0,
+            listings: HashMap::new(),
+            scroll_pending: false,
+        }
+    }
+
+    fn reset(&mut self, root: &Path) {
+        self.root = root.to_path_buf();
+        self.selection.clear();
+        self.focus_depth = 0;
+        self.listings.clear();
+    }
+
+    fn select(&mut self, depth: usize, path: PathBuf) {
+        self.selection.truncate(depth);
+        self.selection.push(path);
+        self.focus_depth = depth;
+        self.scroll_pending = true;
+    }
+}
+
+fn focus_id() -> Id {
+    Id::new("miller_columns")
+}
+
+/// A folder on disk or inside an archive. Archives themselves count as files
+/// here, so selecting one doesn't read it.
+fn is_folder(path: &Path) -> bool {
+    path.is_dir() || archive::is_member(path) && archive::is_folder(path)
+}
+
+/// Entries shown in the column at `depth`, if that column exists.
+fn column_entries(app: &mut FileExplorerApp, depth: usize) -> Option<Vec<FileEntry>> {
+    if depth == 0 {
+        return Some(app.explorer.entries.clone());
+    }
+
+    let parent = app.columns.selection.get(depth - 1)?.clone();
+    if !is_folder(&parent) {
+        return None;
+    }
+
+    let show_hidden = app.settings.show_hidden_files;
+    let (sort_by, ascending) = (app.explorer.sort_by, app.explorer.sort_ascending);
+    let entries = app.columns.listings.entry(parent.clone()).or_insert_with(|| {
+        let mut entries = operations::read_directory(&parent, show_hidden).unwrap_or_default();
+        utils::sort_entries(&mut entries, sort_by, ascending);
+        entries
+    });
+    Some(entries.clone())
+}
+
+pub fn show_columns_view(app: &mut FileExplorerApp, ui: &mut Ui) {
+    if app.columns.root != app.explorer.current_path {
+        let root = app.explorer.current_path.clone();
+        app.columns.reset(&root);
+    }
+
+    // Keep column 0 in step with the regular selection so copy/cut/delete work
+    let first_selected = app.explorer.first_selected().map(|(_, entry)| entry.path.clone());
+    if app.columns.selection.first() != first_selected.as_ref() {
+        match first_selected {
+            Some(path) => app.columns.select(0, path),
+            None => app.columns.reset(&app.explorer.current_path.clone()),
+        }
+    }
+
+    let mut columns = Vec::new();
+    while let Some(entries) = column_entries(app, columns.len()) {
+        columns.push(entries);
+    }
+
+    let mut actions = Vec::new();
+    let has_focus = ui.memory(|m| m.has_focus(focus_id()));
+    if has_focus {
+        ui.memory_mut(|m| m.set_focus_lock_filter(focus_id(), EventFilter {
+            tab: false,
+            horizontal_arrows: true,
+            vertical_arrows: true,
+            escape: false,
+        }));
+        handle_columns_keys(app, ui, &columns, &mut actions);
+    }
+
+    let response = ui.interact(ui.max_rect(), focus_id(), egui::Sense::click());
+    if response.clicked() {
+        response.request_focus();
+    }
+
+    ScrollArea::horizontal().id_source("columns_scroll").show(ui, |ui| {
+        ui.horizontal_top(|ui| {
+            for (depth, entries) in columns.iter().enumerate() {
+                show_column(app, ui, depth, entries, &mut actions);
+                ui.separator();
+            }
+
+            // A selected file gets an info column at the end
+            if let Some(last) = app.columns.selection.last()
+                && let Some(entry) = columns.last().and_then(|c| c.iter().find(|e| &e.path == last))
+                && !entry.is_dir
+            {
+                show_info_column(ui, entry);
+            }
+        });
+    });
+    app.columns.scroll_pending = false;
+
+    for action in actions {
+        match action {
+            ColumnsAction::Select { depth, path } => {
+                if depth == 0 {
+                    app.select_paths(std::slice::from_ref(&path));
+                }
+                app.columns.select(depth, path);
+                ui.memory_mut(|m| m.request_focus(focus_id()));
+            }
+            ColumnsAction::Open(path) => {
+                if is_folder(&path) {
+                    app.navigate_to(path);
+                } else {
+                    app.open_file(&path);
+                }
+            }
+        }
+    }
+}
+
+fn show_column(app: &mut FileExplorerApp, ui: &mut Ui, depth: usize, entries: &[FileEntry], actions: &mut Vec<ColumnsAction>) {
+    ui.vertical(|ui| {
+        ui.set_width(COLUMN_WIDTH);
+        ScrollArea::vertical().id_source(("column", depth)).show(ui, |ui| {
+            ui.set_width(COLUMN_WIDTH);
+            if entries.is_empty() {
+                ui.label(RichText::new("Empty folder").weak());
+            }
+
+            for (i, entry) in entries.iter().enumerate() {
+                let selected = app.columns.selection.get(depth) == Some(&entry.path);
+                let arrow = if entry.is_dir { "  ⏵" } else { "" };
+                let label = format!("{} {}{}", get_file_icon(entry), entry.name, arrow);
+                let response = ui.selectable_label(selected, label);
+
+                if selected && app.columns.scroll_pending && depth == app.columns.focus_depth {
+                    response.scroll_to_me(None);
+                }
+
+                if response.clicked() {
+                    actions.push(ColumnsAction::Select { depth, path: entry.path.clone() });
+                }
+                if response.double_clicked() {
+                    actions.push(ColumnsAction::Open(entry.path.clone()));
+                }
+                if response.secondary_clicked()
+                    && depth == 0
+                    && let Some(pos) = response.interact_pointer_pos()
+                {
+                    if !app.explorer.selection.contains(i) {
+                        app.execute(Command::SelectOnly(i));
+                    }
+                    app.context_menu.show_at(pos, Some(i));
+                }
+            }
+        });
+    });
+}
+
+fn show_info_column(ui: &mut Ui, entry: &FileEntry) {
+    ui.vertical(|ui| {
+        ui.set_width(COLUMN_WIDTH);
+        ui.label(RichText::new(get_file_icon(entry)).size(48.0));
+        ui.label(RichText::new(&entry.name).strong());
+        ui.separator();
+        ui.label(format!("Size: {}", format_file_size(entry.size)));
+        ui.label(format!("Modified: {}", entry.modified.format("%Y-%m-%d %H:%M")));
+        if !entry.extension.is_empty() {
+            ui.label(format!("Type: {} file", entry.extension.to_uppercase()));
+        }
+        ui.label(RichText::new(entry.path.display().to_string()).small().weak());
+    });
+}
+
+fn handle_columns_keys(app: &FileExplorerApp, ui: &Ui, columns: &[Vec<FileEntry>], actions: &mut Vec<ColumnsAction>) {
+    let state = &app.columns;
+    let depth = state.focus_depth.min(columns.len().saturating_sub(1));
+    let Some(column) = columns.get(depth) else { return };
+    let current = state.selection.get(depth).and_then(|p| column.iter().position(|e| &e.path == p));
+
+    ui.input(|i| {
+        if i.key_pressed(egui::Key::ArrowDown) && !column.is_empty() {
+            let next = current.map_or(0, |c| (c + 1).min(column.len() - 1));
+            actions.push(ColumnsAction::Select { depth, path: column[next].path.clone() });
+        }
+        if i.key_pressed(egui::Key::ArrowUp) && !column.is_empty() {
+            let prev = current.map_or(0, |c| c.saturating_sub(1));
+            actions.push(ColumnsAction::Select { depth, path: column[prev].path.clone() });
+        }
+        if i.key_pressed(egui::Key::ArrowRight)
+            && let Some(first) = columns.get(depth + 1).and_then(|c| c.first())
+        {
+            actions.push(ColumnsAction::Select { depth: depth + 1, path: first.path.clone() });
+        }
+        if i.key_pressed(egui::Key::ArrowLeft) && depth > 0 {
+            actions.push(ColumnsAction::Select { depth: depth - 1, path: state.selection[depth - 1].clone() });
+        }
+        if i.key_pressed(egui::Key::Enter)
+            && let Some(path) = state.selection.get(depth)
+        {
+            actions.push(ColumnsAction::Open(path.clone()));
+        }
+    });
+}
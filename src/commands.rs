@@ -0,0 +1,296 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use crate::cli::{Command, TransferArgs};
+use crate::models::ConflictPolicy;
+use crate::settings::AppSettings;
+use fileexp_core::audit::{AuditLog, AuditRecord};
+use fileexp_core::{FileError, Operation};
+use crate::operations;
+use crate::rename;
+use crate::trash;
+use crate::utils::format_file_size;
+
+const EXIT_FAILED: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_PARTIAL: u8 = 3;
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Done,
+    Planned,
+    Skipped,
+    Failed,
+}
+
+/// One line of `--json` output.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum Event<'a> {
+    Item {
+        op: &'a str,
+        index: usize,
+        total: usize,
+        source: &'a Path,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        destination: Option<&'a Path>,
+        status: Status,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<&'a str>,
+    },
+    Summary {
+        op: &'a str,
+        succeeded: usize,
+        skipped: usize,
+        failed: usize,
+    },
+}
+
+/// Prints per-item progress, records it in the audit log and works out the
+/// exit status.
+struct Reporter {
+    op: &'static str,
+    operation: Operation,
+    audit: AuditLog,
+    json: bool,
+    total: usize,
+    index: usize,
+    succeeded: usize,
+    skipped: usize,
+    failed: usize,
+}
+
+impl Reporter {
+    fn new(operation: Operation, json: bool, total: usize) -> Self {
+        let audit = AuditLog::new(AppSettings::config_dir());
+        Self { op: operation.verb(), operation, audit, json, total, index: 0, succeeded: 0, skipped: 0, failed: 0 }
+    }
+
+    fn item(&mut self, source: &Path, destination: Option<&Path>, status: Status, error: Option<&str>) {
+        self.index += 1;
+        match status {
+            Status::Done | Status::Planned => self.succeeded += 1,
+            Status::Skipped => self.skipped += 1,
+            Status::Failed => self.failed += 1,
+        }
+
+        let result = match (&status, error) {
+            (Status::Done, _) => Some(Ok(())),
+            (Status::Failed, error) => Some(Err(error.unwrap_or("failed").to_string())),
+            _ => None,
+        };
+        if let Some(result) = result
+            && let Err(e) = self.audit.record(&AuditRecord::new(self.operation, source, destination, result))
+        {
+            eprintln!("fileexp: {}", e);
+        }
+
+        if self.json {
+            let event = Event::Item { op: self.op, index: self.index, total: self.total, source, destination, status, error };
+            if let Ok(line) = serde_json::to_string(&event) {
+                println!("{}", line);
+            }
+            return;
+        }
+
+        match (status, destination, error) {
+            (Status::Failed, _, error) => eprintln!("{}: {}: {}", self.op, source.display(), error.unwrap_or("failed")),
+            (Status::Skipped, _, _) => println!("skipped {}", source.display()),
+            (Status::Planned, Some(destination), _) => println!("would {} {} -> {}", self.op, source.display(), destination.display()),
+            (_, Some(destination), _) => println!("{} {} -> {}", self.op, source.display(), destination.display()),
+            (_, None, _) => println!("{} {}", self.op, source.display()),
+        }
+    }
+
+    fn result<T>(&mut self, source: &Path, result: Result<Option<T>, FileError>, destination: impl Fn(&T) -> &Path) {
+        match result {
+            Ok(Some(value)) => self.item(source, Some(destination(&value)), Status::Done, None),
+            Ok(None) => self.item(source, None, Status::Skipped, None),
+            Err(e) => self.item(source, None, Status::Failed, Some(&e.reason())),
+        }
+    }
+
+    fn finish(self) -> ExitCode {
+        if self.json {
+            let event = Event::Summary { op: self.op, succeeded: self.succeeded, skipped: self.skipped, failed: self.failed };
+            if let Ok(line) = serde_json::to_string(&event) {
+                println!("{}", line);
+            }
+        }
+        exit_code(self.succeeded + self.skipped, self.failed)
+    }
+}
+
+fn exit_code(succeeded: usize, failed: usize) -> ExitCode {
+    match (succeeded, failed) {
+        (_, 0) => ExitCode::SUCCESS,
+        (0, _) => ExitCode::from(EXIT_FAILED),
+        _ => ExitCode::from(EXIT_PARTIAL),
+    }
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("fileexp: {}", message);
+    ExitCode::from(EXIT_USAGE)
+}
+
+/// Runs a subcommand without opening a window.
+pub fn run(command: Command) -> ExitCode {
+    match command {
+        Command::Cp(args) => transfer(args, false),
+        Command::Mv(args) => transfer(args, true),
+        Command::Trash { paths, json } => trash_paths(&paths, json),
+        Command::Restore { list: true, json, .. } => list_trash(json),
+        Command::Restore { items, conflict, json, .. } => restore(&items, conflict, json),
+        Command::Ls { path, all, json } => list(path, all, json),
+        Command::Rename { pattern, start, dry_run, json, files } => rename_files(&files, &pattern, start, dry_run, json),
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+}
+
+fn transfer(args: TransferArgs, move_items: bool) -> ExitCode {
+    let Some((destination, sources)) = args.paths.split_last() else {
+        return usage_error("missing destination");
+    };
+    let into_dir = destination.is_dir();
+    if !into_dir && sources.len() > 1 {
+        return usage_error(&format!("target '{}' is not a directory", destination.display()));
+    }
+
+    let operation = if move_items { Operation::Move } else { Operation::Copy };
+    let mut reporter = Reporter::new(operation, args.json, sources.len());
+    for source in sources {
+        let target = match source.file_name() {
+            Some(name) if into_dir => destination.join(name),
+            Some(_) => destination.clone(),
+            None => {
+                reporter.item(source, None, Status::Failed, Some("not a file or folder name"));
+                continue;
+            }
+        };
+
+        // Overwriting an item with itself would delete it
+        let result = if let Err(e) = std::fs::symlink_metadata(source) {
+            Err(FileError::io(operation, source, &e))
+        } else if same_file(source, &target) {
+            Err(FileError::invalid(operation, source, format!("'{}' is the same file", target.display())))
+        } else {
+            operations::resolve_conflict(&target, args.conflict, operation).and_then(|target| match target {
+                Some(target) => {
+                    let done = if move_items {
+                        operations::move_item(source, &target, args.conflict)
+                    } else {
+                        operations::copy_item(source, &target, args.conflict)
+                    };
+                    done.map(|_| Some(target))
+                }
+                None => Ok(None),
+            })
+        };
+        reporter.result(source, result, PathBuf::as_path);
+    }
+    reporter.finish()
+}
+
+fn trash_paths(paths: &[PathBuf], json: bool) -> ExitCode {
+    let mut reporter = Reporter::new(Operation::Trash, json, paths.len());
+    for path in paths {
+        let result = trash::trash_item(path).map(|name| Some(trash::trash_dir().join("files").join(name)));
+        reporter.result(path, result, PathBuf::as_path);
+    }
+    reporter.finish()
+}
+
+fn list_trash(json: bool) -> ExitCode {
+    let entries = trash::list_trash();
+    if json {
+        match serde_json::to_string(&entries) {
+            Ok(line) => println!("{}", line),
+            Err(e) => return usage_error(&e.to_string()),
+        }
+    } else {
+        for entry in entries {
+            println!("{}\t{}\t{}", entry.deletion_date, entry.name, entry.original_path.display());
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn restore(items: &[String], policy: ConflictPolicy, json: bool) -> ExitCode {
+    let mut reporter = Reporter::new(Operation::Restore, json, items.len());
+    for item in items {
+        let result = match trash::find_entry(item) {
+            Some(entry) => trash::restore_item(&entry, policy),
+            None => Err(FileError::invalid(Operation::Restore, Path::new(item), "not in the trash")),
+        };
+        reporter.result(Path::new(item), result, PathBuf::as_path);
+    }
+    reporter.finish()
+}
+
+fn list(path: Option<PathBuf>, all: bool, json: bool) -> ExitCode {
+    let path = path.unwrap_or_else(|| PathBuf::from("."));
+    let entries = match operations::read_directory(&path, all) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("fileexp: {}", e);
+            return ExitCode::from(EXIT_FAILED);
+        }
+    };
+
+    if json {
+        match serde_json::to_string(&entries) {
+            Ok(line) => println!("{}", line),
+            Err(e) => return usage_error(&e.to_string()),
+        }
+    } else {
+        for entry in entries {
+            let size = if entry.is_dir { "-".to_string() } else { format_file_size(entry.size) };
+            let suffix = if entry.is_dir { "/" } else { "" };
+            println!("{:>10}  {}  {}{}", size, entry.modified.format("%Y-%m-%d %H:%M"), entry.name, suffix);
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn rename_files(files: &[PathBuf], pattern: &str, start: usize, dry_run: bool, json: bool) -> ExitCode {
+    let mut renames = Vec::new();
+    for (i, file) in files.iter().enumerate() {
+        match rename::render_pattern(pattern, file, start + i) {
+            Ok(name) => renames.push((file.clone(), file.with_file_name(name))),
+            Err(e) => return usage_error(&e),
+        }
+    }
+    let renames = match rename::plan_renames(renames) {
+        Ok(renames) => renames,
+        Err(e) => {
+            eprintln!("fileexp: {}", e);
+            return ExitCode::from(EXIT_FAILED);
+        }
+    };
+
+    let mut reporter = Reporter::new(Operation::Rename, json, renames.len());
+    if dry_run {
+        for (from, to) in &renames {
+            reporter.item(from, Some(to), Status::Planned, None);
+        }
+        return reporter.finish();
+    }
+
+    let applied = rename::apply_renames(&renames, |from, to, result| match result {
+        Ok(()) => reporter.item(from, Some(to), Status::Done, None),
+        Err(e) => reporter.item(from, Some(to), Status::Failed, Some(&e.reason())),
+    });
+    // Nothing was renamed if setting up the temporary names failed
+    if let Err(e) = applied
+        && reporter.index == 0
+    {
+        eprintln!("fileexp: {}", e);
+        return ExitCode::from(EXIT_FAILED);
+    }
+    reporter.finish()
+}
@@ -1,4 +1,5 @@
 use eframe::egui::{self, Context, Pos2, RichText};
+use fileexp_core::archive::ArchiveKind;
 use crate::models::FileEntry;
 
 #[derive(Clone, Debug)]
@@ -10,6 +11,8 @@ pub enum ContextMenuAction {
     Paste,
     Delete,
     Rename,
+    /// Edit the names of the selection, or of the whole folder, in the editor.
+    RenameInEditor,
     Properties,
     CreateNew(NewItemType),
     Compress,
@@ -23,6 +26,24 @@ pub enum ContextMenuAction {
     SendTo,
 }
 
+impl ContextMenuAction {
+    /// Whether the action creates, moves, changes or removes files.
+    pub fn changes_files(&self) -> bool {
+        matches!(
+            self,
+            ContextMenuAction::Cut
+                | ContextMenuAction::Paste
+                | ContextMenuAction::Delete
+                | ContextMenuAction::Rename
+                | ContextMenuAction::RenameInEditor
+                | ContextMenuAction::CreateNew(_)
+                | ContextMenuAction::Compress
+                | ContextMenuAction::Extract
+                | ContextMenuAction::SendTo
+        )
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum NewItemType {
     File,
@@ -39,7 +60,6 @@ pub struct ContextMenuState {
     pub show_new_submenu: bool,
     pub show_open_with_submenu: bool,
     pub show_send_to_submenu: bool,
-    pub selected_action: Option<ContextMenuAction>,
 }
 
 impl ContextMenuState {
@@ -50,7 +70,6 @@ impl ContextMenuState {
             show_new_submenu: false,
             show_open_with_submenu: false,
             show_send_to_submenu: false,
-            selected_action: None,
         }
     }
 
@@ -81,6 +100,8 @@ pub fn show_context_menu(
     entries: &[FileEntry],
     selected_entries: &[usize],
     clipboard_has_content: bool,
+    // Safe mode: actions that change files are shown disabled
+    read_only: bool,
 ) -> Option<ContextMenuAction> {
     if let Some(pos) = state.position {
         let mut action = None;
@@ -96,7 +117,7 @@ pub fn show_context_menu(
                     let has_selection = !selected_entries.is_empty();
                     let single_selection = selected_entries.len() == 1;
                     let is_directory = single_selection && 
-                        state.target_index.map_or(false, |i| entries.get(i).map_or(false, |e| e.is_dir));
+                        state.target_index.is_some_and(|i| entries.get(i).is_some_and(|e| e.is_dir));
                     
                     if has_selection {
                         // Actions for selected items
@@ -104,15 +125,13 @@ pub fn show_context_menu(
                             action = Some(ContextMenuAction::Open);
                         }
                         
-                        if single_selection {
-                            if ui.button("📂 Open with...").clicked() {
-                                state.show_open_with_submenu = true;
-                            }
+                        if single_selection && ui.button("📂 Open with...").clicked() {
+                            state.show_open_with_submenu = true;
                         }
                         
                         ui.separator();
                         
-                        if ui.button("✂️ Cut").clicked() {
+                        if ui.add_enabled(!read_only, egui::Button::new("✂️ Cut")).clicked() {
                             action = Some(ContextMenuAction::Cut);
                         }
                         
@@ -120,42 +139,42 @@ pub fn show_context_menu(
                             action = Some(ContextMenuAction::Copy);
                         }
                         
-                        if single_selection {
-                            if ui.button("📄 Copy path").clicked() {
-                                action = Some(ContextMenuAction::CopyPath);
-                            }
+                        if single_selection && ui.button("📄 Copy path").clicked() {
+                            action = Some(ContextMenuAction::CopyPath);
                         }
                         
                         ui.separator();
                         
-                        if ui.button("🗑️ Delete").clicked() {
+                        if ui.add_enabled(!read_only, egui::Button::new("🗑️ Delete")).clicked() {
                             action = Some(ContextMenuAction::Delete);
                         }
                         
-                        if single_selection {
-                            if ui.button("✏️ Rename").clicked() {
-                                action = Some(ContextMenuAction::Rename);
-                            }
+                        let rename_label = if single_selection { "✏️ Rename" } else { "✏️ Batch rename" };
+                        if ui.add_enabled(!read_only, egui::Button::new(rename_label)).clicked() {
+                            action = Some(ContextMenuAction::Rename);
+                        }
+                        
+                        if ui.add_enabled(!read_only, egui::Button::new("📝 Rename in editor")).clicked() {
+                            action = Some(ContextMenuAction::RenameInEditor);
                         }
                         
                         ui.separator();
                         
                         // Compression options
-                        if ui.button("🗜️ Compress").clicked() {
+                        if ui.add_enabled(!read_only, egui::Button::new("🗜️ Compress")).clicked() {
                             action = Some(ContextMenuAction::Compress);
                         }
                         
                         // Extract if it's an archive
-                        if single_selection {
-                            if let Some(index) = state.target_index {
-                                if let Some(entry) = entries.get(index) {
-                                    let ext = entry.extension.to_lowercase();
-                                    if matches!(ext.as_str(), "zip" | "tar" | "gz" | "bz2" | "xz" | "7z" | "rar") {
-                                        if ui.button("📦 Extract").clicked() {
-                                            action = Some(ContextMenuAction::Extract);
-                                        }
-                                    }
-                                }
+                        if single_selection
+                            && let Some(index) = state.target_index
+                            && let Some(entry) = entries.get(index)
+                        {
+                            // Only the formats the extractor reads
+                            if !entry.is_dir && ArchiveKind::detect(&entry.path).is_some()
+                                && ui.add_enabled(!read_only, egui::Button::new("📦 Extract")).clicked()
+                            {
+                                action = Some(ContextMenuAction::Extract);
                             }
                         }
                         
@@ -173,32 +192,32 @@ pub fn show_context_menu(
                         }
                         
                         // File-specific actions
-                        if single_selection && !is_directory {
-                            if let Some(index) = state.target_index {
-                                if let Some(entry) = entries.get(index) {
-                                    let ext = entry.extension.to_lowercase();
-                                    
-                                    // Text files
-                                    if matches!(ext.as_str(), "txt" | "md" | "rs" | "py" | "js" | "html" | "css" | "json" | "xml" | "yaml" | "toml") {
-                                        if ui.button("📝 Open in editor").clicked() {
-                                            action = Some(ContextMenuAction::OpenInEditor);
-                                        }
-                                    }
-                                    
-                                    // Image files
-                                    if matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "gif" | "bmp" | "webp" | "svg") {
-                                        if ui.button("🖼️ Set as wallpaper").clicked() {
-                                            action = Some(ContextMenuAction::SetAsWallpaper);
-                                        }
-                                    }
-                                }
+                        if single_selection
+                            && !is_directory
+                            && let Some(index) = state.target_index
+                            && let Some(entry) = entries.get(index)
+                        {
+                            let ext = entry.extension.to_lowercase();
+                            
+                            // Text files
+                            if matches!(ext.as_str(), "txt" | "md" | "rs" | "py" | "js" | "html" | "css" | "json" | "xml" | "yaml" | "toml")
+                                && ui.button("📝 Open in editor").clicked()
+                            {
+                                action = Some(ContextMenuAction::OpenInEditor);
+                            }
+                            
+                            // Image files
+                            if matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "gif" | "bmp" | "webp" | "svg")
+                                && ui.button("🖼️ Set as wallpaper").clicked()
+                            {
+                                action = Some(ContextMenuAction::SetAsWallpaper);
                             }
                         }
                         
                         ui.separator();
                         
                         // Send to submenu
-                        if ui.button("📤 Send to...").clicked() {
+                        if ui.add_enabled(!read_only, egui::Button::new("📤 Send to...")).clicked() {
                             state.show_send_to_submenu = true;
                         }
                         
@@ -208,27 +227,29 @@ pub fn show_context_menu(
                         
                         ui.separator();
                         
-                        if single_selection {
-                            if ui.button("ℹ️ Properties").clicked() {
-                                action = Some(ContextMenuAction::Properties);
-                            }
+                        if single_selection && ui.button("ℹ️ Properties").clicked() {
+                            action = Some(ContextMenuAction::Properties);
                         }
                         
                     } else {
                         // Actions for empty space (no selection)
-                        if ui.button("📄 New").clicked() {
+                        if ui.add_enabled(!read_only, egui::Button::new("📄 New")).clicked() {
                             state.show_new_submenu = true;
                         }
                         
                         ui.separator();
                         
                         if clipboard_has_content {
-                            if ui.button("📁 Paste").clicked() {
+                            if ui.add_enabled(!read_only, egui::Button::new("📁 Paste")).clicked() {
                                 action = Some(ContextMenuAction::Paste);
                             }
                             ui.separator();
                         }
                         
+                        if ui.add_enabled(!read_only, egui::Button::new("📝 Rename all in editor")).clicked() {
+                            action = Some(ContextMenuAction::RenameInEditor);
+                        }
+                        
                         if ui.button("⚡ Open terminal here").clicked() {
                             action = Some(ContextMenuAction::OpenInTerminal);
                         }
@@ -0,0 +1,132 @@
+use eframe::egui::Context;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use zbus::blocking::{Connection, ConnectionBuilder};
+use zbus::dbus_interface;
+use crate::ipc::IpcCommand;
+use crate::utils;
+
+const BUS_NAME: &str = "org.freedesktop.FileManager1";
+const OBJECT_PATH: &str = "/org/freedesktop/FileManager1";
+
+/// The `org.freedesktop.FileManager1` interface that browsers and IDEs call
+/// for "Show in folder". Calls are queued as the same commands the control
+/// socket takes.
+struct FileManager1 {
+    commands: Sender<IpcCommand>,
+    ctx: Context,
+}
+
+impl FileManager1 {
+    fn send(&self, command: IpcCommand) {
+        let _ = self.commands.send(command);
+        self.ctx.request_repaint();
+    }
+}
+
+/// Accepts both `file://` URIs and plain absolute paths.
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    utils::path_from_uri(uri).or_else(|| Some(PathBuf::from(uri)).filter(|p| p.is_absolute()))
+}
+
+#[dbus_interface(name = "org.freedesktop.FileManager1")]
+impl FileManager1 {
+    /// Opens the first of `uris`.
+    fn show_folders(&self, uris: Vec<String>, _startup_id: String) {
+        if let Some(path) = uris.iter().find_map(|uri| uri_to_path(uri)) {
+            self.send(IpcCommand::Navigate { path });
+        }
+    }
+
+    /// Opens the folder holding the first of `uris` and selects the items.
+    fn show_items(&self, uris: Vec<String>, _startup_id: String) {
+        let paths: Vec<PathBuf> = uris.iter().filter_map(|uri| uri_to_path(uri)).collect();
+        if !paths.is_empty() {
+            self.send(IpcCommand::Select { paths });
+        }
+    }
+
+    /// There is no separate properties window to raise, so this reveals the items.
+    fn show_item_properties(&self, uris: Vec<String>, startup_id: String) {
+        self.show_items(uris, startup_id);
+    }
+}
+
+/// Connection owning the service on the session bus. The name is released
+/// when this is dropped.
+pub struct DbusService {
+    _connection: Connection,
+    pub commands: Receiver<IpcCommand>,
+}
+
+impl DbusService {
+    /// Claims the well-known name on the bus given by `DBUS_SESSION_BUS_ADDRESS`.
+    pub fn start(ctx: Context) -> Result<Self, String> {
+        Self::serve(ConnectionBuilder::session(), ctx)
+    }
+
+    fn serve(builder: zbus::Result<ConnectionBuilder<'static>>, ctx: Context) -> Result<Self, String> {
+        let (sender, commands) = mpsc::channel();
+        let connection = builder
+            .and_then(|b| b.name(BUS_NAME))
+            .and_then(|b| b.serve_at(OBJECT_PATH, FileManager1 { commands: sender, ctx }))
+            .and_then(|b| b.build())
+            .map_err(|e| format!("Failed to register {} on the session bus: {}", BUS_NAME, e))?;
+        Ok(Self { _connection: connection, commands })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::time::Duration;
+
+    /// A private session bus that is shut down with the test.
+    struct Bus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl Bus {
+        fn start() -> Self {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .expect("dbus-daemon has to be installed");
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take().unwrap()).read_line(&mut address).unwrap();
+            Self { daemon, address: address.trim().to_string() }
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    fn call(bus: &Bus, method: &str, uris: &[&str]) {
+        let client = ConnectionBuilder::address(bus.address.as_str()).and_then(|b| b.build()).unwrap();
+        let uris: Vec<String> = uris.iter().map(|uri| uri.to_string()).collect();
+        client.call_method(Some(BUS_NAME), OBJECT_PATH, Some(BUS_NAME), method, &(uris, "")).unwrap();
+    }
+
+    #[test]
+    #[ignore = "starts a dbus-daemon"]
+    fn calls_are_queued_as_commands() {
+        let bus = Bus::start();
+        let service = DbusService::serve(ConnectionBuilder::address(bus.address.as_str()), Context::default()).unwrap();
+        let next = || service.commands.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        call(&bus, "ShowFolders", &["file:///tmp/some%20folder"]);
+        assert!(matches!(next(), IpcCommand::Navigate { path } if path.as_path() == Path::new("/tmp/some folder")));
+
+        call(&bus, "ShowItems", &["file:///tmp/a.txt", "/tmp/b.txt", "relative.txt"]);
+        assert!(matches!(next(), IpcCommand::Select { paths } if paths == [PathBuf::from("/tmp/a.txt"), PathBuf::from("/tmp/b.txt")]));
+    }
+}
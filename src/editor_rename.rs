@@ -0,0 +1,86 @@
+use eframe::egui::Context;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use tempfile::NamedTempFile;
+use fileexp_core::rename::{self, ListingChanges};
+
+/// An editor open on a list of names.
+struct EditSession {
+    paths: Vec<PathBuf>,
+    // Removed when the session ends
+    file: NamedTempFile,
+    // Receives how the editor exited
+    done: Receiver<Result<(), String>>,
+}
+
+/// Renaming by editing the names in the external editor, like vidir. The
+/// editor runs in the background; `poll` picks up its result.
+pub struct EditorRename {
+    session: Option<EditSession>,
+}
+
+impl EditorRename {
+    pub fn new() -> Self {
+        Self { session: None }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.session.is_some()
+    }
+
+    /// Writes the names of `paths` to a new temporary file that only the
+    /// user can read and opens it with `editor`, which may include arguments
+    /// such as `code --wait`. The file has a random name, in the runtime
+    /// folder when there is one, so nobody can plant it beforehand.
+    pub fn start(&mut self, paths: Vec<PathBuf>, editor: &str, ctx: &Context) -> Result<(), String> {
+        let mut words = editor.split_whitespace();
+        let program = words.next().ok_or("No editor is set")?.to_string();
+        let args: Vec<String> = words.map(str::to_string).collect();
+
+        let folder = dirs::runtime_dir().unwrap_or_else(std::env::temp_dir);
+        let mut file = tempfile::Builder::new()
+            .prefix("fileexp-rename-")
+            .suffix(".txt")
+            .tempfile_in(&folder)
+            .map_err(|e| format!("Failed to create a file in {}: {}", folder.display(), e))?;
+        file.write_all(rename::edit_listing(&paths).as_bytes())
+            .map_err(|e| format!("Failed to write {}: {}", file.path().display(), e))?;
+
+        let (sender, done) = mpsc::channel();
+        let ctx = ctx.clone();
+        let edited = file.path().to_path_buf();
+        thread::spawn(move || {
+            let result = match Command::new(&program).args(&args).arg(&edited).status() {
+                Ok(status) if status.success() => Ok(()),
+                Ok(status) => Err(format!("{} exited with {}, nothing was renamed", program, status)),
+                Err(e) => Err(format!("Failed to start {}: {}", program, e)),
+            };
+            let _ = sender.send(result);
+            ctx.request_repaint();
+        });
+
+        self.session = Some(EditSession { paths, file, done });
+        Ok(())
+    }
+
+    /// The changes asked for once the editor has closed.
+    pub fn poll(&mut self) -> Option<Result<ListingChanges, String>> {
+        let session = self.session.as_ref()?;
+        let exited = match session.done.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err("The editor stopped unexpectedly".to_string()),
+        };
+        let session = self.session.take()?;
+
+        let file = session.file.path();
+        let edited = exited.and_then(|()| {
+            fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))
+        });
+        Some(edited.and_then(|text| rename::diff_listing(&session.paths, &text)))
+    }
+}
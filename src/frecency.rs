@@ -0,0 +1,228 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::Local;
+use crate::settings::AppSettings;
+
+// Once the summed rank passes this, every entry is aged so old paths fade out.
+const MAX_TOTAL_RANK: f64 = 10000.0;
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+const WEEK: i64 = 7 * DAY;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FrecencyEntry {
+    pub path: PathBuf,
+    pub rank: f64,
+    pub last_accessed: i64,
+}
+
+impl FrecencyEntry {
+    /// Visit count weighted by how recently the directory was last used.
+    pub fn score(&self, now: i64) -> f64 {
+        let age = now - self.last_accessed;
+        if age < HOUR {
+            self.rank * 4.0
+        } else if age < DAY {
+            self.rank * 2.0
+        } else if age < WEEK {
+            self.rank * 0.5
+        } else {
+            self.rank * 0.25
+        }
+    }
+
+    fn matches(&self, keywords: &[String]) -> bool {
+        let path = self.path.to_string_lossy().to_lowercase();
+
+        // Every keyword has to appear in order...
+        let mut rest = path.as_str();
+        for keyword in keywords {
+            match rest.find(keyword.as_str()) {
+                Some(pos) => rest = &rest[pos + keyword.len()..],
+                None => return false,
+            }
+        }
+
+        // ...and the last one has to be part of the final component.
+        match (keywords.last(), self.path.file_name()) {
+            (Some(last), Some(name)) => name.to_string_lossy().to_lowercase().contains(last.as_str()),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct FrecencyDb {
+    pub entries: Vec<FrecencyEntry>,
+    // Visits since the last flush; written out by `flush` rather than per navigation
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl FrecencyDb {
+    pub fn load() -> Self {
+        match fs::read_to_string(Self::get_db_path()) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let db_path = Self::get_db_path();
+        if let Some(parent) = db_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let content = serde_json::to_string(self).map_err(|e| format!("Failed to serialize directory history: {}", e))?;
+        fs::write(db_path, content).map_err(|e| format!("Failed to save directory history: {}", e))
+    }
+
+    /// Saves the database if visits were recorded since the last flush.
+    pub fn flush(&mut self) -> Result<(), String> {
+        if self.dirty {
+            self.save()?;
+            self.dirty = false;
+        }
+        Ok(())
+    }
+
+    fn get_db_path() -> PathBuf {
+        AppSettings::config_dir().join("frecency.json")
+    }
+
+    /// Records a visit to `path`; the change is persisted on the next `flush`.
+    pub fn add(&mut self, path: &Path) {
+        let now = Local::now().timestamp();
+
+        match self.entries.iter_mut().find(|e| e.path == path) {
+            Some(entry) => {
+                entry.rank += 1.0;
+                entry.last_accessed = now;
+            }
+            None => self.entries.push(FrecencyEntry {
+                path: path.to_path_buf(),
+                rank: 1.0,
+                last_accessed: now,
+            }),
+        }
+
+        self.age();
+        self.dirty = true;
+    }
+
+    fn age(&mut self) {
+        let total: f64 = self.entries.iter().map(|e| e.rank).sum();
+        if total > MAX_TOTAL_RANK {
+            let factor = 0.9 * MAX_TOTAL_RANK / total;
+            for entry in &mut self.entries {
+                entry.rank *= factor;
+            }
+            self.entries.retain(|e| e.rank >= 1.0);
+        }
+    }
+
+    /// Existing directories matching `query`, best ranked first.
+    pub fn query(&self, query: &str, exclude: &Path) -> Vec<&FrecencyEntry> {
+        let keywords: Vec<String> = query.split_whitespace().map(|k| k.to_lowercase()).collect();
+        let now = Local::now().timestamp();
+
+        let mut matches: Vec<&FrecencyEntry> = self.entries.iter()
+            .filter(|e| e.path != exclude && e.matches(&keywords) && e.path.is_dir())
+            .collect();
+        matches.sort_by(|a, b| b.score(now).total_cmp(&a.score(now)));
+        matches
+    }
+
+    pub fn best_match(&self, query: &str, exclude: &Path) -> Option<PathBuf> {
+        self.query(query, exclude).first().map(|e| e.path.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &Path, rank: f64, last_accessed: i64) -> FrecencyEntry {
+        FrecencyEntry { path: path.to_path_buf(), rank, last_accessed }
+    }
+
+    fn keywords(query: &str) -> Vec<String> {
+        query.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn recent_visits_outrank_old_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let (old, recent) = (dir.path().join("old-project"), dir.path().join("new-project"));
+        fs::create_dir(&old).unwrap();
+        fs::create_dir(&recent).unwrap();
+        let now = Local::now().timestamp();
+        let db = FrecencyDb {
+            entries: vec![entry(&old, 10.0, now - 2 * WEEK), entry(&recent, 2.0, now)],
+            dirty: false,
+        };
+
+        let ranked: Vec<&Path> = db.query("project", Path::new("/")).iter().map(|e| e.path.as_path()).collect();
+        assert_eq!(ranked, vec![recent.as_path(), old.as_path()]);
+        assert_eq!(db.best_match("project", &recent), Some(old.clone()));
+        // Folders that are gone are never suggested
+        fs::remove_dir(&recent).unwrap();
+        assert_eq!(db.best_match("new", Path::new("/")), None);
+    }
+
+    #[test]
+    fn keywords_match_in_order_and_end_in_the_last_component() {
+        let e = entry(Path::new("/home/me/Code/fileexp/src"), 1.0, 0);
+        assert!(e.matches(&keywords("code src")));
+        assert!(e.matches(&keywords("me fileexp sr")));
+        assert!(e.matches(&[]));
+        assert!(!e.matches(&keywords("src code")));
+        assert!(!e.matches(&keywords("fileexp")));
+        assert!(!entry(Path::new("/"), 1.0, 0).matches(&keywords("x")));
+    }
+
+    #[test]
+    fn queries_ignore_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let docs = dir.path().join("Documents");
+        fs::create_dir(&docs).unwrap();
+        let mut db = FrecencyDb::default();
+        db.add(&docs);
+        assert_eq!(db.best_match("DOC", Path::new("/")), Some(docs));
+    }
+
+    #[test]
+    fn ranks_age_once_the_total_passes_the_limit() {
+        let now = Local::now().timestamp();
+        let mut db = FrecencyDb {
+            entries: vec![
+                entry(Path::new("/busy"), MAX_TOTAL_RANK - 1.0, now),
+                entry(Path::new("/rare"), 1.0, now),
+                entry(Path::new("/visited"), 1.0, now),
+            ],
+            dirty: false,
+        };
+        db.add(Path::new("/visited"));
+
+        let total: f64 = db.entries.iter().map(|e| e.rank).sum();
+        assert!(total <= 0.9 * MAX_TOTAL_RANK + 1e-6);
+        let paths: Vec<&Path> = db.entries.iter().map(|e| e.path.as_path()).collect();
+        assert_eq!(paths, vec![Path::new("/busy"), Path::new("/visited")]);
+    }
+
+    #[test]
+    fn visits_are_batched_until_a_flush() {
+        let mut db = FrecencyDb::default();
+        // Nothing to write, so this doesn't touch the real config
+        db.flush().unwrap();
+        assert!(!db.dirty);
+
+        db.add(Path::new("/a"));
+        db.add(Path::new("/a"));
+        assert!(db.dirty);
+        assert_eq!(db.entries.len(), 1);
+        assert_eq!(db.entries[0].rank, 2.0);
+    }
+}
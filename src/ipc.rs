@@ -0,0 +1,125 @@
+use eframe::egui::Context;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// One request on the control socket. Clients write one JSON object per line,
+/// e.g. `{"command":"navigate","path":"/tmp"}`, and get `{"ok":true}` back.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum IpcCommand {
+    /// Open a folder, or a file's folder with the file selected.
+    Navigate { path: PathBuf },
+    /// Select these items, opening the folder of the first one.
+    Select { paths: Vec<PathBuf> },
+    /// Re-read the current folder and the folder tree.
+    Refresh,
+    /// Bring the window to the front.
+    Raise,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IpcReply {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    error: Option<String>,
+}
+
+/// Control endpoint of the running instance. Removes its socket when dropped.
+pub struct IpcServer {
+    path: PathBuf,
+    pub commands: Receiver<IpcCommand>,
+}
+
+/// `$XDG_RUNTIME_DIR/fileexp.sock`, or a per-user name in the temp directory.
+pub fn socket_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("fileexp.sock"),
+        None => std::env::temp_dir().join(format!("fileexp-{}.sock", nix::unistd::getuid())),
+    }
+}
+
+impl IpcServer {
+    /// Listens for commands, waking `ctx` whenever one arrives.
+    pub fn start(ctx: Context) -> Result<Self, String> {
+        let path = socket_path();
+
+        // A socket nobody answers on is left over from a crashed instance
+        if path.exists() && UnixStream::connect(&path).is_err() {
+            let _ = fs::remove_file(&path);
+        }
+        let listener = UnixListener::bind(&path).map_err(|e| format!("Failed to listen on {}: {}", path.display(), e))?;
+
+        let (sender, commands) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().filter_map(Result::ok) {
+                let sender = sender.clone();
+                let ctx = ctx.clone();
+                thread::spawn(move || handle_client(stream, &sender, &ctx));
+            }
+        });
+
+        Ok(Self { path, commands })
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn handle_client(stream: UnixStream, sender: &Sender<IpcCommand>, ctx: &Context) {
+    let Ok(mut writer) = stream.try_clone() else { return };
+    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<IpcCommand>(&line) {
+            Ok(command) => {
+                let _ = sender.send(command);
+                ctx.request_repaint();
+                IpcReply { ok: true, error: None }
+            }
+            Err(e) => IpcReply { ok: false, error: Some(format!("Invalid command: {}", e)) },
+        };
+        let Ok(json) = serde_json::to_string(&reply) else { return };
+        if writeln!(writer, "{}", json).is_err() {
+            return;
+        }
+    }
+}
+
+/// Sends `commands` to an already running instance. Returns `false` when
+/// there is none, so the caller should start the GUI itself.
+pub fn forward(commands: &[IpcCommand]) -> bool {
+    let Ok(stream) = UnixStream::connect(socket_path()) else {
+        return false;
+    };
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+    let Ok(mut writer) = stream.try_clone() else {
+        return false;
+    };
+    let mut replies = BufReader::new(stream).lines();
+
+    for command in commands {
+        let Ok(json) = serde_json::to_string(command) else { continue };
+        if writeln!(writer, "{}", json).is_err() {
+            return false;
+        }
+        match replies.next() {
+            Some(Ok(reply)) => {
+                if let Ok(IpcReply { ok: false, error: Some(e) }) = serde_json::from_str(&reply) {
+                    eprintln!("{}", e);
+                }
+            }
+            _ => return false,
+        }
+    }
+    true
+}
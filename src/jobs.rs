@@ -0,0 +1,113 @@
+use eframe::egui::{self, Align2, Context, RichText};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use fileexp_core::archive::Progress;
+use fileexp_core::{Change, FileError};
+use crate::app::FileExplorerApp;
+use crate::utils;
+
+/// What a finished background job did.
+pub struct JobOutcome {
+    pub changes: Vec<Change>,
+    /// Message on success, or the failure.
+    pub result: Result<String, FileError>,
+}
+
+/// Handed to the job's thread to report progress and notice cancellation.
+#[derive(Clone)]
+pub struct JobHandle {
+    progress: Arc<Mutex<Progress>>,
+    pub cancel: Arc<AtomicBool>,
+    ctx: Context,
+}
+
+impl JobHandle {
+    pub fn report(&self, progress: &Progress) {
+        *self.progress.lock().unwrap() = progress.clone();
+        self.ctx.request_repaint_after(Duration::from_millis(100));
+    }
+}
+
+/// A long file operation running on its own thread.
+struct Job {
+    title: String,
+    handle: JobHandle,
+    thread: Option<JoinHandle<JobOutcome>>,
+}
+
+/// Background jobs such as creating and extracting archives, shown with
+/// progress bars in the bottom left corner.
+pub struct JobList {
+    jobs: Vec<Job>,
+}
+
+impl JobList {
+    pub fn new() -> Self {
+        Self { jobs: Vec::new() }
+    }
+
+    /// Runs `work` on a new thread.
+    pub fn spawn(&mut self, ctx: &Context, title: String, work: impl FnOnce(&JobHandle) -> JobOutcome + Send + 'static) {
+        let handle = JobHandle {
+            progress: Arc::new(Mutex::new(Progress::default())),
+            cancel: Arc::new(AtomicBool::new(false)),
+            ctx: ctx.clone(),
+        };
+        let thread_handle = handle.clone();
+        let thread = thread::spawn(move || {
+            let outcome = work(&thread_handle);
+            thread_handle.ctx.request_repaint();
+            outcome
+        });
+        self.jobs.push(Job { title, handle, thread: Some(thread) });
+    }
+
+    /// Outcomes of the jobs that ended since the last call, or which job
+    /// panicked.
+    pub fn finished(&mut self) -> Vec<Result<JobOutcome, String>> {
+        let mut outcomes = Vec::new();
+        self.jobs.retain_mut(|job| {
+            if !job.thread.as_ref().is_some_and(JoinHandle::is_finished) {
+                return true;
+            }
+            if let Some(thread) = job.thread.take() {
+                outcomes.push(thread.join().map_err(|_| format!("{} stopped unexpectedly", job.title)));
+            }
+            false
+        });
+        outcomes
+    }
+}
+
+pub fn show_jobs(app: &mut FileExplorerApp, ctx: &Context) {
+    if app.jobs.jobs.is_empty() {
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("jobs"))
+        .anchor(Align2::LEFT_BOTTOM, [12.0, -12.0])
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            for job in &app.jobs.jobs {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(320.0);
+                    let progress = job.handle.progress.lock().unwrap().clone();
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(&job.title).strong());
+                        let cancelling = job.handle.cancel.load(Ordering::Relaxed);
+                        if ui.add_enabled(!cancelling, egui::Button::new("Cancel").small()).clicked() {
+                            job.handle.cancel.store(true, Ordering::Relaxed);
+                        }
+                    });
+                    let fraction = if progress.total_bytes > 0 { progress.done_bytes as f32 / progress.total_bytes as f32 } else { 0.0 };
+                    let text = format!("{} of {}", utils::format_file_size(progress.done_bytes), utils::format_file_size(progress.total_bytes));
+                    ui.add(egui::ProgressBar::new(fraction).text(text));
+                    if let Some(name) = progress.current.file_name() {
+                        ui.label(RichText::new(name.to_string_lossy()).weak().small());
+                    }
+                });
+            }
+        });
+}
@@ -0,0 +1,122 @@
+use eframe::egui::{self, Context, RichText, ScrollArea};
+use chrono::{DateTime, Local};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use fileexp_core::replay::{self, Action};
+use fileexp_core::{FileError, Operation};
+use crate::app::FileExplorerApp;
+use crate::utils;
+
+/// Changes made during this session, with a window to export a range of
+/// them as a shell script.
+pub struct SessionJournal {
+    pub show: bool,
+    pub actions: Vec<(DateTime<Local>, Action)>,
+    /// First and last selected row, inclusive.
+    range: Option<(usize, usize)>,
+    export_path: String,
+}
+
+impl SessionJournal {
+    pub fn new() -> Self {
+        let export_path = dirs::home_dir()
+            .map(|home| home.join("fileexp-replay.sh").to_string_lossy().to_string())
+            .unwrap_or_else(|| "fileexp-replay.sh".to_string());
+        Self { show: false, actions: Vec::new(), range: None, export_path }
+    }
+
+    pub fn record(&mut self, action: Action) {
+        self.actions.push((Local::now(), action));
+    }
+
+    fn selected(&self) -> Vec<Action> {
+        let Some((first, last)) = self.range else { return Vec::new() };
+        self.actions[first..=last].iter().map(|(_, action)| action.clone()).collect()
+    }
+
+    /// Selects `index`, or with Shift extends the selection up to it.
+    fn click(&mut self, index: usize, extend: bool) {
+        self.range = match self.range {
+            Some((first, last)) if extend => Some((first.min(index), last.max(index))),
+            _ => Some((index, index)),
+        };
+    }
+}
+
+fn write_script(path: &Path, script: &str) -> Result<(), FileError> {
+    fs::write(path, script).map_err(|e| FileError::io(Operation::Write, path, &e))?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).map_err(|e| FileError::io(Operation::Write, path, &e))
+}
+
+pub fn show_journal_window(app: &mut FileExplorerApp, ctx: &Context) {
+    if !app.journal.show {
+        return;
+    }
+
+    let mut open = true;
+    let mut export = None;
+    let mut copy = None;
+    let journal = &mut app.journal;
+    egui::Window::new("🕘 Session history")
+        .open(&mut open)
+        .resizable(true)
+        .default_width(600.0)
+        .default_height(400.0)
+        .show(ctx, |ui| {
+            ui.label("Click a row to start a range and Shift+click to extend it, then export the range as a shell script.");
+            ui.horizontal(|ui| {
+                if ui.button("Select all").clicked() && !journal.actions.is_empty() {
+                    journal.range = Some((0, journal.actions.len() - 1));
+                }
+                if ui.button("Select none").clicked() {
+                    journal.range = None;
+                }
+            });
+            ui.separator();
+
+            if journal.actions.is_empty() {
+                ui.label(RichText::new("Nothing was changed in this session yet").weak());
+            }
+            ScrollArea::vertical().max_height(ui.available_height() - 60.0).show(ui, |ui| {
+                for i in 0..journal.actions.len() {
+                    let (time, action) = &journal.actions[i];
+                    let selected = journal.range.is_some_and(|(first, last)| (first..=last).contains(&i));
+                    let label = format!("{}  {}", time.format("%H:%M:%S"), action.describe());
+                    if ui.selectable_label(selected, label).clicked() {
+                        let extend = ui.input(|input| input.modifiers.shift);
+                        journal.click(i, extend);
+                    }
+                }
+            });
+            ui.separator();
+
+            let selected = journal.selected();
+            ui.horizontal(|ui| {
+                ui.label("Save to:");
+                ui.text_edit_singleline(&mut journal.export_path);
+                let enabled = !selected.is_empty() && !journal.export_path.trim().is_empty();
+                if ui.add_enabled(enabled, egui::Button::new(format!("💾 Export {} actions", selected.len()))).clicked() {
+                    export = Some((journal.export_path.clone(), replay::shell_script(&selected)));
+                }
+                if ui.add_enabled(!selected.is_empty(), egui::Button::new("📋 Copy script")).clicked() {
+                    copy = Some(replay::shell_script(&selected));
+                }
+            });
+        });
+    app.journal.show = open;
+
+    if let Some((path, script)) = export {
+        let path = utils::expand_path(&path, &app.explorer.current_path);
+        match write_script(&path, &script) {
+            Ok(()) => app.notifications.info(format!("Exported session history to {}", path.display())),
+            Err(e) => app.notifications.file_error(e, None, None),
+        }
+    }
+    if let Some(script) = copy
+        && let Ok(clipboard) = &mut app.clipboard
+    {
+        let _ = clipboard.set_text(script);
+        app.notifications.info("Script copied to clipboard");
+    }
+}
@@ -1,23 +1,99 @@
 mod app;
 mod models;
 mod ui;
-mod operations;
 mod utils;
 mod terminal;
 mod terminal_ui;
 mod context_menu;
 mod settings;
+mod frecency;
+mod tabs;
+mod sidebar;
+mod columns;
+mod session;
+mod cli;
+mod picker;
+mod ipc;
+mod dbus;
+mod commands;
+mod notifications;
+mod audit_ui;
+mod journal_ui;
+mod plan_ui;
+mod protection;
+mod rename_ui;
+mod editor_rename;
+mod jobs;
+mod archive_ui;
 
+use std::process::ExitCode;
+use fileexp_core::{operations, rename, trash};
+use std::sync::{Arc, Mutex};
+use clap::Parser;
 use eframe::{egui, NativeOptions};
+use settings::AppSettings;
 
-fn main() -> Result<(), eframe::Error> {
+fn main() -> Result<ExitCode, eframe::Error> {
+    let mut args = cli::Args::parse();
+    if let Some(command) = args.command.take() {
+        return Ok(commands::run(command));
+    }
+    
+    if let Err(e) = args.start_location() {
+        eprintln!("{}", e);
+        return Ok(ExitCode::from(2));
+    }
+    
+    // Hand the paths over if the explorer is already running
+    if args.should_forward() && ipc::forward(&args.forwarded_commands()) {
+        return Ok(ExitCode::SUCCESS);
+    }
+    
+    let picked = Arc::new(Mutex::new(None));
+    let picker = if args.pick {
+        match picker::PickerState::new(&args, Arc::clone(&picked)) {
+            Ok(picker) => Some(picker),
+            Err(e) => {
+                eprintln!("{}", e);
+                return Ok(ExitCode::from(2));
+            }
+        }
+    } else {
+        None
+    };
+    let pick_mode = picker.is_some();
+    
+    if let Some(dir) = &args.config {
+        AppSettings::set_config_dir(dir.clone());
+    }
+    let settings = AppSettings::load();
+    
+    // eframe restores the saved size and position over this default
+    let mut viewport = egui::ViewportBuilder::default().with_inner_size(egui::Vec2::new(1200.0, 800.0));
+    if let Some(picker) = &picker {
+        viewport = viewport.with_title(picker.title());
+    }
     let options = NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size(egui::Vec2::new(1200.0, 800.0)),
+        viewport,
+        persist_window: settings.remember_window_size,
         ..Default::default()
     };
     eframe::run_native(
         "Rust File Explorer Pro",
         options,
-        Box::new(|cc| Box::new(app::FileExplorerApp::new(cc))),
-    )
+        Box::new(move |cc| Box::new(app::FileExplorerApp::new(cc, &args, picker))),
+    )?;
+    
+    if !pick_mode {
+        return Ok(ExitCode::SUCCESS);
+    }
+    match picked.lock().unwrap().take() {
+        Some(paths) => {
+            for path in paths {
+                println!("{}", path.display());
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        None => Ok(ExitCode::FAILURE),
+    }
 }
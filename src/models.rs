@@ -1,27 +1,19 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use chrono::{DateTime, Local};
 
-#[derive(Clone, Debug)]
-pub enum FileOperation {
-    Copy(Vec<PathBuf>),
-    Cut(Vec<PathBuf>),
-}
-
-#[derive(Clone, Debug)]
-pub struct FileEntry {
-    pub path: PathBuf,
-    pub is_dir: bool,
-    pub size: u64,
-    pub modified: DateTime<Local>,
-    pub name: String,
-    pub extension: String,
-}
+// Data types shared with the CLI live in the core crate
+pub use fileexp_core::models::*;
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub enum ViewMode {
     List,
     Grid,
+    Columns,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PaneSide {
+    Left,
+    Right,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
@@ -29,9 +21,3 @@ pub enum Theme {
     Light,
     Dark,
 }
-
-#[derive(Serialize, Deserialize, Clone)]
-pub struct Bookmark {
-    pub name: String,
-    pub path: PathBuf,
-} 
\ No newline at end of file
@@ -0,0 +1,349 @@
+use eframe::egui::{self, Align2, Color32, Context, RichText, ScrollArea, Ui};
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+use fileexp_core::{Command, FileError};
+use crate::app::FileExplorerApp;
+
+const MAX_LOG_ENTRIES: usize = 500;
+const MAX_TOASTS: usize = 4;
+const TOAST_SECONDS: u64 = 4;
+const ERROR_TOAST_SECONDS: u64 = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    fn icon(self) -> &'static str {
+        match self {
+            Level::Info => "ℹ",
+            Level::Warning => "⚠",
+            Level::Error => "❌",
+        }
+    }
+
+    fn color(self) -> Color32 {
+        match self {
+            Level::Info => Color32::from_rgb(0, 150, 0),
+            Level::Warning => Color32::from_rgb(200, 140, 0),
+            Level::Error => Color32::RED,
+        }
+    }
+
+    fn toast_duration(self) -> Duration {
+        match self {
+            Level::Error => Duration::from_secs(ERROR_TOAST_SECONDS),
+            _ => Duration::from_secs(TOAST_SECONDS),
+        }
+    }
+}
+
+/// One entry of the notification log.
+pub struct Notification {
+    pub id: u64,
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub message: String,
+    pub error: Option<FileError>,
+    /// Runs the failed step again.
+    pub retry: Option<Command>,
+    /// Carries on with the rest of a batch after the failed item.
+    pub skip: Option<Command>,
+}
+
+impl Notification {
+    pub fn new(level: Level, message: impl Into<String>) -> Self {
+        Self { id: 0, time: Local::now(), level, message: message.into(), error: None, retry: None, skip: None }
+    }
+
+    pub fn from_error(error: FileError, retry: Option<Command>, skip: Option<Command>) -> Self {
+        // Problems like copying a folder into itself fail the same way again
+        let retry = retry.filter(|_| error.is_retryable());
+        Self { retry, skip, error: Some(error.clone()), ..Self::new(Level::Error, error.to_string()) }
+    }
+
+    fn to_line(&self) -> String {
+        let level = match self.level {
+            Level::Info => "INFO",
+            Level::Warning => "WARNING",
+            Level::Error => "ERROR",
+        };
+        format!("{} {} {}", self.time.format("%Y-%m-%d %H:%M:%S"), level, self.message)
+    }
+}
+
+/// Posts notifications from background threads, e.g. when a job finishes.
+#[derive(Clone)]
+pub struct Notifier {
+    sender: Sender<Notification>,
+    ctx: Context,
+}
+
+impl Notifier {
+    pub fn post(&self, level: Level, message: impl Into<String>) {
+        if self.sender.send(Notification::new(level, message)).is_ok() {
+            self.ctx.request_repaint();
+        }
+    }
+}
+
+/// Timestamped log of status messages, warnings and errors. New entries pop
+/// up as toasts for a few seconds and stay reviewable in the drawer.
+pub struct NotificationCenter {
+    pub log: VecDeque<Notification>,
+    pub show_drawer: bool,
+    /// Entries added since the drawer was last open.
+    pub unread: usize,
+    toasts: Vec<(u64, Instant)>,
+    next_id: u64,
+    sender: Sender<Notification>,
+    receiver: Receiver<Notification>,
+}
+
+impl NotificationCenter {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            log: VecDeque::new(),
+            show_drawer: false,
+            unread: 0,
+            toasts: Vec::new(),
+            next_id: 1,
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn notifier(&self, ctx: &Context) -> Notifier {
+        Notifier { sender: self.sender.clone(), ctx: ctx.clone() }
+    }
+
+    pub fn push(&mut self, mut notification: Notification) {
+        notification.id = self.next_id;
+        self.next_id += 1;
+        self.toasts.push((notification.id, Instant::now()));
+        if !self.show_drawer {
+            self.unread += 1;
+        }
+        self.log.push_back(notification);
+        while self.log.len() > MAX_LOG_ENTRIES {
+            self.log.pop_front();
+        }
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Notification::new(Level::Info, message));
+    }
+
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.push(Notification::new(Level::Warning, message));
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(Notification::new(Level::Error, message));
+    }
+
+    pub fn file_error(&mut self, error: FileError, retry: Option<Command>, skip: Option<Command>) {
+        self.push(Notification::from_error(error, retry, skip));
+    }
+
+    /// Moves notifications posted by other threads into the log.
+    pub fn receive(&mut self) {
+        while let Ok(notification) = self.receiver.try_recv() {
+            self.push(notification);
+        }
+    }
+
+    fn remove(&mut self, id: u64) -> Option<Notification> {
+        self.toasts.retain(|(toast, _)| *toast != id);
+        let index = self.log.iter().position(|n| n.id == id)?;
+        self.log.remove(index)
+    }
+
+    pub fn clear(&mut self) {
+        self.log.clear();
+        self.toasts.clear();
+        self.unread = 0;
+    }
+
+    pub fn toggle_drawer(&mut self) {
+        self.show_drawer = !self.show_drawer;
+        self.unread = 0;
+    }
+
+    /// The whole log as text, oldest first.
+    pub fn to_text(&self) -> String {
+        self.log.iter().map(Notification::to_line).collect::<Vec<_>>().join("\n")
+    }
+}
+
+enum NotificationAction {
+    Retry(u64),
+    Skip(u64),
+    Dismiss(u64),
+    Copy(String),
+    Clear,
+}
+
+/// Bell button that opens the drawer, with the number of unread entries.
+pub fn show_drawer_button(app: &mut FileExplorerApp, ui: &mut Ui) {
+    let center = &mut app.notifications;
+    let label = if center.unread > 0 { format!("🔔 {}", center.unread) } else { "🔔".to_string() };
+    if ui.selectable_label(center.show_drawer, label).on_hover_text("Notifications").clicked() {
+        center.toggle_drawer();
+    }
+}
+
+/// Shows the newest notifications in the bottom right corner until they expire.
+pub fn show_toasts(app: &mut FileExplorerApp, ctx: &Context) {
+    let center = &mut app.notifications;
+    let now = Instant::now();
+    let log = &center.log;
+    center.toasts.retain(|(id, shown)| {
+        log.iter().find(|n| n.id == *id).is_some_and(|n| now.duration_since(*shown) < n.level.toast_duration())
+    });
+    if center.toasts.len() > MAX_TOASTS {
+        center.toasts.drain(..center.toasts.len() - MAX_TOASTS);
+    }
+    if center.toasts.is_empty() || center.show_drawer {
+        return;
+    }
+
+    let mut open_drawer = false;
+    let mut next_expiry = Duration::MAX;
+    egui::Area::new(egui::Id::new("toasts"))
+        .anchor(Align2::RIGHT_BOTTOM, [-12.0, -12.0])
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            for (id, shown) in &center.toasts {
+                let Some(notification) = center.log.iter().find(|n| n.id == *id) else { continue };
+                next_expiry = next_expiry.min(notification.level.toast_duration().saturating_sub(now.duration_since(*shown)));
+                let response = egui::Frame::popup(ui.style())
+                    .show(ui, |ui| {
+                        ui.set_max_width(360.0);
+                        ui.label(RichText::new(format!("{} {}", notification.level.icon(), notification.message))
+                            .color(notification.level.color()));
+                    })
+                    .response
+                    .interact(egui::Sense::click());
+                if response.on_hover_text("Click to open notifications").clicked() {
+                    open_drawer = true;
+                }
+            }
+        });
+
+    if open_drawer {
+        center.toggle_drawer();
+    }
+    ctx.request_repaint_after(next_expiry);
+}
+
+/// Side panel with the full log: review entries, retry failed operations,
+/// copy and clear.
+pub fn show_drawer(app: &mut FileExplorerApp, ctx: &Context) {
+    if !app.notifications.show_drawer {
+        return;
+    }
+
+    let mut action = None;
+    egui::SidePanel::right("notification_drawer")
+        .resizable(true)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Notifications");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("✖").on_hover_text("Close").clicked() {
+                        app.notifications.show_drawer = false;
+                    }
+                    if ui.button("🗑 Clear").clicked() {
+                        action = Some(NotificationAction::Clear);
+                    }
+                    if ui.button("📋 Copy all").clicked() {
+                        action = Some(NotificationAction::Copy(app.notifications.to_text()));
+                    }
+                });
+            });
+            ui.separator();
+
+            if app.notifications.log.is_empty() {
+                ui.label(RichText::new("No notifications").weak());
+            }
+            ScrollArea::vertical().show(ui, |ui| {
+                for notification in app.notifications.log.iter().rev() {
+                    show_notification(ui, notification, &mut action);
+                    ui.separator();
+                }
+            });
+        });
+
+    let Some(action) = action else { return };
+    match action {
+        NotificationAction::Retry(id) => {
+            if let Some(command) = app.notifications.remove(id).and_then(|n| n.retry) {
+                app.execute(command);
+            }
+        }
+        NotificationAction::Skip(id) => {
+            if let Some(command) = app.notifications.remove(id).and_then(|n| n.skip) {
+                app.execute(command);
+            }
+        }
+        NotificationAction::Dismiss(id) => {
+            app.notifications.remove(id);
+        }
+        NotificationAction::Copy(text) => {
+            if let Ok(clipboard) = &mut app.clipboard {
+                let _ = clipboard.set_text(text);
+            }
+        }
+        NotificationAction::Clear => app.notifications.clear(),
+    }
+}
+
+fn show_notification(ui: &mut Ui, notification: &Notification, action: &mut Option<NotificationAction>) {
+    let id = notification.id;
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(notification.time.format("%H:%M:%S").to_string()).small().weak())
+            .on_hover_text(notification.time.format("%Y-%m-%d %H:%M:%S").to_string());
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui.small_button("✖").on_hover_text("Dismiss").clicked() {
+                *action = Some(NotificationAction::Dismiss(id));
+            }
+            if ui.small_button("📋").on_hover_text("Copy").clicked() {
+                *action = Some(NotificationAction::Copy(notification.to_line()));
+            }
+            if notification.skip.is_some() && ui.small_button("Skip").on_hover_text("Carry on with the remaining items").clicked() {
+                *action = Some(NotificationAction::Skip(id));
+            }
+            if notification.retry.is_some() && ui.small_button("Retry").clicked() {
+                *action = Some(NotificationAction::Retry(id));
+            }
+        });
+    });
+    ui.label(RichText::new(format!("{} {}", notification.level.icon(), notification.message)).color(notification.level.color()));
+
+    if let Some(error) = &notification.error {
+        egui::CollapsingHeader::new("Details").id_source(("notification_details", id)).show(ui, |ui| {
+            egui::Grid::new(("notification_grid", id)).num_columns(2).show(ui, |ui| {
+                ui.label("Operation:");
+                ui.label(format!("{:?}", error.operation()));
+                ui.end_row();
+                ui.label("Path:");
+                ui.label(error.path().display().to_string());
+                ui.end_row();
+                ui.label("Reason:");
+                ui.label(error.reason());
+                ui.end_row();
+                ui.label("Error kind:");
+                ui.label(format!("{:?}", error.kind()));
+                ui.end_row();
+            });
+        });
+    }
+}
@@ -0,0 +1,187 @@
+use eframe::egui::{self, Color32, RichText, Ui};
+use glob::{MatchOptions, Pattern};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use crate::app::FileExplorerApp;
+use crate::cli::Args;
+use crate::models::FileEntry;
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: false,
+    require_literal_leading_dot: false,
+};
+
+/// State of `--pick` mode, where the explorer works as a file dialog.
+pub struct PickerState {
+    pub multiple: bool,
+    pub directory: bool,
+    /// File name typed in save mode; `None` when opening.
+    pub save_name: Option<String>,
+    /// Set once the user confirmed or cancelled; the window closes next frame.
+    pub done: bool,
+    filters: Vec<Pattern>,
+    last_selected: Option<PathBuf>,
+    result: Arc<Mutex<Option<Vec<PathBuf>>>>,
+}
+
+impl PickerState {
+    /// Chosen paths are written to `result`, which stays `None` on cancel.
+    pub fn new(args: &Args, result: Arc<Mutex<Option<Vec<PathBuf>>>>) -> Result<Self, String> {
+        let filters = args.filter.iter()
+            .map(|f| Pattern::new(f).map_err(|e| format!("Invalid filter '{}': {}", f, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            multiple: args.multiple,
+            directory: args.directory,
+            save_name: args.save.clone(),
+            done: false,
+            filters,
+            last_selected: None,
+            result,
+        })
+    }
+
+    /// Whether `entry` is listed. Folders always are so they can be browsed.
+    pub fn accepts(&self, entry: &FileEntry) -> bool {
+        if entry.is_dir {
+            return true;
+        }
+        !self.directory && (self.filters.is_empty() || self.filters.iter().any(|p| p.matches_with(&entry.name, MATCH_OPTIONS)))
+    }
+
+    pub fn finish(&mut self, paths: Vec<PathBuf>) {
+        *self.result.lock().unwrap() = Some(paths);
+        self.done = true;
+    }
+
+    pub fn cancel(&mut self) {
+        self.done = true;
+    }
+
+    pub fn title(&self) -> &'static str {
+        match (&self.save_name, self.directory, self.multiple) {
+            (Some(_), _, _) => "Save As",
+            (None, true, true) => "Choose Folders",
+            (None, true, false) => "Choose Folder",
+            (None, false, true) => "Open Files",
+            (None, false, false) => "Open File",
+        }
+    }
+}
+
+/// Paths the current selection would return, or why it can't be confirmed yet.
+fn chosen_paths(app: &FileExplorerApp, picker: &PickerState) -> Result<Vec<PathBuf>, String> {
+    if let Some(name) = &picker.save_name {
+        let name = name.trim();
+        if name.is_empty() || name.contains('/') {
+            return Err("Enter a file name".to_string());
+        }
+        return Ok(vec![app.explorer.current_path.join(name)]);
+    }
+
+    let mut paths: Vec<PathBuf> = app.explorer.selected_entries().into_iter()
+        .filter(|e| e.is_dir == picker.directory)
+        .map(|e| e.path)
+        .collect();
+
+    // Without a selection, a folder chooser takes the folder being shown
+    if paths.is_empty() && picker.directory {
+        paths.push(app.explorer.current_path.clone());
+    }
+    if paths.is_empty() {
+        return Err("Select a file".to_string());
+    }
+    if !picker.multiple {
+        paths.truncate(1);
+    }
+    Ok(paths)
+}
+
+/// Confirms the current selection, as the Open/Save button does.
+pub fn confirm(app: &mut FileExplorerApp) {
+    let Some(picker) = app.picker.as_ref() else { return };
+    match chosen_paths(app, picker) {
+        Ok(paths) => {
+            if let Some(picker) = app.picker.as_mut() {
+                picker.finish(paths);
+            }
+        }
+        Err(e) => app.notifications.warn(e),
+    }
+}
+
+/// Chooses `path` directly, as double-clicking it does. In save mode a file
+/// lends its name before confirming, since the bar hasn't seen it yet.
+pub fn choose(app: &mut FileExplorerApp, path: &Path) {
+    app.select_paths(&[path.to_path_buf()]);
+    if let Some(picker) = app.picker.as_mut()
+        && let Some(name) = picker.save_name.as_mut()
+        && !path.is_dir()
+        && let Some(file_name) = path.file_name()
+    {
+        *name = file_name.to_string_lossy().to_string();
+        picker.last_selected = Some(path.to_path_buf());
+    }
+    confirm(app);
+}
+
+/// Replaces the action row while picking.
+pub fn show_picker_bar(app: &mut FileExplorerApp, ui: &mut Ui) {
+    let Some(picker) = app.picker.as_mut() else { return };
+
+    // Picking a file in save mode suggests its name
+    let first_selected = app.explorer.first_selected().map(|(_, entry)| entry);
+    let selected_path = first_selected.map(|e| e.path.clone());
+    if selected_path != picker.last_selected {
+        if let (Some(name), Some(entry)) = (picker.save_name.as_mut(), first_selected)
+            && !entry.is_dir
+        {
+            *name = entry.name.clone();
+        }
+        picker.last_selected = selected_path;
+    }
+
+    let title = picker.title();
+    let is_save = picker.save_name.is_some();
+    let mut confirmed = false;
+
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(title).strong());
+        ui.separator();
+
+        if let Some(name) = app.picker.as_mut().and_then(|p| p.save_name.as_mut()) {
+            ui.label("Name:");
+            let response = ui.add(egui::TextEdit::singleline(name).desired_width(240.0));
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                confirmed = true;
+            }
+            let name = name.trim().to_string();
+            if !name.is_empty() && app.explorer.current_path.join(&name).exists() {
+                ui.label(RichText::new("⚠ Replaces the existing file").color(Color32::from_rgb(255, 165, 0)));
+            }
+        } else if let Some(picker) = &app.picker
+            && !picker.filters.is_empty()
+        {
+            let patterns: Vec<&str> = picker.filters.iter().map(Pattern::as_str).collect();
+            ui.label(format!("Showing: {}", patterns.join(", ")));
+        }
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let label = if is_save { "💾 Save" } else { "✔ Open" };
+            if ui.button(label).clicked() {
+                confirmed = true;
+            }
+            if ui.button("✖ Cancel").clicked()
+                && let Some(picker) = app.picker.as_mut()
+            {
+                picker.cancel();
+            }
+        });
+    });
+
+    if confirmed {
+        confirm(app);
+    }
+}
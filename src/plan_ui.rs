@@ -0,0 +1,206 @@
+use eframe::egui::{self, Color32, Context, RichText, ScrollArea};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use fileexp_core::models::FileOperation;
+use fileexp_core::plan::{Effect, Plan, PlanItem, PlanKind};
+use crate::app::FileExplorerApp;
+use crate::utils;
+
+// Files listed under an expanded item; the rest are only counted
+const MAX_SHOWN_FILES: usize = 200;
+
+/// A paste or delete preview, worked out on its own thread because walking
+/// large folders takes a while.
+pub enum PlanPreview {
+    Building { thread: JoinHandle<Plan>, cancel: Arc<AtomicBool> },
+    Ready(Plan),
+}
+
+impl PlanPreview {
+    pub fn build(work: impl FnOnce(&AtomicBool) -> Plan + Send + 'static) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread_cancel = Arc::clone(&cancel);
+        let thread = thread::spawn(move || work(&thread_cancel));
+        PlanPreview::Building { thread, cancel }
+    }
+}
+
+fn effect_label(effect: Effect) -> RichText {
+    match effect {
+        Effect::Create => RichText::new("create").color(Color32::from_rgb(0, 150, 0)),
+        Effect::Overwrite => RichText::new("⚠ overwrite").color(Color32::from_rgb(220, 120, 0)),
+        Effect::Merge => RichText::new("⚠ merge").color(Color32::from_rgb(200, 160, 0)),
+        Effect::Remove => RichText::new("remove").color(Color32::RED),
+    }
+}
+
+fn title(plan: &Plan) -> String {
+    match &plan.kind {
+        PlanKind::Transfer { operation: FileOperation::Copy(_), target } => format!("Copy into {}", target.display()),
+        PlanKind::Transfer { operation: FileOperation::Cut(_), target } => format!("Move into {}", target.display()),
+        PlanKind::Delete => "Delete".to_string(),
+    }
+}
+
+fn show_item(ui: &mut egui::Ui, index: usize, item: &mut PlanItem) {
+    ui.horizontal(|ui| {
+        ui.add_enabled(item.problem.is_none(), egui::Checkbox::without_text(&mut item.selected));
+        ui.label(effect_label(item.effect));
+        let name = item.source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        ui.label(name).on_hover_text(item.source.display().to_string());
+        if let Some(problem) = &item.problem {
+            ui.colored_label(Color32::RED, problem);
+            return;
+        }
+        ui.label(RichText::new(utils::format_file_size(item.bytes)).weak());
+        let conflicts = item.conflicts();
+        if conflicts > 0 && item.files.len() > 1 {
+            ui.label(RichText::new(format!("{} conflicts", conflicts)).color(Color32::from_rgb(220, 120, 0)));
+        }
+    });
+
+    // The first file is the item itself, checked above
+    if item.files.len() > 1 {
+        let mut toggled = None;
+        egui::CollapsingHeader::new(format!("{} files and folders", item.files.len() - 1))
+            .id_source(("plan_item", index))
+            .show(ui, |ui| {
+                for (file_index, file) in item.files.iter().enumerate().skip(1).take(MAX_SHOWN_FILES) {
+                    ui.horizontal(|ui| {
+                        let mut selected = file.selected;
+                        if ui.add_enabled(item.selected, egui::Checkbox::without_text(&mut selected)).changed() {
+                            toggled = Some((file_index, selected));
+                        }
+                        ui.label(effect_label(file.effect));
+                        let mut path = file.path.display().to_string();
+                        if file.is_dir {
+                            path.push('/');
+                        }
+                        ui.label(path);
+                        if !file.is_dir {
+                            ui.label(RichText::new(utils::format_file_size(file.bytes)).weak());
+                        }
+                    });
+                }
+                if item.files.len() > MAX_SHOWN_FILES + 1 {
+                    ui.label(RichText::new(format!("… and {} more", item.files.len() - MAX_SHOWN_FILES - 1)).weak());
+                }
+            });
+        // Unchecking a folder leaves out everything in it
+        if let Some((file_index, selected)) = toggled {
+            item.set_selected(file_index, selected);
+        }
+    }
+}
+
+/// Shown while the preview is worked out. Returns whether it was cancelled.
+fn show_building_window(ctx: &Context) -> bool {
+    let mut open = true;
+    let mut cancel = false;
+    egui::Window::new("🔍 Preview")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Working out what will change…");
+            });
+            if ui.button("Cancel").clicked() {
+                cancel = true;
+            }
+        });
+    cancel || !open
+}
+
+/// Window listing everything a pending paste or delete will do, where items
+/// can be unchecked before running it.
+pub fn show_plan_window(app: &mut FileExplorerApp, ctx: &Context) {
+    if let Some(PlanPreview::Building { thread, .. }) = &app.pending_plan
+        && thread.is_finished()
+        && let Some(PlanPreview::Building { thread, .. }) = app.pending_plan.take()
+    {
+        match thread.join() {
+            Ok(plan) => app.pending_plan = Some(PlanPreview::Ready(plan)),
+            Err(_) => app.notifications.error("Working out the preview stopped unexpectedly"),
+        }
+    }
+    let plan = match &mut app.pending_plan {
+        None => return,
+        Some(PlanPreview::Building { cancel, .. }) => {
+            // The spinner keeps repainting, so the finished plan is picked up
+            if show_building_window(ctx) {
+                cancel.store(true, Ordering::Relaxed);
+                app.pending_plan = None;
+            }
+            return;
+        }
+        Some(PlanPreview::Ready(plan)) => plan,
+    };
+
+    let mut open = true;
+    let mut run = false;
+    let mut cancel = false;
+    egui::Window::new("🔍 Preview")
+        .open(&mut open)
+        .resizable(true)
+        .default_width(600.0)
+        .default_height(420.0)
+        .show(ctx, |ui| {
+            ui.heading(title(plan));
+            let summary = match plan.kind {
+                PlanKind::Delete => format!(
+                    "{} files and folders removed, {} freed",
+                    plan.count(Effect::Remove),
+                    utils::format_file_size(plan.total_bytes()),
+                ),
+                PlanKind::Transfer { .. } => format!(
+                    "{} created, {} overwritten, {} merged, {} written",
+                    plan.count(Effect::Create),
+                    plan.count(Effect::Overwrite),
+                    plan.count(Effect::Merge),
+                    utils::format_file_size(plan.total_bytes()),
+                ),
+            };
+            ui.label(summary);
+            if plan.items.iter().any(|item| item.problem.is_none() && !item.selected) {
+                ui.label(RichText::new("Items that would overwrite files start unchecked.").weak());
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Select all").clicked() {
+                    plan.items.iter_mut().filter(|item| item.problem.is_none()).for_each(|item| item.selected = true);
+                }
+                if ui.button("Select none").clicked() {
+                    plan.items.iter_mut().for_each(|item| item.selected = false);
+                }
+            });
+            ui.separator();
+
+            ScrollArea::vertical().max_height(ui.available_height() - 40.0).show(ui, |ui| {
+                for (index, item) in plan.items.iter_mut().enumerate() {
+                    show_item(ui, index, item);
+                }
+            });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let selected = plan.selected().count();
+                if ui.add_enabled(selected > 0, egui::Button::new(format!("▶ Run {} items", selected))).clicked() {
+                    run = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+    if run && let Some(PlanPreview::Ready(plan)) = app.pending_plan.take() {
+        app.execute_all(plan.commands());
+        if app.dual_pane {
+            app.refresh_other_pane();
+        }
+    } else if cancel || !open {
+        app.pending_plan = None;
+    }
+}
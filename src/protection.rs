@@ -0,0 +1,222 @@
+use eframe::egui::{self, Color32, Context, RichText};
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use fileexp_core::Command;
+use crate::app::FileExplorerApp;
+use crate::utils;
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Protection {
+    /// Deleting or moving asks to type the folder name first.
+    Confirm,
+    Block,
+}
+
+/// A glob such as `/etc` or `/mnt/*`; matching folders and everything in
+/// them are protected.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProtectedPath {
+    pub pattern: String,
+    pub protection: Protection,
+}
+
+impl ProtectedPath {
+    pub fn new(pattern: &str, protection: Protection) -> Self {
+        Self { pattern: pattern.to_string(), protection }
+    }
+
+    /// The folder among `path` and its parents that the pattern matches.
+    fn matching_folder(&self, path: &Path) -> Option<PathBuf> {
+        if self.pattern.trim().is_empty() {
+            return None;
+        }
+        let expanded = utils::expand_path(&self.pattern, Path::new("/"));
+        let pattern = Pattern::new(&expanded.to_string_lossy()).ok()?;
+        path.ancestors()
+            .find(|folder| pattern.matches_path_with(folder, MATCH_OPTIONS))
+            .map(Path::to_path_buf)
+    }
+}
+
+pub fn default_protected_paths() -> Vec<ProtectedPath> {
+    vec![
+        ProtectedPath::new("/boot", Protection::Block),
+        ProtectedPath::new("/usr", Protection::Block),
+        ProtectedPath::new("/etc", Protection::Confirm),
+        ProtectedPath::new("/mnt/*", Protection::Confirm),
+        ProtectedPath::new("/media/*/*", Protection::Confirm),
+    ]
+}
+
+/// `path` with its parent folder's symlinks resolved, so a link into a
+/// protected folder can't be used to get around the rules. The last
+/// component stays as it is because removing a link leaves its target alone.
+fn resolved(path: &Path) -> Option<PathBuf> {
+    let parent = path.parent()?.canonicalize().ok()?;
+    Some(match path.file_name() {
+        Some(name) => parent.join(name),
+        None => parent,
+    })
+}
+
+/// The strictest rule any of `paths` falls under, with the protected folder;
+/// among rules that are equally strict the first match wins. Each path is
+/// matched as given and with its parent resolved.
+///
+/// Permission changes aren't covered: the explorer has no chmod command, and
+/// commands typed into the built-in terminal are never checked.
+pub fn check(rules: &[ProtectedPath], paths: &[PathBuf]) -> Option<(Protection, PathBuf)> {
+    let mut found: Option<(Protection, PathBuf)> = None;
+    let candidates = paths.iter().flat_map(|path| [Some(path.clone()), resolved(path)]).flatten();
+    for path in candidates {
+        for rule in rules {
+            if let Some(folder) = rule.matching_folder(&path) {
+                if rule.protection == Protection::Block {
+                    return Some((Protection::Block, folder));
+                }
+                found.get_or_insert((Protection::Confirm, folder));
+            }
+        }
+    }
+    found
+}
+
+/// What to type to confirm a change under `folder`.
+pub fn confirmation_text(folder: &Path) -> String {
+    match folder.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => folder.display().to_string(),
+    }
+}
+
+/// A command held back until the protected folder's name is typed.
+pub struct PendingConfirmation {
+    pub command: Command,
+    pub folder: PathBuf,
+    /// Commands that depend on `command` and run once the dialog is closed.
+    pub then: Vec<Command>,
+    typed: String,
+}
+
+impl PendingConfirmation {
+    pub fn new(command: Command, folder: PathBuf) -> Self {
+        Self { command, folder, then: Vec::new(), typed: String::new() }
+    }
+}
+
+pub fn show_confirmation_window(app: &mut FileExplorerApp, ctx: &Context) {
+    let Some(pending) = &mut app.pending_confirmation else { return };
+
+    let expected = confirmation_text(&pending.folder);
+    let mut open = true;
+    let mut confirmed = false;
+    let mut cancelled = false;
+    egui::Window::new("🛡 Protected folder")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(format!("This changes files in {}, which is protected.", pending.folder.display()));
+            ui.label(RichText::new(format!("Type \"{}\" to go ahead:", expected)).strong());
+            let response = ui.text_edit_singleline(&mut pending.typed);
+            let matches = pending.typed == expected;
+            if !pending.typed.is_empty() && !matches {
+                ui.colored_label(Color32::RED, "The name doesn't match");
+            }
+            ui.horizontal(|ui| {
+                let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.add_enabled(matches, egui::Button::new("Continue")).clicked() || (enter && matches) {
+                    confirmed = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancelled = true;
+                }
+            });
+        });
+
+    if !(confirmed || cancelled || !open) {
+        return;
+    }
+    if let Some(pending) = app.pending_confirmation.take() {
+        if confirmed {
+            app.run_command(pending.command);
+        }
+        // Follow-up commands go through their own checks either way
+        app.execute_all(pending.then);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    fn check_one(rules: &[ProtectedPath], path: &str) -> Option<(Protection, PathBuf)> {
+        check(rules, &[PathBuf::from(path)])
+    }
+
+    #[test]
+    fn defaults_block_system_folders_and_confirm_mounts() {
+        let rules = default_protected_paths();
+        assert_eq!(check_one(&rules, "/boot/vmlinuz"), Some((Protection::Block, PathBuf::from("/boot"))));
+        assert_eq!(check_one(&rules, "/usr/bin/ls"), Some((Protection::Block, PathBuf::from("/usr"))));
+        assert_eq!(check_one(&rules, "/etc/fstab"), Some((Protection::Confirm, PathBuf::from("/etc"))));
+        assert_eq!(check_one(&rules, "/mnt/disk/a.txt"), Some((Protection::Confirm, PathBuf::from("/mnt/disk"))));
+        assert_eq!(check_one(&rules, "/media/me/usb/a.txt"), Some((Protection::Confirm, PathBuf::from("/media/me/usb"))));
+        assert_eq!(check_one(&rules, "/home/me/a.txt"), None);
+        // Only the prefix matters, not names that merely start alike
+        assert_eq!(check_one(&rules, "/usrdata/a.txt"), None);
+    }
+
+    #[test]
+    fn globs_match_at_their_own_depth() {
+        let rules = default_protected_paths();
+        assert_eq!(check_one(&rules, "/mnt"), None);
+        assert_eq!(check_one(&rules, "/mnt/disk"), Some((Protection::Confirm, PathBuf::from("/mnt/disk"))));
+        assert_eq!(check_one(&rules, "/media/me"), None);
+        assert_eq!(check_one(&rules, "/media/me/usb/deep/er"), Some((Protection::Confirm, PathBuf::from("/media/me/usb"))));
+    }
+
+    #[test]
+    fn the_strictest_rule_wins_then_the_first() {
+        let rules = vec![
+            ProtectedPath::new("/data", Protection::Confirm),
+            ProtectedPath::new("/data/*", Protection::Confirm),
+            ProtectedPath::new("/data/secret", Protection::Block),
+        ];
+        assert_eq!(check_one(&rules, "/data/notes/a.txt"), Some((Protection::Confirm, PathBuf::from("/data"))));
+        assert_eq!(check_one(&rules, "/data/secret/a.txt"), Some((Protection::Block, PathBuf::from("/data/secret"))));
+        let paths = [PathBuf::from("/data/notes"), PathBuf::from("/data/secret/a.txt")];
+        assert_eq!(check(&rules, &paths), Some((Protection::Block, PathBuf::from("/data/secret"))));
+    }
+
+    #[test]
+    fn paths_that_dont_exist_yet_are_matched_as_given() {
+        let rules = default_protected_paths();
+        assert_eq!(check_one(&rules, "/usr/no/such/folder/new.txt"), Some((Protection::Block, PathBuf::from("/usr"))));
+        assert_eq!(check_one(&rules, "/etc/new.conf"), Some((Protection::Confirm, PathBuf::from("/etc"))));
+    }
+
+    #[test]
+    fn links_into_protected_folders_are_resolved() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("system");
+        symlink("/usr", &link).unwrap();
+        let rules = default_protected_paths();
+
+        assert_eq!(resolved(&link.join("new.txt")), Some(PathBuf::from("/usr/new.txt")));
+        let inside = [link.join("new.txt")];
+        assert_eq!(check(&rules, &inside), Some((Protection::Block, PathBuf::from("/usr"))));
+        // Removing the link itself leaves /usr alone
+        assert_eq!(resolved(&link), Some(dir.path().canonicalize().unwrap().join("system")));
+        assert_eq!(check(&rules, &[link]), None);
+    }
+}
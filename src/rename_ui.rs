@@ -0,0 +1,195 @@
+use eframe::egui::{self, Color32, Context, RichText, ScrollArea};
+use fileexp_core::models::FileEntry;
+use fileexp_core::rename::{CaseChange, RenameRules};
+use fileexp_core::Command;
+use crate::app::FileExplorerApp;
+
+/// Window that renames several files at once from find/replace, case,
+/// insert/remove, numbering and date rules, with a live preview.
+pub struct BatchRenamer {
+    pub show: bool,
+    pub rules: RenameRules,
+    entries: Vec<FileEntry>,
+    change_extension: bool,
+    new_extension: String,
+}
+
+impl BatchRenamer {
+    pub fn new() -> Self {
+        Self {
+            show: false,
+            rules: RenameRules::default(),
+            entries: Vec::new(),
+            change_extension: false,
+            new_extension: String::new(),
+        }
+    }
+
+    /// Opens the window for `entries`, keeping the rules of the last use.
+    pub fn open(&mut self, entries: Vec<FileEntry>) {
+        self.entries = entries;
+        self.show = true;
+    }
+}
+
+fn case_label(case: CaseChange) -> &'static str {
+    match case {
+        CaseChange::Keep => "Keep",
+        CaseChange::Lower => "lower case",
+        CaseChange::Upper => "UPPER CASE",
+        CaseChange::Title => "Title Case",
+    }
+}
+
+fn show_rules(ui: &mut egui::Ui, renamer: &mut BatchRenamer) {
+    let rules = &mut renamer.rules;
+    egui::Grid::new("batch_rename_rules").num_columns(2).spacing([8.0, 6.0]).show(ui, |ui| {
+        ui.label("Find:");
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut rules.find);
+            ui.checkbox(&mut rules.use_regex, "Regular expression");
+        });
+        ui.end_row();
+
+        ui.label("Replace with:");
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut rules.replace);
+            if rules.use_regex {
+                ui.label(RichText::new("$1, ${name} insert groups").weak());
+            }
+        });
+        ui.end_row();
+
+        ui.label("Remove:");
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut rules.remove_count).clamp_range(0..=255));
+            ui.label("characters at");
+            ui.add(egui::DragValue::new(&mut rules.remove_at).clamp_range(0..=255));
+        });
+        ui.end_row();
+
+        ui.label("Insert:");
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut rules.insert);
+            ui.label("at");
+            ui.add(egui::DragValue::new(&mut rules.insert_at).clamp_range(0..=255));
+        });
+        ui.end_row();
+
+        ui.label("");
+        ui.checkbox(&mut rules.from_end, "Count positions from the end of the name");
+        ui.end_row();
+
+        ui.label("Case:");
+        egui::ComboBox::from_id_source("batch_rename_case")
+            .selected_text(case_label(rules.case))
+            .show_ui(ui, |ui| {
+                for case in [CaseChange::Keep, CaseChange::Lower, CaseChange::Upper, CaseChange::Title] {
+                    ui.selectable_value(&mut rules.case, case, case_label(case));
+                }
+            });
+        ui.end_row();
+
+        ui.label("Template:");
+        ui.add(egui::TextEdit::singleline(&mut rules.template).hint_text("{name}"))
+            .on_hover_text("{name} the name so far, {n} or {n:3} a counter, {date} or {date:%Y%m%d} the modification date");
+        ui.end_row();
+
+        ui.label("Numbering:");
+        ui.horizontal(|ui| {
+            ui.label("start");
+            ui.add(egui::DragValue::new(&mut rules.start));
+            ui.label("step");
+            ui.add(egui::DragValue::new(&mut rules.step).clamp_range(1..=1000));
+        });
+        ui.end_row();
+
+        ui.label("Extension:");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut renamer.change_extension, "Change to");
+            ui.add_enabled(renamer.change_extension, egui::TextEdit::singleline(&mut renamer.new_extension).hint_text("empty removes it").desired_width(80.0));
+        });
+        ui.end_row();
+    });
+    rules.extension = renamer.change_extension.then(|| renamer.new_extension.clone());
+}
+
+pub fn show_batch_rename_window(app: &mut FileExplorerApp, ctx: &Context) {
+    if !app.batch_renamer.show {
+        return;
+    }
+
+    let mut open = true;
+    let mut apply = None;
+    let renamer = &mut app.batch_renamer;
+    egui::Window::new("✏️ Batch rename")
+        .open(&mut open)
+        .resizable(true)
+        .default_width(640.0)
+        .default_height(480.0)
+        .show(ctx, |ui| {
+            show_rules(ui, renamer);
+            ui.separator();
+
+            let preview = match renamer.rules.preview(&renamer.entries) {
+                Ok(preview) => preview,
+                Err(e) => {
+                    ui.colored_label(Color32::RED, format!("Invalid expression: {}", e));
+                    return;
+                }
+            };
+            let changed = preview.iter().filter(|row| !row.is_unchanged()).count();
+            let problems = preview.iter().filter(|row| !row.is_ok()).count();
+
+            ScrollArea::both().max_height(ui.available_height() - 40.0).show(ui, |ui| {
+                egui::Grid::new("batch_rename_preview").striped(true).num_columns(3).show(ui, |ui| {
+                    ui.label(RichText::new("Old name").strong());
+                    ui.label(RichText::new("New name").strong());
+                    ui.label("");
+                    ui.end_row();
+
+                    for row in &preview {
+                        let name = |path: &std::path::Path| path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                        ui.label(name(&row.from));
+                        match (&row.to, &row.collision) {
+                            (Err(e), _) => {
+                                ui.label("");
+                                ui.colored_label(Color32::RED, e);
+                            }
+                            (Ok(to), Some(collision)) => {
+                                ui.colored_label(Color32::RED, name(to));
+                                ui.colored_label(Color32::RED, format!("⚠ {}", collision));
+                            }
+                            (Ok(to), None) if row.is_unchanged() => {
+                                ui.label(RichText::new(name(to)).weak());
+                                ui.label(RichText::new("unchanged").weak());
+                            }
+                            (Ok(to), None) => {
+                                ui.label(RichText::new(name(to)).strong());
+                                ui.label("");
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let enabled = problems == 0 && changed > 0;
+                if ui.add_enabled(enabled, egui::Button::new(format!("Rename {} items", changed))).clicked() {
+                    let renames = preview.into_iter().filter_map(|row| Some((row.from, row.to.ok()?))).collect();
+                    apply = Some(renames);
+                }
+                if problems > 0 {
+                    ui.colored_label(Color32::RED, format!("{} names need fixing first", problems));
+                }
+            });
+        });
+    app.batch_renamer.show = open;
+
+    if let Some(renames) = apply {
+        app.batch_renamer.show = false;
+        app.execute(Command::RenameBatch(renames));
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use crate::tabs::Tab;
+
+const SESSION_KEY: &str = "session";
+
+/// Browsing state carried over between launches through eframe's storage.
+/// Window size and position are persisted by eframe itself.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub tabs: Vec<Tab>,
+    pub active_tab: usize,
+    pub terminal_height: f32,
+    pub maximized: bool,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            tabs: Vec::new(),
+            active_tab: 0,
+            terminal_height: 250.0,
+            maximized: false,
+        }
+    }
+}
+
+impl Session {
+    /// Loads the previous session, dropping tabs whose folder is gone.
+    pub fn restore(storage: Option<&dyn eframe::Storage>) -> Option<Self> {
+        let mut session: Session = eframe::get_value(storage?, SESSION_KEY)?;
+
+        let active_path = session.tabs.get(session.active_tab).map(|t| t.path.clone());
+        session.tabs.retain(|t| t.path.is_dir());
+        session.active_tab = active_path
+            .and_then(|path| session.tabs.iter().position(|t| t.path == path))
+            .unwrap_or(0);
+
+        for tab in &mut session.tabs {
+            if tab.history.is_empty() || tab.history_index >= tab.history.len() {
+                tab.history = vec![tab.path.clone()];
+                tab.history_index = 0;
+            }
+        }
+
+        Some(session)
+    }
+
+    pub fn store(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SESSION_KEY, self);
+    }
+}
@@ -1,10 +1,16 @@
 use serde::{Deserialize, Serialize};
 use eframe::egui::{self, Context};
 use crate::models::{Theme, ViewMode};
+use crate::protection::{self, Protection, ProtectedPath};
 use std::path::PathBuf;
 use std::fs;
+use std::sync::OnceLock;
+
+// Set once at startup by `--config DIR`
+static CONFIG_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
 
 #[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct AppSettings {
     // Appearance
     pub theme: Theme,
@@ -18,6 +24,8 @@ pub struct AppSettings {
     pub confirm_deletions: bool,
     pub auto_refresh: bool,
     pub remember_window_size: bool,
+    /// Double-clicking a zip or tar archive lists it like a folder.
+    pub browse_archives: bool,
     
     // Terminal
     pub terminal_font_family: String,
@@ -31,6 +39,11 @@ pub struct AppSettings {
     pub default_terminal: String,
     pub show_file_extensions: bool,
     pub natural_sort: bool,
+    pub preview_bulk_operations: bool,
+    pub editor_rename_trashes_deleted: bool,
+    pub protected_paths: Vec<ProtectedPath>,
+    // Read-only: nothing that changes files can run
+    pub safe_mode: bool,
     
     // Advanced
     pub enable_thumbnails: bool,
@@ -52,6 +65,7 @@ impl Default for AppSettings {
             confirm_deletions: true,
             auto_refresh: false,
             remember_window_size: true,
+            browse_archives: true,
             
             terminal_font_family: "JetBrains Mono".to_string(),
             terminal_font_size: 12.0,
@@ -63,6 +77,10 @@ impl Default for AppSettings {
             default_terminal: std::env::var("TERMINAL").unwrap_or_else(|_| "gnome-terminal".to_string()),
             show_file_extensions: true,
             natural_sort: true,
+            preview_bulk_operations: false,
+            editor_rename_trashes_deleted: false,
+            protected_paths: protection::default_protected_paths(),
+            safe_mode: false,
             
             enable_thumbnails: true,
             thumbnail_size: 64.0,
@@ -93,12 +111,28 @@ impl AppSettings {
     }
 
     fn get_config_path() -> PathBuf {
-        if let Some(config_dir) = dirs::config_dir() {
-            config_dir.join("fileexp").join("settings.json")
+        if CONFIG_DIR_OVERRIDE.get().is_some() || dirs::config_dir().is_some() {
+            Self::config_dir().join("settings.json")
         } else {
             PathBuf::from(".").join("fileexp_settings.json")
         }
     }
+
+    /// Directory holding settings and the other per-user state files.
+    pub fn config_dir() -> PathBuf {
+        if let Some(dir) = CONFIG_DIR_OVERRIDE.get() {
+            return dir.clone();
+        }
+        dirs::config_dir()
+            .map(|dir| dir.join("fileexp"))
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /// Uses `dir` instead of the platform config directory. Only the first
+    /// call has an effect, and it must happen before anything is loaded.
+    pub fn set_config_dir(dir: PathBuf) {
+        let _ = CONFIG_DIR_OVERRIDE.set(dir);
+    }
 }
 
 pub struct SettingsWindow {
@@ -164,10 +198,8 @@ impl SettingsWindow {
                 
                 // Bottom buttons
                 ui.horizontal(|ui| {
-                    if ui.button("💾 Save").clicked() {
-                        if let Err(e) = settings.save() {
-                            eprintln!("Failed to save settings: {}", e);
-                        }
+                    if ui.button("💾 Save").clicked() && let Err(e) = settings.save() {
+                        eprintln!("Failed to save settings: {}", e);
                     }
                     
                     if ui.button("🔄 Reset to Defaults").clicked() {
@@ -194,9 +226,10 @@ impl SettingsWindow {
         });
         
         ui.horizontal(|ui| {
-            ui.label("View Mode:");
+            ui.label("Default View Mode:");
             ui.selectable_value(&mut settings.view_mode, ViewMode::List, "📋 List");
             ui.selectable_value(&mut settings.view_mode, ViewMode::Grid, "⊞ Grid");
+            ui.selectable_value(&mut settings.view_mode, ViewMode::Columns, "▥ Columns");
         });
         
         ui.horizontal(|ui| {
@@ -227,8 +260,10 @@ impl SettingsWindow {
         ui.checkbox(&mut settings.show_hidden_files, "Show hidden files");
         ui.checkbox(&mut settings.double_click_to_open, "Double-click to open files");
         ui.checkbox(&mut settings.confirm_deletions, "Confirm file deletions");
+        ui.checkbox(&mut settings.safe_mode, "Safe mode (read-only, nothing can be changed)");
         ui.checkbox(&mut settings.auto_refresh, "Auto-refresh directory");
         ui.checkbox(&mut settings.remember_window_size, "Remember window size");
+        ui.checkbox(&mut settings.browse_archives, "Open zip and tar archives as folders");
         ui.checkbox(&mut settings.natural_sort, "Natural sorting (1, 2, 10 instead of 1, 10, 2)");
         ui.checkbox(&mut settings.follow_symlinks, "Follow symbolic links");
     }
@@ -266,6 +301,7 @@ impl SettingsWindow {
         ui.label("• Command history (Up/Down arrows)");
         ui.label("• Tab completion for files and commands");
         ui.label("• Built-in cd command");
+        ui.label("• Built-in z command to jump to frequently used directories");
         ui.label("• Real-time command output");
     }
 
@@ -283,6 +319,37 @@ impl SettingsWindow {
             ui.text_edit_singleline(&mut settings.default_terminal);
         });
         
+        ui.checkbox(&mut settings.preview_bulk_operations, "Preview pastes and deletes before running them");
+        ui.checkbox(&mut settings.editor_rename_trashes_deleted, "Rename in editor: move files whose lines were deleted to the trash");
+        
+        ui.separator();
+        ui.label("Protected paths:");
+        ui.label(egui::RichText::new("Deleting, moving or renaming in folders matching these globs asks to type the folder name, or is blocked.").weak());
+        let mut remove = None;
+        for (i, protected) in settings.protected_paths.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut protected.pattern);
+                egui::ComboBox::from_id_source(("protection", i))
+                    .selected_text(match protected.protection {
+                        Protection::Confirm => "Confirm",
+                        Protection::Block => "Block",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut protected.protection, Protection::Confirm, "Confirm");
+                        ui.selectable_value(&mut protected.protection, Protection::Block, "Block");
+                    });
+                if ui.small_button("🗑").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            settings.protected_paths.remove(i);
+        }
+        if ui.button("➕ Add path").clicked() {
+            settings.protected_paths.push(ProtectedPath::new("", Protection::Confirm));
+        }
+        
         ui.label("Supported Operations:");
         ui.label("• Copy, Cut, Paste");
         ui.label("• Create new files and folders");
@@ -310,10 +377,8 @@ impl SettingsWindow {
         ui.label(format!("Shell: {}", settings.terminal_shell_path));
         ui.label(format!("Editor: {}", settings.default_editor));
         
-        if ui.button("🗂 Open Config Directory").clicked() {
-            if let Some(parent) = AppSettings::get_config_path().parent() {
-                let _ = open::that(parent);
-            }
+        if ui.button("🗂 Open Config Directory").clicked() && let Some(parent) = AppSettings::get_config_path().parent() {
+            let _ = open::that(parent);
         }
     }
 } 
\ No newline at end of file
@@ -0,0 +1,258 @@
+use eframe::egui::{self, Color32, Context, RichText, ScrollArea, Ui};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::app::FileExplorerApp;
+use crate::models::Bookmark;
+use crate::operations;
+
+/// Drag-and-drop payload carrying the paths dragged out of the file list.
+pub struct FileDragPayload(pub Vec<PathBuf>);
+
+pub struct SidebarState {
+    pub show: bool,
+    pub expanded: HashSet<PathBuf>,
+    children: HashMap<PathBuf, Vec<PathBuf>>,
+    revealed_path: Option<PathBuf>,
+    scroll_pending: bool,
+    roots: Vec<(String, PathBuf)>,
+}
+
+enum SidebarAction {
+    Navigate(PathBuf),
+    Drop { sources: Vec<PathBuf>, target: PathBuf, copy: bool },
+}
+
+impl SidebarState {
+    pub fn new() -> Self {
+        Self {
+            show: true,
+            expanded: HashSet::new(),
+            children: HashMap::new(),
+            revealed_path: None,
+            scroll_pending: false,
+            roots: tree_roots(),
+        }
+    }
+
+    /// Forgets the cached listings so they are read again on the next frame.
+    pub fn refresh(&mut self) {
+        self.children.clear();
+        self.roots = tree_roots();
+    }
+
+    /// Drops the cached subfolders of `path` after its contents changed.
+    pub fn invalidate(&mut self, path: &Path) {
+        self.children.remove(path);
+    }
+
+    /// Expands every ancestor of `path` so it is visible in the tree.
+    fn reveal(&mut self, path: &Path) {
+        for ancestor in path.ancestors().skip(1) {
+            self.expanded.insert(ancestor.to_path_buf());
+        }
+        self.revealed_path = Some(path.to_path_buf());
+        self.scroll_pending = true;
+    }
+
+    fn children_of(&mut self, path: &Path, show_hidden: bool) -> &[PathBuf] {
+        self.children.entry(path.to_path_buf()).or_insert_with(|| list_subdirectories(path, show_hidden))
+    }
+}
+
+pub fn list_subdirectories(path: &Path, show_hidden: bool) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = match fs::read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_ok_and(|ft| ft.is_dir()))
+            .filter(|e| show_hidden || !e.file_name().to_string_lossy().starts_with('.'))
+            .map(|e| e.path())
+            .collect(),
+        // Folders inside an archive
+        Err(_) => operations::read_directory(&path.to_path_buf(), show_hidden)
+            .map(|entries| entries.into_iter().filter(|e| e.is_dir).map(|e| e.path).collect())
+            .unwrap_or_default(),
+    };
+    dirs.sort();
+    dirs
+}
+
+fn tree_roots() -> Vec<(String, PathBuf)> {
+    let mut roots = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        roots.push(("🏠 Home".to_string(), home));
+    }
+    roots.push(("💻 File System".to_string(), PathBuf::from("/")));
+    for mount in mounted_volumes() {
+        let name = mount.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        roots.push((format!("💾 {}", name), mount));
+    }
+    roots
+}
+
+/// Mount points of real block devices, skipping the root and boot partitions.
+fn mounted_volumes() -> Vec<PathBuf> {
+    let Ok(mounts) = fs::read_to_string("/proc/mounts") else {
+        return Vec::new();
+    };
+
+    let mut volumes = Vec::new();
+    for line in mounts.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(device), Some(mount_point)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        // Spaces and tabs in mount points are octal-escaped
+        let mount_point = mount_point.replace("\\040", " ").replace("\\011", "\t");
+        let is_removable = ["/media/", "/mnt/", "/run/media/"].iter().any(|p| mount_point.starts_with(p));
+        let is_system = mount_point == "/" || mount_point.starts_with("/boot");
+        if (device.starts_with("/dev/") || is_removable) && !is_system {
+            let path = PathBuf::from(mount_point);
+            if !volumes.contains(&path) {
+                volumes.push(path);
+            }
+        }
+    }
+    volumes
+}
+
+fn places() -> Vec<(&'static str, Option<PathBuf>)> {
+    vec![
+        ("🖥 Desktop", dirs::desktop_dir()),
+        ("📄 Documents", dirs::document_dir()),
+        ("⬇ Downloads", dirs::download_dir()),
+        ("🖼 Pictures", dirs::picture_dir()),
+        ("🎵 Music", dirs::audio_dir()),
+        ("🎬 Videos", dirs::video_dir()),
+    ]
+}
+
+pub fn show_sidebar(app: &mut FileExplorerApp, ctx: &Context) {
+    if !app.sidebar.show {
+        return;
+    }
+
+    if app.sidebar.revealed_path.as_ref() != Some(&app.explorer.current_path) {
+        let current_path = app.explorer.current_path.clone();
+        app.sidebar.reveal(&current_path);
+    }
+
+    let mut actions = Vec::new();
+    let bookmarks = app.bookmarks.items.clone();
+    let current_path = app.explorer.current_path.clone();
+    let show_hidden = app.settings.show_hidden_files;
+
+    egui::SidePanel::left("sidebar")
+        .resizable(true)
+        .default_width(220.0)
+        .show(ctx, |ui| {
+            ScrollArea::vertical().show(ui, |ui| {
+                ui.collapsing(RichText::new("Places").strong(), |ui| {
+                    for (label, path) in places() {
+                        if let Some(path) = path.filter(|p| p.is_dir()) {
+                            show_drop_target(ui, label, &path, path == current_path, &mut actions);
+                        }
+                    }
+                });
+
+                if !bookmarks.is_empty() {
+                    ui.collapsing(RichText::new("Bookmarks").strong(), |ui| {
+                        for Bookmark { name, path } in &bookmarks {
+                            show_drop_target(ui, &format!("⭐ {}", name), path, *path == current_path, &mut actions);
+                        }
+                    });
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("Folders").strong());
+                    if ui.small_button("🔄").on_hover_text("Reload folder tree").clicked() {
+                        app.sidebar.refresh();
+                    }
+                });
+
+                let roots = app.sidebar.roots.clone();
+                for (label, root) in &roots {
+                    show_tree_node(ui, &mut app.sidebar, label, root, &current_path, show_hidden, &mut actions);
+                }
+            });
+        });
+
+    for action in actions {
+        match action {
+            SidebarAction::Navigate(path) => app.navigate_to(path),
+            SidebarAction::Drop { sources, target, copy } => app.drop_paths(&sources, &target, copy),
+        }
+    }
+}
+
+fn show_tree_node(
+    ui: &mut Ui,
+    state: &mut SidebarState,
+    label: &str,
+    path: &Path,
+    current_path: &Path,
+    show_hidden: bool,
+    actions: &mut Vec<SidebarAction>,
+) {
+    let expanded = state.expanded.contains(path);
+
+    ui.horizontal(|ui| {
+        let arrow = if expanded { "⏷" } else { "⏵" };
+        if ui.add(egui::Button::new(arrow).small().frame(false)).clicked() {
+            if expanded {
+                state.expanded.remove(path);
+            } else {
+                state.expanded.insert(path.to_path_buf());
+            }
+        }
+
+        let is_current = path == current_path;
+        let response = show_drop_target(ui, label, path, is_current, actions);
+        if is_current && state.scroll_pending {
+            response.scroll_to_me(None);
+            state.scroll_pending = false;
+        }
+    });
+
+    if expanded {
+        let children = state.children_of(path, show_hidden).to_vec();
+        ui.indent(path, |ui| {
+            for child in &children {
+                let name = child.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                show_tree_node(ui, state, &format!("📁 {}", name), child, current_path, show_hidden, actions);
+            }
+        });
+    }
+}
+
+/// A clickable folder label that also accepts files dragged from the file list.
+/// Dropping moves the files; holding Ctrl copies them instead.
+fn show_drop_target(
+    ui: &mut Ui,
+    label: &str,
+    path: &Path,
+    is_current: bool,
+    actions: &mut Vec<SidebarAction>,
+) -> egui::Response {
+    let response = ui.selectable_label(is_current, label);
+
+    if response.clicked() {
+        actions.push(SidebarAction::Navigate(path.to_path_buf()));
+    }
+
+    if response.dnd_hover_payload::<FileDragPayload>().is_some() {
+        ui.painter().rect_stroke(response.rect, 2.0, (1.5, Color32::from_rgb(0, 150, 0)));
+    }
+
+    if let Some(payload) = response.dnd_release_payload::<FileDragPayload>() {
+        let copy = ui.input(|i| i.modifiers.ctrl);
+        actions.push(SidebarAction::Drop {
+            sources: payload.0.clone(),
+            target: path.to_path_buf(),
+            copy,
+        });
+    }
+
+    response
+}
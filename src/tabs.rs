@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use crate::models::{SortBy, ViewMode};
+
+/// Browsing state of one tab. The active tab is mirrored by the live fields on
+/// `FileExplorerApp` and written back here when another tab is activated.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Tab {
+    pub path: PathBuf,
+    pub history: Vec<PathBuf>,
+    pub history_index: usize,
+    pub selected_paths: Vec<PathBuf>,
+    pub filter: String,
+    pub sort_by: SortBy,
+    pub sort_ascending: bool,
+    pub view_mode: ViewMode,
+}
+
+impl Tab {
+    pub fn new(path: PathBuf, view_mode: ViewMode) -> Self {
+        Self {
+            history: vec![path.clone()],
+            history_index: 0,
+            path,
+            selected_paths: Vec::new(),
+            filter: String::new(),
+            sort_by: SortBy::Name,
+            sort_ascending: true,
+            view_mode,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        self.path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.to_string_lossy().to_string())
+    }
+}
+
+/// Drag-and-drop payload used when reordering tabs.
+pub struct TabDragPayload(pub usize);
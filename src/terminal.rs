@@ -2,7 +2,12 @@ use std::io::{BufRead, BufReader};
 use std::process::{Command, Stdio};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 use std::collections::VecDeque;
+use crate::frecency::FrecencyDb;
+use crate::notifications::{Level, Notifier};
+use fileexp_core::audit::{AuditLog, AuditRecord};
+use crate::utils;
 
 #[derive(Clone)]
 pub struct TerminalState {
@@ -12,14 +17,21 @@ pub struct TerminalState {
     pub history_index: usize,
     pub current_dir: std::path::PathBuf,
     pub is_running_command: bool,
-    pub shell_path: String,
     pub autocomplete_suggestions: Vec<String>,
     pub show_autocomplete: bool,
+    pub frecency: Arc<Mutex<FrecencyDb>>,
+    pub panel_height: f32,
+    // Reports the end of commands that ran for a while
+    pub notifier: Option<Notifier>,
+    // Every external command is recorded here
+    pub audit: Option<AuditLog>,
 }
 
+// Commands running at least this long post a notice when they finish
+const LONG_COMMAND: Duration = Duration::from_secs(5);
+
 impl TerminalState {
-    pub fn new() -> Self {
-        let shell_path = std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string());
+    pub fn new(frecency: Arc<Mutex<FrecencyDb>>) -> Self {
         let current_dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("/"));
         
         Self {
@@ -29,9 +41,12 @@ impl TerminalState {
             history_index: 0,
             current_dir,
             is_running_command: false,
-            shell_path,
             autocomplete_suggestions: Vec::new(),
             show_autocomplete: false,
+            frecency,
+            panel_height: 250.0,
+            notifier: None,
+            audit: None,
         }
     }
 
@@ -64,6 +79,12 @@ impl TerminalState {
             return;
         }
 
+        if command == "z" || command.starts_with("z ") {
+            let query = command.strip_prefix('z').unwrap().trim();
+            self.z_internal(query);
+            return;
+        }
+
         // Execute external command
         self.execute_external_command(command);
     }
@@ -94,6 +115,28 @@ impl TerminalState {
         }
     }
 
+    fn z_internal(&mut self, query: &str) {
+        // Without a query, list the highest ranked directories instead of jumping
+        if query.is_empty() {
+            let now = chrono::Local::now().timestamp();
+            let db = self.frecency.lock().unwrap();
+            let mut output = self.output_lines.lock().unwrap();
+            for entry in db.query("", &self.current_dir).iter().take(10) {
+                output.push_back(format!("{:>8.1}  {}", entry.score(now), entry.path.display()));
+            }
+            return;
+        }
+
+        let best = self.frecency.lock().unwrap().best_match(query, &self.current_dir);
+        match best {
+            Some(path) => self.cd_internal(&path.to_string_lossy()),
+            None => {
+                let mut output = self.output_lines.lock().unwrap();
+                output.push_back(format!("z: no match found for '{}'", query));
+            }
+        }
+    }
+
     pub fn change_directory(&mut self, path: &str) {
         self.cd_internal(path);
     }
@@ -102,10 +145,18 @@ impl TerminalState {
         let output_lines = Arc::clone(&self.output_lines);
         let current_dir = self.current_dir.clone();
         let command_string = command.to_string();
+        let notifier = self.notifier.clone();
+        let audit = self.audit.clone();
 
         self.is_running_command = true;
 
         thread::spawn(move || {
+            let started = Instant::now();
+            let record = |result| {
+                if let Some(audit) = &audit {
+                    let _ = audit.record(&AuditRecord::command(&command_string, &current_dir, result));
+                }
+            };
             let result = Command::new("sh")
                 .arg("-c")
                 .arg(&command_string)
@@ -156,16 +207,26 @@ impl TerminalState {
                                 let mut output = output_lines.lock().unwrap();
                                 output.push_back(format!("Command exited with status: {}", status));
                             }
+                            record(if status.success() { Ok(()) } else { Err(status.to_string()) });
+                            if let Some(notifier) = notifier.filter(|_| started.elapsed() >= LONG_COMMAND) {
+                                if status.success() {
+                                    notifier.post(Level::Info, format!("Command finished: {}", command_string));
+                                } else {
+                                    notifier.post(Level::Warning, format!("Command failed ({}): {}", status, command_string));
+                                }
+                            }
                         }
                         Err(e) => {
                             let mut output = output_lines.lock().unwrap();
                             output.push_back(format!("Failed to wait for command: {}", e));
+                            record(Err(e.to_string()));
                         }
                     }
                 }
                 Err(e) => {
                     let mut output = output_lines.lock().unwrap();
                     output.push_back(format!("Failed to execute command: {}", e));
+                    record(Err(e.to_string()));
                 }
             }
         });
@@ -190,45 +251,12 @@ impl TerminalState {
             }
         }
 
-        // Get file/directory suggestions
-        let (path_part, file_part) = if let Some(space_pos) = input.rfind(' ') {
-            let args = &input[space_pos + 1..];
-            if let Some(slash_pos) = args.rfind('/') {
-                (&args[..slash_pos + 1], &args[slash_pos + 1..])
-            } else {
-                ("", args)
-            }
-        } else {
-            ("", input)
-        };
-
-        let search_dir = if path_part.is_empty() {
-            self.current_dir.clone()
-        } else if path_part.starts_with('/') {
-            std::path::PathBuf::from(path_part)
-        } else {
-            self.current_dir.join(path_part)
+        // Get file/directory suggestions for the word under the cursor
+        let word = match input.rfind(' ') {
+            Some(space_pos) => &input[space_pos + 1..],
+            None => input,
         };
-
-        if let Ok(entries) = std::fs::read_dir(&search_dir) {
-            for entry in entries.flatten() {
-                if let Some(name) = entry.file_name().to_str() {
-                    if name.starts_with(file_part) {
-                        let suggestion = if path_part.is_empty() {
-                            name.to_string()
-                        } else {
-                            format!("{}{}", path_part, name)
-                        };
-                        
-                        if entry.file_type().map_or(false, |ft| ft.is_dir()) {
-                            suggestions.push(format!("{}/", suggestion));
-                        } else {
-                            suggestions.push(suggestion);
-                        }
-                    }
-                }
-            }
-        }
+        suggestions.extend(utils::complete_path(&self.current_dir, word, false));
 
         suggestions.sort();
         suggestions.truncate(10); // Limit to 10 suggestions
@@ -3,10 +3,10 @@ use crate::terminal::TerminalState;
 use crate::settings::AppSettings;
 
 pub fn show_terminal_panel(ctx: &Context, terminal: &mut TerminalState, settings: &AppSettings) {
-    egui::TopBottomPanel::bottom("terminal_panel")
+    let panel = egui::TopBottomPanel::bottom("terminal_panel")
         .resizable(true)
         .min_height(100.0)
-        .default_height(250.0)
+        .default_height(terminal.panel_height)
         .show(ctx, |ui| {
             // Terminal header
             ui.horizontal(|ui| {
@@ -58,6 +58,7 @@ pub fn show_terminal_panel(ctx: &Context, terminal: &mut TerminalState, settings
             // Terminal input area
             show_terminal_input(ui, terminal, settings);
         });
+    terminal.panel_height = panel.response.rect.height();
 }
 
 fn show_terminal_input(ui: &mut egui::Ui, terminal: &mut TerminalState, _settings: &AppSettings) {
@@ -82,8 +83,9 @@ fn show_terminal_input(ui: &mut egui::Ui, terminal: &mut TerminalState, _setting
             handle_terminal_input_events(ui, terminal, &response);
         }
         
-        // Auto-focus the input
-        if !response.has_focus() && !terminal.is_running_command {
+        // Auto-focus the input unless another text field is being edited
+        let nothing_focused = ui.memory(|m| m.focused().is_none());
+        if nothing_focused && !terminal.is_running_command {
             response.request_focus();
         }
     });
@@ -102,39 +104,32 @@ fn handle_terminal_input_events(
     let events = ui.input(|i| i.events.clone());
     
     for event in events {
-        match event {
-            egui::Event::Key { key, pressed: true, modifiers, .. } => {
-                match key {
-                    egui::Key::Enter => {
-                        if !terminal.input_buffer.trim().is_empty() {
-                            execute_command(terminal);
-                        }
-                    }
-                    egui::Key::Tab => {
-                        handle_tab_completion(terminal);
-                    }
-                    egui::Key::ArrowUp => {
-                        terminal.navigate_history(-1);
-                    }
-                    egui::Key::ArrowDown => {
-                        terminal.navigate_history(1);
-                    }
-                    egui::Key::C if modifiers.ctrl => {
-                        // Ctrl+C - interrupt current command (if running)
-                        if terminal.is_running_command {
-                            terminal.is_running_command = false;
-                            let mut output = terminal.output_lines.lock().unwrap();
-                            output.push_back("^C".to_string());
-                        }
-                    }
-                    egui::Key::L if modifiers.ctrl => {
-                        // Ctrl+L - clear terminal
-                        terminal.clear_output();
-                    }
-                    _ => {}
+        if let egui::Event::Key { key, pressed: true, modifiers, .. } = event {
+            match key {
+                egui::Key::Enter if !terminal.input_buffer.trim().is_empty() => {
+                    execute_command(terminal);
+                }
+                egui::Key::Tab => {
+                    handle_tab_completion(terminal);
+                }
+                egui::Key::ArrowUp => {
+                    terminal.navigate_history(-1);
+                }
+                egui::Key::ArrowDown => {
+                    terminal.navigate_history(1);
                 }
+                egui::Key::C if modifiers.ctrl && terminal.is_running_command => {
+                    // Ctrl+C - interrupt current command (if running)
+                    terminal.is_running_command = false;
+                    let mut output = terminal.output_lines.lock().unwrap();
+                    output.push_back("^C".to_string());
+                }
+                egui::Key::L if modifiers.ctrl => {
+                    // Ctrl+L - clear terminal
+                    terminal.clear_output();
+                }
+                _ => {}
             }
-            _ => {}
         }
     }
 }
@@ -183,7 +178,7 @@ fn show_autocomplete_popup(ui: &mut egui::Ui, terminal: &mut TerminalState) {
         
         ScrollArea::vertical().show(ui, |ui| {
             let suggestions = terminal.autocomplete_suggestions.clone();
-            for (_i, suggestion) in suggestions.iter().enumerate() {
+            for suggestion in suggestions.iter() {
                 let is_dir = suggestion.ends_with('/');
                 let icon = if is_dir { "📁" } else { "📄" };
                 
@@ -204,15 +199,3 @@ fn show_autocomplete_popup(ui: &mut egui::Ui, terminal: &mut TerminalState) {
         ui.label(RichText::new("Press Tab to cycle, Esc to cancel").small());
     });
 }
-
-pub fn show_terminal_shortcuts_help(ui: &mut egui::Ui) {
-    ui.collapsing("Terminal Shortcuts", |ui| {
-        ui.label("• Enter: Execute command");
-        ui.label("• Tab: Auto-complete");
-        ui.label("• ↑/↓: Navigate history");
-        ui.label("• Ctrl+C: Interrupt command");
-        ui.label("• Ctrl+L: Clear terminal");
-        ui.label("• cd <path>: Change directory");
-        ui.label("• Built-in commands: cd, pwd, ls, clear");
-    });
-} 
\ No newline at end of file
@@ -1,70 +1,98 @@
 use eframe::egui::{self, Color32, Context, RichText, ScrollArea, Ui};
 use crate::app::FileExplorerApp;
-use crate::models::ViewMode;
+use crate::models::{PaneSide, SortBy, ViewMode};
+use crate::tabs::TabDragPayload;
+use crate::sidebar::{self, FileDragPayload};
+use crate::columns;
+use crate::picker;
+use crate::notifications;
 use crate::utils::{format_file_size, get_file_icon};
 
+// How long a back/forward button has to be held before its history menu opens
+const LONG_PRESS_SECONDS: f64 = 0.5;
+const MAX_HISTORY_MENU_ITEMS: usize = 15;
+
 pub fn show_top_panel(app: &mut FileExplorerApp, ctx: &Context) {
     egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+        show_tab_bar(app, ui);
+        
         // Navigation row
         ui.horizontal(|ui| {
-            // Back/Forward buttons
-            ui.add_enabled(app.history_index > 0, egui::Button::new("⬅")).clicked().then(|| app.go_back());
-            ui.add_enabled(app.history_index < app.navigation_history.len() - 1, egui::Button::new("➡")).clicked().then(|| app.go_forward());
+            // Back/Forward buttons; hold or right-click for the history list
+            let back = ui.add_enabled(app.explorer.history.index > 0, egui::Button::new("⬅"));
+            let back_entries: Vec<usize> = (0..app.explorer.history.index).rev().collect();
+            if show_history_button(app, ui, &back, "back_history", &back_entries) {
+                app.go_back();
+            }
+            let forward = ui.add_enabled(app.explorer.history.index < app.explorer.history.entries.len() - 1, egui::Button::new("➡"));
+            let forward_entries: Vec<usize> = (app.explorer.history.index + 1..app.explorer.history.entries.len()).collect();
+            if show_history_button(app, ui, &forward, "forward_history", &forward_entries) {
+                app.go_forward();
+            }
             
-            if ui.button("⬆ Up").clicked() {
-                if let Some(parent) = app.current_path.parent() {
-                    app.navigate_to(parent.to_path_buf());
-                }
+            if ui.button("⬆ Up").clicked() && let Some(parent) = app.explorer.current_path.parent() {
+                app.navigate_to(parent.to_path_buf());
             }
             
             ui.separator();
             
-            // Breadcrumb navigation - collect paths first to avoid borrow issues
-            let breadcrumbs = app.breadcrumbs.clone();
-            for (i, (name, path)) in breadcrumbs.iter().enumerate() {
-                if i > 0 {
-                    ui.label("/");
-                }
-                if ui.link(name).clicked() {
-                    app.navigate_to(path.clone());
-                }
-            }
-            
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                notifications::show_drawer_button(app, ui);
+                
                 if ui.button("⚙ Settings").clicked() {
                     app.settings_window.show = true;
                 }
                 
+                if ui.selectable_label(app.settings.safe_mode, "🔒 Safe mode").on_hover_text("Read-only: nothing can be created, moved, renamed or deleted").clicked() {
+                    app.settings.safe_mode = !app.settings.safe_mode;
+                }
+                
+                if ui.button("🕘 Session").on_hover_text("Changes made in this session, exportable as a shell script").clicked() {
+                    app.journal.show = !app.journal.show;
+                }
+                
+                if ui.button("📜 Audit log").clicked() {
+                    app.audit_viewer.open(&app.audit);
+                }
+                
                 if ui.button("⭐ Bookmarks").clicked() {
                     app.show_bookmarks = !app.show_bookmarks;
                 }
+                
+                if ui.selectable_label(app.sidebar.show, "🌲 Sidebar").on_hover_text("Toggle folder tree (Ctrl+B)").clicked() {
+                    app.sidebar.show = !app.sidebar.show;
+                }
+                
+                ui.separator();
+                
+                // Breadcrumbs or path field fill the space left of the buttons
+                ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                    show_path_bar(app, ui);
+                });
             });
         });
         
-        // Action buttons row
-        ui.horizontal(|ui| {
-            if ui.button("📄 New File").clicked() {
-                app.show_new_file_dialog = true;
-                app.new_name_input.clear();
-            }
-            
-            if ui.button("📁 New Folder").clicked() {
-                app.show_new_folder_dialog = true;
-                app.new_name_input.clear();
-            }
-            
-            ui.separator();
-            
-            ui.label("View:");
-            ui.selectable_value(&mut app.settings.view_mode, ViewMode::List, "📋 List");
-            ui.selectable_value(&mut app.settings.view_mode, ViewMode::Grid, "⊞ Grid");
-            
-            ui.separator();
-            
-            if ui.button("🔄 Refresh").clicked() {
-                app.read_directory();
-            }
-        });
+        // Path completion candidates
+        if app.path_bar_editing && !app.path_bar_suggestions.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                let suggestions = app.path_bar_suggestions.clone();
+                for suggestion in &suggestions {
+                    let name = suggestion.trim_end_matches('/').rsplit('/').next().unwrap_or(suggestion);
+                    if ui.small_button(format!("📁 {}", name)).clicked() {
+                        app.path_bar_text = suggestion.clone();
+                        app.path_bar_suggestions.clear();
+                        app.path_bar_focus_pending = true;
+                    }
+                }
+            });
+        }
+        
+        // Action buttons row, or Open/Save buttons when picking files
+        if app.picker.is_some() {
+            picker::show_picker_bar(app, ui);
+        } else {
+            show_action_row(app, ui);
+        }
 
         // Bookmarks panel
         if app.show_bookmarks {
@@ -73,13 +101,13 @@ pub fn show_top_panel(app: &mut FileExplorerApp, ctx: &Context) {
                 ui.label("Add bookmark:");
                 ui.text_edit_singleline(&mut app.bookmark_name_input);
                 if ui.button("Add").clicked() && !app.bookmark_name_input.is_empty() {
-                    app.add_bookmark(app.bookmark_name_input.clone(), app.current_path.clone());
+                    app.add_bookmark(app.bookmark_name_input.clone(), app.explorer.current_path.clone());
                     app.bookmark_name_input.clear();
                 }
             });
             
             // Clone bookmarks to avoid borrow issues
-            let bookmarks = app.bookmarks.clone();
+            let bookmarks = app.bookmarks.items.clone();
             let mut bookmark_to_remove = None;
             ui.horizontal_wrapped(|ui| {
                 for (i, bookmark) in bookmarks.iter().enumerate() {
@@ -93,28 +121,361 @@ pub fn show_top_panel(app: &mut FileExplorerApp, ctx: &Context) {
             });
             
             if let Some(index) = bookmark_to_remove {
-                app.bookmarks.remove(index);
-                app.save_bookmarks();
+                app.remove_bookmark(index);
             }
         }
 
+    });
+}
+
+fn show_action_row(app: &mut FileExplorerApp, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        if ui.button("📄 New File").clicked() {
+            app.show_new_file_dialog = true;
+            app.new_name_input.clear();
+        }
+        
+        if ui.button("📁 New Folder").clicked() {
+            app.show_new_folder_dialog = true;
+            app.new_name_input.clear();
+        }
+        
+        ui.separator();
+        
+        ui.label("View:");
+        ui.selectable_value(&mut app.view_mode, ViewMode::List, "📋 List");
+        ui.selectable_value(&mut app.view_mode, ViewMode::Grid, "⊞ Grid");
+        ui.selectable_value(&mut app.view_mode, ViewMode::Columns, "▥ Columns");
+        
+        ui.separator();
+        
+        ui.label("Sort:");
+        let previous_sort = (app.explorer.sort_by, app.explorer.sort_ascending);
+        egui::ComboBox::from_id_source("sort_by")
+            .selected_text(format!("{:?}", app.explorer.sort_by))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut app.explorer.sort_by, SortBy::Name, "Name");
+                ui.selectable_value(&mut app.explorer.sort_by, SortBy::Size, "Size");
+                ui.selectable_value(&mut app.explorer.sort_by, SortBy::Modified, "Modified");
+                ui.selectable_value(&mut app.explorer.sort_by, SortBy::Type, "Type");
+            });
+        if ui.button(if app.explorer.sort_ascending { "⬆" } else { "⬇" }).clicked() {
+            app.explorer.sort_ascending = !app.explorer.sort_ascending;
+        }
+        if previous_sort != (app.explorer.sort_by, app.explorer.sort_ascending) {
+            app.read_directory();
+        }
+        
+        ui.label("Filter:");
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut app.explorer.filter)
+                .hint_text("name contains")
+                .desired_width(120.0)
+        );
+        if response.changed() {
+            app.read_directory();
+        }
+        
+        ui.separator();
+        
+        if ui.button("🔄 Refresh").clicked() {
+            app.read_directory();
+            if app.dual_pane {
+                app.refresh_other_pane();
+            }
+        }
+        
+        if ui.selectable_label(app.dual_pane, "◫ Dual Pane").on_hover_text("Commander mode (Ctrl+D)").clicked() {
+            app.toggle_dual_pane();
+        }
+        
         ui.separator();
+        
+        // Frecency jump box, same ranking as the terminal's `z` builtin
+        ui.label("Jump:");
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut app.jump_input)
+                .hint_text("z query")
+                .desired_width(160.0)
+        );
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) && !app.jump_input.trim().is_empty() {
+            let query = app.jump_input.trim().to_string();
+            app.jump_to(&query);
+            app.jump_input.clear();
+        }
+    });
+}
+
+/// Handles a back/forward button. A plain click returns true; holding the
+/// button or right-clicking it opens a menu of the history `entries` instead.
+fn show_history_button(app: &mut FileExplorerApp, ui: &mut Ui, response: &egui::Response, id: &str, entries: &[usize]) -> bool {
+    let popup_id = ui.make_persistent_id(id);
+    let popup_open = ui.memory(|m| m.is_popup_open(popup_id));
+    
+    let long_press = response.is_pointer_button_down_on()
+        && ui.input(|i| i.pointer.press_start_time().is_some_and(|t| i.time - t > LONG_PRESS_SECONDS));
+    if response.is_pointer_button_down_on() {
+        ui.ctx().request_repaint_after(std::time::Duration::from_secs_f64(LONG_PRESS_SECONDS));
+    }
+    if (long_press || response.secondary_clicked()) && !popup_open {
+        ui.memory_mut(|m| m.open_popup(popup_id));
+    }
+    
+    egui::popup_below_widget(ui, popup_id, response, |ui| {
+        ui.set_min_width(200.0);
+        for &index in entries.iter().take(MAX_HISTORY_MENU_ITEMS) {
+            let path = &app.explorer.history.entries[index];
+            let label = path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string_lossy().to_string());
+            if ui.button(format!("📁 {}", label)).on_hover_text(path.display().to_string()).clicked() {
+                app.go_to_history(index);
+                ui.memory_mut(|m| m.close_popup());
+            }
+        }
+    });
+    
+    // Releasing a long press must not also count as a click
+    response.clicked() && !popup_open && !long_press
+}
+
+fn show_path_bar(app: &mut FileExplorerApp, ui: &mut Ui) {
+    if !app.path_bar_editing {
+        show_breadcrumbs(app, ui);
+        
+        // Clicking the empty part of the bar switches to typing a path
+        let rest = ui.allocate_response(ui.available_size(), egui::Sense::click())
+            .on_hover_cursor(egui::CursorIcon::Text)
+            .on_hover_text("Click or press Ctrl+L to type a path");
+        if rest.clicked() {
+            app.start_path_edit();
+        }
+        return;
+    }
+    
+    let id = egui::Id::new("path_bar_input");
+    let response = ui.add(
+        egui::TextEdit::singleline(&mut app.path_bar_text)
+            .id(id)
+            .lock_focus(true)
+            .hint_text("Path, ~/dir, $VAR/dir or file:// URI")
+            .desired_width(ui.available_width())
+    );
+    
+    let mut move_cursor_to_end = false;
+    if app.path_bar_focus_pending {
+        response.request_focus();
+        app.path_bar_focus_pending = false;
+        move_cursor_to_end = true;
+    }
+    
+    if response.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Tab)) {
+        app.complete_path_bar();
+        move_cursor_to_end = true;
+    }
+    
+    if move_cursor_to_end && let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), id) {
+        let end = egui::text::CCursor::new(app.path_bar_text.chars().count());
+        state.cursor.set_char_range(Some(egui::text::CCursorRange::one(end)));
+        state.store(ui.ctx(), id);
+    }
+    
+    if response.lost_focus() {
+        if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            app.submit_path_bar();
+        } else {
+            app.cancel_path_edit();
+        }
+    }
+}
+
+/// Draws the breadcrumbs, collapsing middle segments into an overflow menu when
+/// they don't fit. Every crumb is followed by a dropdown of its subfolders.
+fn show_breadcrumbs(app: &mut FileExplorerApp, ui: &mut Ui) {
+    let breadcrumbs = app.breadcrumbs.clone();
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    let spacing = ui.spacing().item_spacing.x;
+    let separator_width = ui.spacing().interact_size.x;
+    let crumb_widths: Vec<f32> = breadcrumbs.iter()
+        .map(|(name, _)| {
+            let text_width = ui.fonts(|f| f.layout_no_wrap(name.clone(), font_id.clone(), Color32::WHITE).size().x);
+            text_width + separator_width + 2.0 * spacing
+        })
+        .collect();
+    
+    // Keep the first crumb and as many trailing ones as fit, hiding the rest
+    let available = ui.available_width() - separator_width - 40.0;
+    let mut hidden = 0..0;
+    let mut total: f32 = crumb_widths.iter().sum();
+    if total > available && breadcrumbs.len() > 2 {
+        total += separator_width;
+        let mut end = 1;
+        while end < breadcrumbs.len() - 1 && total > available {
+            total -= crumb_widths[end];
+            end += 1;
+        }
+        hidden = 1..end;
+    }
+    
+    for (i, (name, path)) in breadcrumbs.iter().enumerate() {
+        if hidden.contains(&i) {
+            if i == hidden.start {
+                ui.menu_button("…", |ui| {
+                    for (name, path) in &breadcrumbs[hidden.clone()] {
+                        if ui.button(format!("📁 {}", name)).clicked() {
+                            app.navigate_to(path.clone());
+                            ui.close_menu();
+                        }
+                    }
+                }).response.on_hover_text("Show hidden path segments");
+            }
+            continue;
+        }
+        
+        if ui.link(name).clicked() {
+            app.navigate_to(path.clone());
+        }
+        
+        // The separator lists this level's subfolders for quick sibling jumps
+        let show_hidden = app.settings.show_hidden_files;
+        let next = breadcrumbs.get(i + 1).map(|(_, p)| p.clone());
+        ui.menu_button("›", |ui| {
+            let subdirs = sidebar::list_subdirectories(path, show_hidden);
+            if subdirs.is_empty() {
+                ui.label(RichText::new("No subfolders").weak());
+            }
+            ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for dir in subdirs {
+                    let name = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                    let on_path = next.as_ref() == Some(&dir);
+                    if ui.selectable_label(on_path, format!("📁 {}", name)).clicked() {
+                        app.navigate_to(dir);
+                        ui.close_menu();
+                    }
+                }
+            });
+        });
+    }
+}
 
-        // Status messages
-        if let Some(error_message) = &app.error {
-            ui.colored_label(Color32::RED, error_message);
+fn show_tab_bar(app: &mut FileExplorerApp, ui: &mut Ui) {
+    let mut switch_to = None;
+    let mut close = None;
+    let mut reorder = None;
+    
+    ui.horizontal(|ui| {
+        for i in 0..app.tabs.len() {
+            let title = format!("📁 {}", app.tab_title(i));
+            let response = ui.add(egui::SelectableLabel::new(i == app.active_tab, title))
+                .interact(egui::Sense::drag());
+            
+            // Tabs are reordered by dragging one onto another
+            response.dnd_set_drag_payload(TabDragPayload(i));
+            if let Some(payload) = response.dnd_release_payload::<TabDragPayload>() {
+                reorder = Some((payload.0, i));
+            }
+            
+            if response.clicked() {
+                switch_to = Some(i);
+            }
+            if response.middle_clicked() {
+                close = Some(i);
+            }
+            if app.tabs.len() > 1 && ui.small_button("✖").on_hover_text("Close tab (Ctrl+W)").clicked() {
+                close = Some(i);
+            }
+            ui.separator();
+        }
+        
+        if ui.button("➕").on_hover_text("New tab (Ctrl+T)").clicked() {
+            app.open_tab(app.explorer.current_path.clone(), true);
+        }
+    });
+    
+    if let Some((from, to)) = reorder {
+        app.move_tab(from, to);
+    } else if let Some(index) = close {
+        app.close_tab(index);
+    } else if let Some(index) = switch_to {
+        app.switch_tab(index);
+    }
+    
+    ui.separator();
+}
+
+pub fn show_dual_pane(app: &mut FileExplorerApp, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        if ui.button("📋 F5 Copy").clicked() {
+            app.transfer_to_other_pane(false);
+        }
+        if ui.button("✂️ F6 Move").clicked() {
+            app.transfer_to_other_pane(true);
+        }
+        ui.separator();
+        if ui.button("⇄ Swap").on_hover_text("Swap panes (Ctrl+U)").clicked() {
+            app.swap_panes();
+        }
+        if ui.button("⇉ Sync").on_hover_text("Open this directory in the other pane (Alt+O)").clicked() {
+            app.sync_panes();
         }
+        ui.label(RichText::new("Tab switches panes").weak());
+    });
+    ui.separator();
+    
+    let mut focus_other = false;
+    ui.columns(2, |columns| {
+        let (left, right) = columns.split_at_mut(1);
+        let (active_ui, other_ui) = match app.active_side {
+            PaneSide::Left => (&mut left[0], &mut right[0]),
+            PaneSide::Right => (&mut right[0], &mut left[0]),
+        };
+        
+        show_pane_header(active_ui, &app.explorer.current_path.display().to_string(), true);
+        show_file_list(app, active_ui);
+        
+        show_pane_header(other_ui, &app.other_pane.path.display().to_string(), false);
+        focus_other = show_other_pane(app, other_ui);
+    });
+    
+    if focus_other {
+        app.switch_pane();
+    }
+}
 
-        if let Some(status_message) = &app.status_message {
-            ui.colored_label(Color32::from_rgb(0, 150, 0), status_message);
+fn show_pane_header(ui: &mut Ui, path: &str, focused: bool) {
+    let text = RichText::new(format!("📁 {}", path)).strong();
+    if focused {
+        ui.label(text.color(ui.visuals().selection.stroke.color).background_color(ui.visuals().selection.bg_fill));
+    } else {
+        ui.label(text.weak());
+    }
+    ui.separator();
+}
+
+/// Read-only listing of the unfocused pane. Returns true when it was clicked.
+fn show_other_pane(app: &FileExplorerApp, ui: &mut Ui) -> bool {
+    let mut clicked = false;
+    ScrollArea::vertical().id_source("other_pane").show(ui, |ui| {
+        for entry in &app.other_entries {
+            let selected = app.other_pane.selected_paths.contains(&entry.path);
+            let label = format!("{} {}", get_file_icon(entry), entry.name);
+            if ui.selectable_label(selected, label).clicked() {
+                clicked = true;
+            }
         }
+        
+        // Clicking the blank area below the entries focuses the pane too
+        let rest = ui.allocate_response(ui.available_size(), egui::Sense::click());
+        clicked |= rest.clicked();
     });
+    clicked
 }
 
 pub fn show_file_list(app: &mut FileExplorerApp, ui: &mut Ui) {
-    match app.settings.view_mode {
+    match app.view_mode {
         ViewMode::List => show_list_view(app, ui),
         ViewMode::Grid => show_grid_view(app, ui),
+        ViewMode::Columns => columns::show_columns_view(app, ui),
     }
 }
 
@@ -131,14 +492,16 @@ fn show_list_view(app: &mut FileExplorerApp, ui: &mut Ui) {
         ui.separator();
         
         // Clone entries to avoid borrow issues
-        let entries = app.entries.clone();
+        let entries = app.explorer.entries.clone();
         let ctx = ui.ctx().clone();
         for (i, entry) in entries.iter().enumerate() {
             let response = ui.horizontal(|ui| {
                 let icon = get_file_icon(entry);
-                let selected = app.selected_entries.contains(&i);
+                let selected = app.explorer.selection.contains(i);
                 
-                let response = ui.selectable_label(selected, format!("{} {}", icon, entry.name));
+                let response = ui.selectable_label(selected, format!("{} {}", icon, entry.name))
+                    .interact(egui::Sense::drag());
+                set_file_drag_payload(app, &response, i);
                 ui.separator();
                 
                 if entry.is_dir {
@@ -162,17 +525,19 @@ fn show_grid_view(app: &mut FileExplorerApp, ui: &mut Ui) {
     ScrollArea::vertical().show(ui, |ui| {
         ui.horizontal_wrapped(|ui| {
             // Clone entries to avoid borrow issues
-            let entries = app.entries.clone();
+            let entries = app.explorer.entries.clone();
             let ctx = ui.ctx().clone();
             for (i, entry) in entries.iter().enumerate() {
-                let icon = get_file_icon(&entry);
-                let selected = app.selected_entries.contains(&i);
+                let icon = get_file_icon(entry);
+                let selected = app.explorer.selection.contains(i);
                 
                 let response = ui.vertical(|ui| {
                     ui.set_max_width(80.0);
                     ui.set_min_height(80.0);
                     
-                    let response = ui.selectable_label(selected, RichText::new(icon).size(32.0));
+                    let response = ui.selectable_label(selected, RichText::new(icon).size(32.0))
+                        .interact(egui::Sense::drag());
+                    set_file_drag_payload(app, &response, i);
                     ui.label(&entry.name);
                     
                     response
@@ -184,6 +549,18 @@ fn show_grid_view(app: &mut FileExplorerApp, ui: &mut Ui) {
     });
 }
 
+/// Starts dragging the selection, or just this entry if it isn't selected.
+fn set_file_drag_payload(app: &FileExplorerApp, response: &egui::Response, index: usize) {
+    if response.drag_started() {
+        let paths = if app.explorer.selection.contains(index) {
+            app.selected_paths()
+        } else {
+            vec![app.explorer.entries[index].path.clone()]
+        };
+        response.dnd_set_drag_payload(FileDragPayload(paths));
+    }
+}
+
 pub fn show_dialogs(app: &mut FileExplorerApp, ctx: &Context) {
     // Properties dialog
     if app.show_properties_dialog {
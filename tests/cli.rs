@@ -0,0 +1,119 @@
+//! Runs the headless subcommands against temporary folders.
+
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// Runs `fileexp args...` with its config and trash inside `home`.
+fn fileexp(home: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fileexp"))
+        .args(args)
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("XDG_DATA_HOME", home.join("data"))
+        .output()
+        .unwrap()
+}
+
+/// The `--json` output, one value per line.
+fn events(output: &Output) -> Vec<Value> {
+    String::from_utf8_lossy(&output.stdout).lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+}
+
+/// A home folder with `a.txt`, `b.txt` and an empty `out/` in `work/`.
+fn setup() -> (TempDir, String) {
+    let home = tempfile::tempdir().unwrap();
+    let work = home.path().join("work");
+    fs::create_dir_all(work.join("out")).unwrap();
+    fs::write(work.join("a.txt"), "a").unwrap();
+    fs::write(work.join("b.txt"), "b").unwrap();
+    let work = work.to_string_lossy().to_string();
+    (home, work)
+}
+
+#[test]
+fn copy_reports_each_item_and_a_summary() {
+    let (home, work) = setup();
+    let output = fileexp(home.path(), &["cp", "--json", &format!("{work}/a.txt"), &format!("{work}/b.txt"), &format!("{work}/out")]);
+    assert_eq!(output.status.code(), Some(0));
+
+    let events = events(&output);
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0]["event"], "item");
+    assert_eq!(events[0]["op"], "copy");
+    assert_eq!(events[0]["status"], "done");
+    assert_eq!(events[1]["index"], 2);
+    assert_eq!(events[1]["destination"], format!("{work}/out/b.txt"));
+    assert_eq!(events[2]["event"], "summary");
+    assert_eq!(events[2]["succeeded"], 2);
+    assert_eq!(fs::read_to_string(format!("{work}/out/a.txt")).unwrap(), "a");
+    assert!(Path::new(&format!("{work}/a.txt")).exists());
+}
+
+#[test]
+fn some_failures_exit_with_3() {
+    let (home, work) = setup();
+    let output = fileexp(home.path(), &["mv", "--json", &format!("{work}/a.txt"), &format!("{work}/missing"), &format!("{work}/out")]);
+    assert_eq!(output.status.code(), Some(3));
+
+    let events = events(&output);
+    assert_eq!(events[0]["status"], "done");
+    assert_eq!(events[1]["status"], "failed");
+    assert!(events[1]["error"].is_string());
+    assert_eq!(events[2]["failed"], 1);
+    assert!(!Path::new(&format!("{work}/a.txt")).exists());
+    assert!(Path::new(&format!("{work}/out/a.txt")).exists());
+}
+
+#[test]
+fn only_failures_exit_with_1() {
+    let (home, work) = setup();
+    fs::write(format!("{work}/out/a.txt"), "taken").unwrap();
+    let output = fileexp(home.path(), &["mv", &format!("{work}/a.txt"), &format!("{work}/out")]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(fs::read_to_string(format!("{work}/out/a.txt")).unwrap(), "taken");
+    assert!(Path::new(&format!("{work}/a.txt")).exists());
+}
+
+#[test]
+fn bad_arguments_exit_with_2() {
+    let (home, work) = setup();
+    let output = fileexp(home.path(), &["cp", &format!("{work}/a.txt"), &format!("{work}/b.txt"), &format!("{work}/new")]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is not a directory"));
+    assert!(!Path::new(&format!("{work}/new")).exists());
+}
+
+#[test]
+fn conflicts_follow_the_policy() {
+    let (home, work) = setup();
+    fs::write(format!("{work}/out/a.txt"), "taken").unwrap();
+    let output = fileexp(home.path(), &["cp", "--conflict", "rename", &format!("{work}/a.txt"), &format!("{work}/out")]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(fs::read_to_string(format!("{work}/out/a.txt")).unwrap(), "taken");
+    assert_eq!(fs::read_to_string(format!("{work}/out/a (2).txt")).unwrap(), "a");
+
+    let output = fileexp(home.path(), &["cp", "--json", "--conflict", "skip", &format!("{work}/a.txt"), &format!("{work}/out")]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(events(&output)[0]["status"], "skipped");
+}
+
+#[test]
+fn trash_moves_into_the_data_folder() {
+    let (home, work) = setup();
+    let output = fileexp(home.path(), &["trash", "--json", &format!("{work}/a.txt")]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let events = events(&output);
+    let trashed = home.path().join("data/Trash/files/a.txt");
+    assert_eq!(events[0]["op"], "trash");
+    assert_eq!(events[0]["destination"], trashed.to_string_lossy().as_ref());
+    assert_eq!(fs::read_to_string(&trashed).unwrap(), "a");
+    assert!(home.path().join("data/Trash/info/a.txt.trashinfo").exists());
+    assert!(!Path::new(&format!("{work}/a.txt")).exists());
+
+    let output = fileexp(home.path(), &["trash", &format!("{work}/missing")]);
+    assert_eq!(output.status.code(), Some(1));
+}